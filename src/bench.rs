@@ -0,0 +1,466 @@
+//! Timing a day's parsing and each of its parts over many iterations, and
+//! comparing the timings with a saved baseline.
+//!
+//! Allocations are counted by [`CountingAllocator`], which the `aoc2021`
+//! binary installs as its global allocator. Where it is not installed, as in
+//! tests, every allocation count is 0.
+//!
+//! A baseline is a TOML file with one `[[bench]]` table per day and phase:
+//!
+//! ```toml
+//! [[bench]]
+//! day = 15
+//! phase = "part2"
+//! median_ns = 61234000
+//! allocations = 1520
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use toml::Value;
+use crate::error::AocError;
+use crate::input;
+use crate::params::Params;
+use crate::solution::Day;
+use crate::verify;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+/**
+The system allocator, counting every allocation made through it.
+ */
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/**
+The number of allocations made so far through [`CountingAllocator`].
+ */
+pub fn allocations() -> u64 {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/**
+What was timed: parsing the input, or solving one part from the parsed
+input.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Phase {
+    Parse,
+    Part(u8),
+}
+
+impl Phase {
+    /**
+    The phase's name in a baseline file.
+
+    # Examples
+    ```
+    use aoc2021::bench::Phase;
+
+    assert_eq!("part2", Phase::Part(2).key());
+    assert_eq!(Some(Phase::Part(2)), Phase::from_key("part2"));
+    assert_eq!(Some(Phase::Parse), Phase::from_key("parse"));
+    assert_eq!(None, Phase::from_key("part3"));
+    ```
+     */
+    pub fn key(&self) -> String {
+        match self {
+            Phase::Parse => String::from("parse"),
+            Phase::Part(part) => format!("part{}", part),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Phase> {
+        match key {
+            "parse" => Some(Phase::Parse),
+            "part1" => Some(Phase::Part(1)),
+            "part2" => Some(Phase::Part(2)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Part(part) => write!(f, "part {}", part),
+        }
+    }
+}
+
+/**
+The timings of one phase over every iteration, and the fewest allocations
+it made in any iteration.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub allocations: u64,
+}
+
+impl Stats {
+    /**
+    Summarize the samples of a phase, which must not be empty. Percentiles
+    are taken by nearest rank.
+
+    # Examples
+    ```
+    use std::time::Duration;
+    use aoc2021::bench::Stats;
+
+    let times: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+    let stats = Stats::from_samples(&times, &[5, 3, 4]);
+    assert_eq!(Duration::from_millis(1), stats.min);
+    assert_eq!(Duration::from_millis(10), stats.median);
+    assert_eq!(Duration::from_millis(19), stats.p95);
+    assert_eq!(3, stats.allocations);
+    ```
+     */
+    pub fn from_samples(times: &[Duration], allocations: &[u64]) -> Stats {
+        let mut times = times.to_vec();
+        times.sort_unstable();
+        let percentile = |p: usize| times[(times.len() * p).div_ceil(100).max(1) - 1];
+        Stats {
+            min: times[0],
+            median: percentile(50),
+            p95: percentile(95),
+            allocations: allocations.iter().copied().min().unwrap_or(0),
+        }
+    }
+}
+
+/**
+The timings of one phase of a day.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub phase: Phase,
+    pub stats: Stats,
+}
+
+/**
+Time `f` once, counting the allocations it makes.
+ */
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Duration, u64) {
+    let allocations_before = allocations();
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    (result, elapsed, allocations() - allocations_before)
+}
+
+/**
+Parse the text of a day's input `iterations` times, then solve each part
+from the parsed input as many times, timing each phase. Any error parsing
+or solving stops the benchmark.
+
+# Examples
+```
+# #[cfg(feature = "day01")] {
+use aoc2021::bench::{bench_day, Phase};
+use aoc2021::params::Params;
+
+let registry = aoc2021::registry();
+let input = std::fs::read_to_string("test_inputs/day01.txt").unwrap();
+let measurements = bench_day(registry.get(1).unwrap(), &input, &Params::new(), 5).unwrap();
+let phases: Vec<Phase> = measurements.iter().map(|m| m.phase).collect();
+assert_eq!(vec![Phase::Parse, Phase::Part(1), Phase::Part(2)], phases);
+assert!(measurements.iter().all(|m| m.stats.min <= m.stats.median && m.stats.median <= m.stats.p95));
+
+assert!(bench_day(registry.get(1).unwrap(), "x\n", &Params::new(), 5).is_err());
+assert!(bench_day(registry.get(1).unwrap(), &input, &Params::new(), 0).is_err());
+# }
+```
+ */
+pub fn bench_day(day: &Day, input: &str, params: &Params, iterations: usize) -> Result<Vec<Measurement>, AocError> {
+    crate::params::at_least("iterations", iterations, 1)?;
+    let mut times = Vec::with_capacity(iterations);
+    let mut counts = Vec::with_capacity(iterations);
+    let mut parsed = None;
+    for _ in 0..iterations {
+        let (solution, time, count) = measure(|| day.parse_str(input));
+        times.push(time);
+        counts.push(count);
+        parsed = Some(solution?);
+    }
+    let mut solution = parsed.expect("there is at least one iteration");
+    solution.configure(params)?;
+    let mut measurements = vec![Measurement { day: day.day, phase: Phase::Parse, stats: Stats::from_samples(&times, &counts) }];
+    for part in 1..=solution.parts() {
+        times.clear();
+        counts.clear();
+        for _ in 0..iterations {
+            let (answer, time, count) = measure(|| day.solve_parsed(part, solution.as_ref()));
+            answer?;
+            times.push(time);
+            counts.push(count);
+        }
+        measurements.push(Measurement { day: day.day, phase: Phase::Part(part), stats: Stats::from_samples(&times, &counts) });
+    }
+    Ok(measurements)
+}
+
+/**
+What a baseline recorded for one phase of a day.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recorded {
+    pub median: Duration,
+    pub allocations: u64,
+}
+
+/**
+How a measurement compares with its baseline: the change in median time as
+a fraction of the baseline's, and the change in allocations.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Comparison {
+    pub change: f64,
+    pub extra_allocations: i64,
+    pub regressed: bool,
+}
+
+/**
+Earlier timings to compare against, by day and phase.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Baseline {
+    recorded: BTreeMap<(u8, Phase), Recorded>,
+}
+
+impl Baseline {
+    /**
+    Parse a baseline file.
+
+    # Examples
+    ```
+    use std::time::Duration;
+    use aoc2021::bench::{Baseline, Phase};
+
+    let baseline = Baseline::parse("[[bench]]\nday = 15\nphase = \"part2\"\nmedian_ns = 2000000\nallocations = 7\n").unwrap();
+    let recorded = baseline.get(15, Phase::Part(2)).unwrap();
+    assert_eq!(Duration::from_millis(2), recorded.median);
+    assert_eq!(7, recorded.allocations);
+    assert!(baseline.get(15, Phase::Parse).is_none());
+
+    let e = Baseline::parse("[[bench]]\nday = 15\nphase = \"part3\"\n").unwrap_err();
+    assert_eq!("line 1, column 1: unknown phase \"part3\"", e.to_string());
+    let text = "[[ bench ]]\nday = 1\nphase = \"parse\"\nmedian_ns = 1000\nallocations = 2\n\n\
+        [[\"bench\"]] # part 3 was never a phase\nday = 15\nphase = \"part3\"\n";
+    let e = Baseline::parse(text).unwrap_err();
+    assert_eq!("line 7, column 1: unknown phase \"part3\"", e.to_string());
+    ```
+     */
+    pub fn parse(text: &str) -> Result<Baseline, AocError> {
+        let value = verify::parse_toml(text)?;
+        let benches = match value.get("bench") {
+            Some(Value::Array(benches)) => benches,
+            Some(_) => return Err(AocError::parse(1, 1, "expected [[bench]] tables")),
+            None => return Ok(Baseline::default()),
+        };
+        let mut baseline = Baseline::default();
+        for (ndx, bench) in benches.iter().enumerate() {
+            let line = verify::table_line(text, "bench", ndx);
+            let error = |reason: String| AocError::parse(line, 1, reason);
+            let number = |name: &str| match bench.get(name) {
+                Some(Value::Integer(n)) if *n >= 0 => Ok(*n as u64),
+                Some(_) => Err(error(format!("\"{}\" must be a number", name))),
+                None => Err(error(format!("bench is missing \"{}\"", name))),
+            };
+            let phase = match bench.get("phase") {
+                Some(Value::String(phase)) => Phase::from_key(phase)
+                    .ok_or_else(|| error(format!("unknown phase \"{}\"", phase)))?,
+                _ => return Err(error(String::from("bench is missing \"phase\""))),
+            };
+            let day = u8::try_from(number("day")?).map_err(|_| error(String::from("day is out of range")))?;
+            let recorded = Recorded {
+                median: Duration::from_nanos(number("median_ns")?),
+                allocations: number("allocations")?,
+            };
+            baseline.recorded.insert((day, phase), recorded);
+        }
+        Ok(baseline)
+    }
+
+    pub fn load(file: &str) -> Result<Baseline, AocError> {
+        input::from_file(file, Baseline::parse)
+    }
+
+    pub fn get(&self, day: u8, phase: Phase) -> Option<&Recorded> {
+        self.recorded.get(&(day, phase))
+    }
+
+    /**
+    Record the measurements, replacing what was recorded for the same day
+    and phase.
+     */
+    pub fn record(&mut self, measurements: &[Measurement]) {
+        for m in measurements {
+            let recorded = Recorded { median: m.stats.median, allocations: m.stats.allocations };
+            self.recorded.insert((m.day, m.phase), recorded);
+        }
+    }
+
+    /**
+    The baseline as a TOML file.
+
+    # Examples
+    ```
+    use std::time::Duration;
+    use aoc2021::bench::{Baseline, Measurement, Phase, Stats};
+
+    let stats = Stats { min: Duration::ZERO, median: Duration::from_micros(3), p95: Duration::ZERO, allocations: 2 };
+    let mut baseline = Baseline::default();
+    baseline.record(&[Measurement { day: 1, phase: Phase::Parse, stats }]);
+    let text = baseline.to_toml();
+    assert_eq!("[[bench]]\nday = 1\nphase = \"parse\"\nmedian_ns = 3000\nallocations = 2\n", text);
+    assert_eq!(baseline, Baseline::parse(&text).unwrap());
+    ```
+     */
+    pub fn to_toml(&self) -> String {
+        self.recorded.iter()
+            .map(|((day, phase), recorded)| format!("[[bench]]\nday = {}\nphase = \"{}\"\nmedian_ns = {}\nallocations = {}\n",
+                day, phase.key(), recorded.median.as_nanos(), recorded.allocations))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn save(&self, file: &str) -> Result<(), AocError> {
+        fs::write(file, self.to_toml()).map_err(|e| AocError::io(file, e))
+    }
+
+    /**
+    Compare a measurement with the baseline. It has regressed if its median
+    time is more than `threshold` percent slower, or it makes more
+    allocations. Returns `None` when the baseline has nothing for its day
+    and phase.
+
+    # Examples
+    ```
+    use std::time::Duration;
+    use aoc2021::bench::{Baseline, Measurement, Phase, Stats};
+
+    let baseline = Baseline::parse("[[bench]]\nday = 1\nphase = \"parse\"\nmedian_ns = 1000\nallocations = 2\n").unwrap();
+    let stats = Stats { min: Duration::ZERO, median: Duration::from_nanos(1200), p95: Duration::ZERO, allocations: 2 };
+    let mut m = Measurement { day: 1, phase: Phase::Parse, stats };
+    let comparison = baseline.compare(&m, 10).unwrap();
+    assert!((comparison.change - 0.2).abs() < 1e-9);
+    assert!(comparison.regressed);
+    assert!(!baseline.compare(&m, 25).unwrap().regressed);
+    m.stats.allocations = 3;
+    assert!(baseline.compare(&m, 25).unwrap().regressed);
+    m.phase = Phase::Part(1);
+    assert!(baseline.compare(&m, 25).is_none());
+    ```
+     */
+    pub fn compare(&self, measurement: &Measurement, threshold: u32) -> Option<Comparison> {
+        let recorded = self.get(measurement.day, measurement.phase)?;
+        let before = recorded.median.as_secs_f64();
+        let after = measurement.stats.median.as_secs_f64();
+        let change = if before > 0.0 { after / before - 1.0 } else { 0.0 };
+        let extra_allocations = measurement.stats.allocations as i64 - recorded.allocations as i64;
+        let regressed = change * 100.0 > threshold as f64 || extra_allocations > 0;
+        Some(Comparison { change, extra_allocations, regressed })
+    }
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.3}", d.as_secs_f64() * 1000.0)
+}
+
+/**
+Print the measurements as a table, comparing each with the baseline if
+there is one. Returns whether nothing regressed.
+
+# Examples
+```
+use std::time::Duration;
+use aoc2021::bench::{write_report, Baseline, Measurement, Phase, Stats};
+
+let stats = Stats {
+    min: Duration::from_micros(900),
+    median: Duration::from_millis(1),
+    p95: Duration::from_micros(1500),
+    allocations: 12,
+};
+let measurements = [Measurement { day: 15, phase: Phase::Part(2), stats }];
+let mut out = Vec::new();
+assert!(write_report(&measurements, None, 10, &mut out).unwrap());
+let out = String::from_utf8(out).unwrap();
+assert_eq!("day phase      min ms  median ms     p95 ms   allocs", out.lines().next().unwrap());
+assert_eq!(" 15 part 2      0.900      1.000      1.500       12", out.lines().nth(1).unwrap());
+
+let baseline = Baseline::parse("[[bench]]\nday = 15\nphase = \"part2\"\nmedian_ns = 500000\nallocations = 12\n").unwrap();
+let mut out = Vec::new();
+assert!(!write_report(&measurements, Some(&baseline), 10, &mut out).unwrap());
+let out = String::from_utf8(out).unwrap();
+assert!(out.lines().nth(1).unwrap().ends_with("+100.0% REGRESSION"));
+assert_eq!("1 regression against the baseline", out.lines().last().unwrap());
+```
+ */
+pub fn write_report(
+    measurements: &[Measurement],
+    baseline: Option<&Baseline>,
+    threshold: u32,
+    out: &mut impl Write,
+) -> io::Result<bool> {
+    let mut header = format!("{:>3} {:<6} {:>10} {:>10} {:>10} {:>8}", "day", "phase", "min ms", "median ms", "p95 ms", "allocs");
+    if baseline.is_some() {
+        header += "  vs baseline";
+    }
+    writeln!(out, "{}", header)?;
+    let mut regressions = 0;
+    for m in measurements {
+        let mut row = format!("{:>3} {:<6} {:>10} {:>10} {:>10} {:>8}", m.day, m.phase.to_string(),
+            millis(m.stats.min), millis(m.stats.median), millis(m.stats.p95), m.stats.allocations);
+        match baseline.map(|b| b.compare(m, threshold)) {
+            Some(Some(comparison)) => {
+                row += &format!("  {:+.1}%", comparison.change * 100.0);
+                if comparison.extra_allocations != 0 {
+                    row += &format!(" {:+} allocs", comparison.extra_allocations);
+                }
+                if comparison.regressed {
+                    row += " REGRESSION";
+                    regressions += 1;
+                }
+            },
+            Some(None) => row += "  (not in baseline)",
+            None => (),
+        }
+        writeln!(out, "{}", row)?;
+    }
+    if baseline.is_some() {
+        let plural = if regressions == 1 { "" } else { "s" };
+        writeln!(out, "{} regression{} against the baseline", regressions, plural)?;
+    }
+    Ok(regressions == 0)
+}
@@ -0,0 +1,289 @@
+//! A cache of answers on disk, so slow days such as 19 and 23 are only
+//! solved once for each input.
+//!
+//! An answer is kept under its day, its part, and a SHA-256 digest of the
+//! input and any parameters, in a directory for this version of the crate:
+//! `DIR/aoc2021-0.1.0/day19-part1-<digest>.json`. Another version looks in
+//! its own directory, so answers never outlive the code that found them, and
+//! it removes the directories of other versions when it saves an answer.
+//! Only answers are kept, never errors.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use crate::error::AocError;
+use crate::params::Params;
+use crate::solution::Answer;
+
+/**
+The environment variable naming the cache directory, when no directory is
+given on the command line.
+ */
+pub const DIR_VAR: &str = "AOC2021_CACHE_DIR";
+
+/**
+The version of the crate whose answers this cache holds.
+ */
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/**
+The cache directory named by [`DIR_VAR`], if it is set.
+ */
+pub fn dir_from_env() -> Option<PathBuf> {
+    std::env::var_os(DIR_VAR).filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+/**
+What an answer is kept under: the day, the part, and the digest of the input
+and parameters.
+
+# Examples
+```
+use aoc2021::cache::Key;
+use aoc2021::params::Params;
+
+let key = Key::new(6, 1, "3,4,3,1,2\n", &Params::new());
+assert_eq!(key, Key::new(6, 1, "3,4,3,1,2\n", &Params::new()));
+assert_ne!(key, Key::new(6, 2, "3,4,3,1,2\n", &Params::new()));
+assert_ne!(key, Key::new(6, 1, "3,4,3,1,1\n", &Params::new()));
+let mut params = Params::new();
+params.add("part1_days=18").unwrap();
+assert_ne!(key, Key::new(6, 1, "3,4,3,1,2\n", &params));
+assert!(key.file_name().starts_with("day06-part1-"));
+```
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub day: u8,
+    pub part: u8,
+    digest: String,
+}
+
+impl Key {
+    pub fn new(day: u8, part: u8, input: &str, params: &Params) -> Key {
+        let mut hasher = Sha256::new();
+        hasher.update(input.as_bytes());
+        // the same parameters in any order give the same answer
+        let mut params: Vec<_> = params.iter().collect();
+        params.sort();
+        for (key, value) in params {
+            hasher.update(format!("\0{}={}", key, value).as_bytes());
+        }
+        let digest = hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect();
+        Key { day, part, digest }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("day{:02}-part{}-{}.json", self.day, self.part, self.digest)
+    }
+}
+
+/**
+A cached answer, with the number of parts its day has.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub answer: Answer,
+    pub parts: u8,
+}
+
+/**
+A directory of cached answers.
+
+# Examples
+```
+use aoc2021::cache::{Cache, Entry, Key};
+use aoc2021::params::Params;
+use aoc2021::solution::Answer;
+
+let dir = std::env::temp_dir().join(format!("aoc2021-cache-example-{}", std::process::id()));
+let cache = Cache::new(&dir);
+let key = Key::new(13, 2, "6,10\n\nfold along y=7\n", &Params::new());
+assert_eq!(None, cache.get(&key));
+
+let entry = Entry { answer: Answer::Lines(vec![String::from("#.#"), String::from(".#.")]), parts: 2 };
+cache.put(&key, &entry).unwrap();
+assert_eq!(Some(entry), cache.get(&key));
+assert_eq!(1, cache.clear().unwrap());
+assert_eq!(None, cache.get(&key));
+std::fs::remove_dir_all(&dir).unwrap();
+```
+ */
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Cache {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /**
+    The directory holding this version's answers.
+     */
+    fn version_dir(&self) -> PathBuf {
+        self.dir.join(format!("aoc2021-{}", VERSION))
+    }
+
+    /**
+    The cached answer for the key. An entry that cannot be read is taken
+    as missing, and is overwritten when the answer is next saved.
+     */
+    pub fn get(&self, key: &Key) -> Option<Entry> {
+        let text = fs::read_to_string(self.version_dir().join(key.file_name())).ok()?;
+        let value: Value = serde_json::from_str(&text).ok()?;
+        if value["day"] != json!(key.day) || value["part"] != json!(key.part) {
+            return None;
+        }
+        Some(Entry {
+            answer: decode(&value["answer"])?,
+            parts: value["parts"].as_u64().and_then(|n| u8::try_from(n).ok())?,
+        })
+    }
+
+    /**
+    Save an answer, and remove the answers of every other version.
+     */
+    pub fn put(&self, key: &Key, entry: &Entry) -> Result<(), AocError> {
+        let dir = self.version_dir();
+        self.remove_versions(|name| name != dir.file_name().unwrap_or_default())?;
+        fs::create_dir_all(&dir).map_err(|e| AocError::io(&dir.to_string_lossy(), e))?;
+        let value = json!({
+            "day": key.day,
+            "part": key.part,
+            "parts": entry.parts,
+            "answer": encode(&entry.answer),
+        });
+        // write then rename, so no reader ever sees half an entry
+        let file = dir.join(key.file_name());
+        let partial = dir.join(format!("{}.{}.tmp", key.file_name(), std::process::id()));
+        fs::write(&partial, value.to_string())
+            .and_then(|_| fs::rename(&partial, &file))
+            .map_err(|e| AocError::io(&file.to_string_lossy(), e))
+    }
+
+    /**
+    Remove every cached answer, of this version and any other, returning
+    how many there were. Nothing else in the directory is touched.
+     */
+    pub fn clear(&self) -> Result<usize, AocError> {
+        self.remove_versions(|_| true)
+    }
+
+    fn remove_versions(&self, remove: impl Fn(&std::ffi::OsStr) -> bool) -> Result<usize, AocError> {
+        let io_error = |path: &Path, e| AocError::io(&path.to_string_lossy(), e);
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(io_error(&self.dir, e)),
+        };
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry.map_err(|e| io_error(&self.dir, e))?.path();
+            let name = path.file_name().unwrap_or_default();
+            if !path.is_dir() || !name.to_string_lossy().starts_with("aoc2021-") || !remove(name) {
+                continue;
+            }
+            removed += fs::read_dir(&path).map_err(|e| io_error(&path, e))?
+                .filter(|file| file.as_ref().is_ok_and(|f| f.path().extension() == Some("json".as_ref())))
+                .count();
+            fs::remove_dir_all(&path).map_err(|e| io_error(&path, e))?;
+        }
+        Ok(removed)
+    }
+}
+
+/**
+An answer as JSON that, unlike [`Answer::to_json`], says which kind of
+answer it is, so it can be read back.
+ */
+fn encode(answer: &Answer) -> Value {
+    match answer {
+        Answer::Number(n) => json!({ "number": n.to_string() }),
+        Answer::Text(s) => json!({ "text": s }),
+        Answer::Lines(lines) => json!({ "lines": lines }),
+        Answer::Record(fields) => json!({
+            "record": fields.iter().map(|(name, value)| json!([name, encode(value)])).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+fn decode(value: &Value) -> Option<Answer> {
+    let string = |value: &Value| value.as_str().map(String::from);
+    let (kind, value) = value.as_object()?.iter().next()?;
+    match kind.as_str() {
+        "number" => value.as_str()?.parse().ok().map(Answer::Number),
+        "text" => string(value).map(Answer::Text),
+        "lines" => value.as_array()?.iter().map(string).collect::<Option<_>>().map(Answer::Lines),
+        "record" => value.as_array()?.iter()
+            .map(|field| match field.as_array()?.as_slice() {
+                [name, value] => Some((string(name)?, decode(value)?)),
+                _ => None,
+            })
+            .collect::<Option<_>>()
+            .map(Answer::Record),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> Cache {
+        let dir = std::env::temp_dir().join(format!("aoc2021-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir)
+    }
+
+    #[test]
+    fn test_answers_round_trip() {
+        let answers = [
+            Answer::Number(-7),
+            Answer::from(u128::MAX),
+            Answer::Text(String::from("CDRBLOBH")),
+            Answer::Lines(vec![String::from("#..#"), String::new()]),
+            Answer::Record(vec![
+                (String::from("depth"), Answer::Number(10)),
+                (String::from("path"), Answer::Lines(vec![String::from("start,end")])),
+            ]),
+        ];
+        for answer in answers {
+            assert_eq!(Some(answer.clone()), decode(&encode(&answer)));
+        }
+    }
+
+    #[test]
+    fn test_other_versions_are_removed() {
+        let cache = scratch("versions");
+        let old = cache.dir().join("aoc2021-0.0.1");
+        fs::create_dir_all(&old).unwrap();
+        fs::write(old.join("day19-part1-00.json"), "{}").unwrap();
+        fs::write(cache.dir().join("notes.txt"), "not ours").unwrap();
+
+        let key = Key::new(19, 1, "--- scanner 0 ---\n", &Params::new());
+        cache.put(&key, &Entry { answer: Answer::Number(79), parts: 2 }).unwrap();
+        assert!(!old.exists());
+        assert!(cache.dir().join("notes.txt").exists());
+        assert_eq!(Some(Answer::Number(79)), cache.get(&key).map(|entry| entry.answer));
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_unreadable_entry_is_missing() {
+        let cache = scratch("unreadable");
+        let key = Key::new(23, 2, "#############\n", &Params::new());
+        fs::create_dir_all(cache.version_dir()).unwrap();
+        fs::write(cache.version_dir().join(key.file_name()), "{\"day\":23,").unwrap();
+        assert_eq!(None, cache.get(&key));
+        cache.put(&key, &Entry { answer: Answer::Number(44169), parts: 2 }).unwrap();
+        assert_eq!(Some(Answer::Number(44169)), cache.get(&key).map(|entry| entry.answer));
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,691 @@
+//! The command line: parsing the arguments into a command, and running it.
+//!
+//! Options take their value as the next argument or after an `=`, as in
+//! `--day 15` or `--day=15`, and may come in any order. Flags such as
+//! `--trace` take no value.
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+use crate::bench::{self, Baseline};
+use crate::cache::{self, Cache};
+use crate::error::AocError;
+use crate::input;
+use crate::gen;
+use crate::params::Params;
+use crate::render::{self, ImageFormat};
+use crate::runner::{self, Format};
+use crate::solution::Registry;
+use crate::trace::{self, NoTrace};
+use crate::verify;
+
+/**
+The exit status when everything succeeded.
+ */
+pub const EXIT_OK: i32 = 0;
+
+/**
+The exit status when a puzzle could not be solved, or an answer was wrong.
+ */
+pub const EXIT_FAILED: i32 = 1;
+
+/**
+The exit status when the command line itself was wrong.
+ */
+pub const EXIT_USAGE: i32 = 2;
+
+pub const USAGE: &str = "\
+usage: aoc2021 solve --day DAY --part PART --input FILE [--param KEY=VALUE]... [--format text|json]
+                     [--trace] [--cache-dir DIR | --no-cache]
+       aoc2021 all --input-dir DIR [--format text|json] [--cache-dir DIR | --no-cache]
+       aoc2021 cache clear [--cache-dir DIR]
+       aoc2021 verify ANSWERS.toml
+       aoc2021 gen --day DAY [--size N] [--seed N]
+       aoc2021 bench --day DAY [--iterations K] [--input FILE] [--param KEY=VALUE]...
+                     [--baseline FILE] [--save-baseline FILE] [--threshold PERCENT]
+       aoc2021 render --day DAY --part PART --input FILE --out PATH [--image ascii|ppm|pbm|gif]
+                      [--scale N] [--delay CS] [--param KEY=VALUE]...
+       aoc2021 list
+       aoc2021 help
+
+Commands:
+  solve   solve one part of a day's puzzle; FILE may be - for stdin
+  all     solve every day on the dayNN.txt inputs in DIR, timing each part
+  cache   `cache clear` removes every cached answer, of any version, from DIR
+  verify  check answers against a TOML file of known answers
+  gen     print a random input for a day, for stress testing; the same seed
+          (0 by default) always gives the same input
+  bench   time parsing and each part over K iterations (10 by default), on
+          FILE or on a generated input, reporting min, median and 95th
+          percentile times and allocations; --baseline flags any phase more
+          than PERCENT (10 by default) slower than the baseline, or making
+          more allocations, and --save-baseline records this run
+  render  draw every step of the simulation on day 11, 13, 20 or 25 as a
+          numbered frame in the directory PATH, as text (the default) or as
+          PPM or PBM images with each cell N pixels wide (4 by default); or
+          as one animated GIF file PATH showing each frame for CS hundredths
+          of a second (10 by default)
+  list    list the days that can be solved
+  help    show this message
+
+`aoc2021 DAY PART FILE` is short for `aoc2021 solve --day DAY --part PART --input FILE`.
+
+--param overrides one of the puzzle's constants, such as --param tiles=3 on
+day 15; it may be given more than once. The parameters for each day are:
+  day  6  part1_days (80), part2_days (256), exact (false)
+  day 14  part1_steps (10), part2_steps (40), exact (false)
+  day 15  tiles (5)
+  day 19  overlap (12)
+  day 20  part1_steps (2), part2_steps (50)
+  day 21  board (10), practice_target (1000), dirac_target (21)
+  day 22  region (50)
+exact=true counts without overflowing, when built with the bigint feature.
+
+--trace writes every step of the simulation to stderr: the school of fish on
+day 6, the octopuses on day 11, the paper on day 13, the pair counts on day 14, the snailfish sum
+on day 18, the image on day 20 and the seafloor on day 25.
+
+--cache-dir keeps answers in DIR, and takes them from there when the same
+day, part, input and parameters come again with the same version of aoc2021.
+The directory may be given by the AOC2021_CACHE_DIR environment variable
+instead; --no-cache ignores it. --trace always solves the puzzle afresh.
+
+Exit status: 0 on success, 1 when a puzzle failed or an answer was wrong,
+2 when the command line was wrong.";
+
+/**
+A command given on the command line.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Solve { day: u8, part: u8, input: String, params: Params, format: Format, trace: bool, cache: Option<PathBuf> },
+    All { input_dir: PathBuf, format: Format, cache: Option<PathBuf> },
+    ClearCache { dir: PathBuf },
+    Verify { answers: String },
+    Gen { day: u8, size: Option<usize>, seed: u64 },
+    Bench {
+        day: u8,
+        iterations: usize,
+        input: Option<String>,
+        params: Params,
+        baseline: Option<String>,
+        save_baseline: Option<String>,
+        threshold: u32,
+    },
+    Render {
+        day: u8,
+        part: u8,
+        input: String,
+        params: Params,
+        output: PathBuf,
+        image: ImageFormat,
+        scale: usize,
+        delay: u16,
+    },
+    List { format: Format },
+    Help,
+}
+
+/**
+The options and positional arguments of one command.
+ */
+struct Arguments {
+    options: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+/**
+The options that take no value.
+ */
+const FLAGS: [&str; 2] = ["trace", "no-cache"];
+
+impl Arguments {
+    /**
+    Split the arguments into options, which must be among `known`, and
+    positional arguments.
+     */
+    fn scan(args: &[String], known: &[&str]) -> Result<Arguments, AocError> {
+        let mut options = Vec::new();
+        let mut positional = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix("--") {
+                Some(name) => name,
+                None => {
+                    positional.push(arg.clone());
+                    continue;
+                },
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, _)) if FLAGS.contains(&name) => {
+                    return Err(usage_error(format!("--{} takes no value", name)));
+                },
+                Some((name, value)) => (name, value.to_string()),
+                None if FLAGS.contains(&name) => (name, String::new()),
+                None => {
+                    if !known.contains(&name) {
+                        return Err(usage_error(format!("unknown option --{}", name)));
+                    }
+                    let value = args.next()
+                        .ok_or_else(|| usage_error(format!("--{} needs a value", name)))?;
+                    (name, value.clone())
+                },
+            };
+            if !known.contains(&name) {
+                return Err(usage_error(format!("unknown option --{}", name)));
+            }
+            options.push((name.to_string(), value));
+        }
+        Ok(Arguments { options, positional })
+    }
+
+    /**
+    The value of an option given at most once.
+     */
+    fn value(&self, name: &str) -> Result<Option<&str>, AocError> {
+        let mut values = self.options.iter().filter(|(n, _)| n == name);
+        let value = values.next().map(|(_, v)| v.as_str());
+        if values.next().is_some() {
+            return Err(usage_error(format!("--{} given more than once", name)));
+        }
+        Ok(value)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(n, _)| n == name)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, AocError> {
+        self.value(name)?.ok_or_else(|| usage_error(format!("missing --{}", name)))
+    }
+
+    fn format(&self) -> Result<Format, AocError> {
+        self.value("format")?.map_or(Ok(Format::Text), str::parse)
+    }
+
+    /**
+    Every `--param`, which unlike other options may be repeated.
+     */
+    fn params(&self) -> Result<Params, AocError> {
+        let mut params = Params::new();
+        for (_, param) in self.options.iter().filter(|(n, _)| n == "param") {
+            params.add(param)?;
+        }
+        Ok(params)
+    }
+
+    /**
+    The cache directory from `--cache-dir` or the environment, unless
+    `--no-cache` turns the cache off.
+     */
+    fn cache_dir(&self) -> Result<Option<PathBuf>, AocError> {
+        let dir = self.value("cache-dir")?;
+        if self.flag("no-cache") {
+            if dir.is_some() {
+                return Err(usage_error("--cache-dir and --no-cache cannot be given together"));
+            }
+            return Ok(None);
+        }
+        Ok(dir.map(PathBuf::from).or_else(cache::dir_from_env))
+    }
+
+    fn no_positional(&self) -> Result<(), AocError> {
+        match self.positional.first() {
+            Some(arg) => Err(usage_error(format!("unexpected argument '{}'", arg))),
+            None => Ok(()),
+        }
+    }
+}
+
+fn usage_error(message: impl Into<String>) -> AocError {
+    AocError::Usage(message.into())
+}
+
+fn parse_day(day: &str) -> Result<u8, AocError> {
+    match day.parse::<u8>() {
+        Ok(n) if (1..=25).contains(&n) => Ok(n),
+        _ => Err(AocError::UnknownDay(format!("{} (days run from 1 to 25)", day))),
+    }
+}
+
+fn parse_part(part: &str) -> Result<u8, AocError> {
+    match part {
+        "1" => Ok(1),
+        "2" => Ok(2),
+        _ => Err(AocError::UnknownPart(format!("{} (parts are 1 and 2)", part))),
+    }
+}
+
+fn parse_number<T>(name: &str, value: &str) -> Result<T, AocError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|e| usage_error(format!("invalid --{} '{}': {}", name, value, e)))
+}
+
+/**
+Parse the command line, without the program name.
+
+# Examples
+```
+use std::path::PathBuf;
+use aoc2021::cli::{parse_args, Command};
+use aoc2021::params::Params;
+use aoc2021::render::ImageFormat;
+use aoc2021::runner::Format;
+
+let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+assert_eq!(
+    Command::Solve {
+        day: 15, part: 2, input: String::from("in.txt"), params: Params::new(), format: Format::Json, trace: false,
+        cache: None,
+    },
+    parse_args(&args("solve --day 15 --format=json --part 2 --input in.txt")).unwrap(),
+);
+assert_eq!(
+    Command::Solve {
+        day: 1, part: 1, input: String::from("-"), params: Params::new(), format: Format::Text, trace: false,
+        cache: None,
+    },
+    parse_args(&args("1 1 -")).unwrap(),
+);
+assert_eq!(
+    Command::Solve {
+        day: 25, part: 1, input: String::from("in.txt"), params: Params::new(), format: Format::Text, trace: true,
+        cache: None,
+    },
+    parse_args(&args("25 1 --trace in.txt")).unwrap(),
+);
+let e = parse_args(&args("25 1 in.txt --trace=yes")).unwrap_err();
+assert_eq!("--trace takes no value", e.to_string());
+let mut params = Params::new();
+params.add("board=12").unwrap();
+params.add("dirac_target=15").unwrap();
+assert_eq!(
+    Command::Solve {
+        day: 21, part: 2, input: String::from("in.txt"), params, format: Format::Text, trace: false, cache: None,
+    },
+    parse_args(&args("21 2 in.txt --param board=12 --param=dirac_target=15")).unwrap(),
+);
+assert_eq!(
+    Command::Solve {
+        day: 19, part: 1, input: String::from("in.txt"), params: Params::new(), format: Format::Text, trace: false,
+        cache: Some(PathBuf::from("answers")),
+    },
+    parse_args(&args("19 1 in.txt --cache-dir answers")).unwrap(),
+);
+let e = parse_args(&args("19 1 in.txt --cache-dir answers --no-cache")).unwrap_err();
+assert_eq!("--cache-dir and --no-cache cannot be given together", e.to_string());
+assert_eq!(
+    Command::ClearCache { dir: PathBuf::from("answers") },
+    parse_args(&args("cache clear --cache-dir answers")).unwrap(),
+);
+let e = parse_args(&args("cache empty --cache-dir answers")).unwrap_err();
+assert_eq!("unknown cache action 'empty', expected clear", e.to_string());
+assert_eq!(Command::Help, parse_args(&[]).unwrap());
+assert_eq!(Command::Help, parse_args(&args("solve --help")).unwrap());
+
+let e = parse_args(&args("solve --day 26 --part 1 --input in.txt")).unwrap_err();
+assert_eq!("unknown day 26 (days run from 1 to 25)", e.to_string());
+let e = parse_args(&args("solve --day 1 --part 1")).unwrap_err();
+assert_eq!("missing --input", e.to_string());
+let e = parse_args(&args("solve --day 1 --part 1 --input a --colour red")).unwrap_err();
+assert_eq!("unknown option --colour", e.to_string());
+let e = parse_args(&args("1 1 in.txt --param tiles")).unwrap_err();
+assert_eq!("expected key=value, found 'tiles'", e.to_string());
+assert_eq!(
+    Command::Gen { day: 4, size: Some(3), seed: 0 },
+    parse_args(&args("gen --day 4 --size 3")).unwrap(),
+);
+let e = parse_args(&args("gen --day 4 --seed x")).unwrap_err();
+assert_eq!("invalid --seed 'x': invalid digit found in string", e.to_string());
+assert_eq!(
+    Command::Bench {
+        day: 15,
+        iterations: 50,
+        input: None,
+        params: Params::new(),
+        baseline: Some(String::from("bench.toml")),
+        save_baseline: None,
+        threshold: 10,
+    },
+    parse_args(&args("bench --day 15 --iterations 50 --baseline bench.toml")).unwrap(),
+);
+let e = parse_args(&args("bench --day 15 --threshold 4294967306")).unwrap_err();
+assert_eq!("invalid --threshold '4294967306': number too large to fit in target type", e.to_string());
+assert_eq!(
+    Command::Render {
+        day: 20,
+        part: 1,
+        input: String::from("in.txt"),
+        params: Params::new(),
+        output: PathBuf::from("day20.gif"),
+        image: ImageFormat::Gif,
+        scale: 4,
+        delay: 50,
+    },
+    parse_args(&args("render --day 20 --part 1 --input in.txt --out day20.gif --image gif --delay 50")).unwrap(),
+);
+let e = parse_args(&args("render --day 6 --part 1 --input in.txt --out frames")).unwrap_err();
+assert_eq!("day 6 has no pictures to render (days 11, 13, 20 and 25 do)", e.to_string());
+let e = parse_args(&args("render --day 11 --part 1 --input in.txt --out frames --scale 0")).unwrap_err();
+assert_eq!("--scale must be at least 1", e.to_string());
+let e = parse_args(&args("frobnicate")).unwrap_err();
+assert_eq!("unknown command 'frobnicate'", e.to_string());
+```
+ */
+pub fn parse_args(args: &[String]) -> Result<Command, AocError> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Ok(Command::Help),
+    };
+    if rest.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return Ok(Command::Help);
+    }
+    match command {
+        "solve" => {
+            let args = Arguments::scan(rest, &["day", "part", "input", "param", "format", "trace", "cache-dir",
+                "no-cache"])?;
+            args.no_positional()?;
+            Ok(Command::Solve {
+                day: parse_day(args.required("day")?)?,
+                part: parse_part(args.required("part")?)?,
+                input: args.required("input")?.to_string(),
+                params: args.params()?,
+                format: args.format()?,
+                trace: args.flag("trace"),
+                cache: args.cache_dir()?,
+            })
+        },
+        "all" => {
+            let args = Arguments::scan(rest, &["input-dir", "format", "cache-dir", "no-cache"])?;
+            args.no_positional()?;
+            Ok(Command::All {
+                input_dir: PathBuf::from(args.required("input-dir")?),
+                format: args.format()?,
+                cache: args.cache_dir()?,
+            })
+        },
+        "cache" => {
+            let args = Arguments::scan(rest, &["cache-dir"])?;
+            match args.positional.as_slice() {
+                [action] if action == "clear" => {},
+                [action] => return Err(usage_error(format!("unknown cache action '{}', expected clear", action))),
+                _ => return Err(usage_error("expected cache clear")),
+            }
+            let dir = args.value("cache-dir")?.map(PathBuf::from).or_else(cache::dir_from_env)
+                .ok_or_else(|| usage_error(format!("no cache directory: give --cache-dir or set {}", cache::DIR_VAR)))?;
+            Ok(Command::ClearCache { dir })
+        },
+        "verify" => {
+            let args = Arguments::scan(rest, &[])?;
+            match args.positional.as_slice() {
+                [answers] => Ok(Command::Verify { answers: answers.clone() }),
+                _ => Err(usage_error("expected one answers file")),
+            }
+        },
+        "gen" => {
+            let args = Arguments::scan(rest, &["day", "size", "seed"])?;
+            args.no_positional()?;
+            Ok(Command::Gen {
+                day: parse_day(args.required("day")?)?,
+                size: args.value("size")?.map(|size| parse_number("size", size)).transpose()?,
+                seed: args.value("seed")?.map_or(Ok(0), |seed| parse_number("seed", seed))?,
+            })
+        },
+        "bench" => {
+            let args = Arguments::scan(rest, &["day", "iterations", "input", "param", "baseline",
+                "save-baseline", "threshold"])?;
+            args.no_positional()?;
+            let number = |name, default| args.value(name)?.map_or(Ok(default), |n| parse_number(name, n));
+            Ok(Command::Bench {
+                day: parse_day(args.required("day")?)?,
+                iterations: number("iterations", 10)?,
+                input: args.value("input")?.map(String::from),
+                params: args.params()?,
+                baseline: args.value("baseline")?.map(String::from),
+                save_baseline: args.value("save-baseline")?.map(String::from),
+                threshold: args.value("threshold")?.map_or(Ok(10), |n| parse_number("threshold", n))?,
+            })
+        },
+        "render" => {
+            let args = Arguments::scan(rest, &["day", "part", "input", "param", "out", "image", "scale", "delay"])?;
+            args.no_positional()?;
+            let day = parse_day(args.required("day")?)?;
+            if !render::DAYS.contains(&day) {
+                return Err(usage_error(format!("day {} has no pictures to render (days 11, 13, 20 and 25 do)", day)));
+            }
+            let scale = args.value("scale")?.map_or(Ok(4), |n| parse_number("scale", n))?;
+            if scale == 0 {
+                return Err(usage_error("--scale must be at least 1"));
+            }
+            Ok(Command::Render {
+                day,
+                part: parse_part(args.required("part")?)?,
+                input: args.required("input")?.to_string(),
+                params: args.params()?,
+                output: PathBuf::from(args.required("out")?),
+                image: args.value("image")?.map_or(Ok(ImageFormat::Ascii), str::parse)?,
+                scale,
+                delay: args.value("delay")?.map_or(Ok(10), |n| parse_number("delay", n))?,
+            })
+        },
+        "list" => {
+            let args = Arguments::scan(rest, &["format"])?;
+            args.no_positional()?;
+            Ok(Command::List { format: args.format()? })
+        },
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        day if day.parse::<u8>().is_ok() => {
+            // the short form: DAY PART FILE
+            let args = Arguments::scan(rest, &["param", "format", "trace", "cache-dir", "no-cache"])?;
+            match args.positional.as_slice() {
+                [part, input] => Ok(Command::Solve {
+                    day: parse_day(day)?,
+                    part: parse_part(part)?,
+                    input: input.clone(),
+                    params: args.params()?,
+                    format: args.format()?,
+                    trace: args.flag("trace"),
+                    cache: args.cache_dir()?,
+                }),
+                _ => Err(usage_error("expected DAY PART FILE")),
+            }
+        },
+        _ => Err(usage_error(format!("unknown command '{}'", command))),
+    }
+}
+
+/**
+Run a command, printing its output. Returns whether every puzzle it ran was
+solved, and every answer it checked was right.
+
+# Examples
+```
+# #[cfg(feature = "all")] {
+use aoc2021::cli::{execute, Command};
+use aoc2021::params::Params;
+use aoc2021::runner::Format;
+
+let registry = aoc2021::registry();
+let mut out = Vec::new();
+let command = Command::Solve {
+    day: 1, part: 1, input: String::from("test_inputs/day01.txt"), params: Params::new(), format: Format::Text,
+    trace: false, cache: None,
+};
+assert!(execute(&registry, &command, &mut out).unwrap());
+assert_eq!("7\n", String::from_utf8(out).unwrap());
+
+let mut out = Vec::new();
+let command = Command::Gen { day: 6, size: Some(4), seed: 1 };
+assert!(execute(&registry, &command, &mut out).unwrap());
+assert_eq!(4, String::from_utf8(out).unwrap().trim().split(',').count());
+
+let mut out = Vec::new();
+let command = Command::Verify { answers: String::from("test_inputs/answers.toml") };
+assert!(execute(&registry, &command, &mut out).unwrap());
+
+let mut out = Vec::new();
+let command = Command::Solve {
+    day: 25, part: 2, input: String::from("test_inputs/day25.txt"), params: Params::new(), format: Format::Text,
+    trace: false, cache: None,
+};
+assert!(execute(&registry, &command, &mut out).is_err());
+# }
+```
+ */
+pub fn execute(registry: &Registry, command: &Command, out: &mut impl Write) -> Result<bool, AocError> {
+    let stdout_error = |e| AocError::io("<stdout>", e);
+    match command {
+        Command::Solve { day, part, input, params, format, trace, cache } => {
+            let solution = registry.get(*day)
+                .ok_or_else(|| AocError::UnknownDay(day.to_string()))?;
+            let outcome = if *trace {
+                let mut log = trace::Log::new(io::BufWriter::new(io::stderr().lock()));
+                let outcome = runner::run_part_traced(solution, *part, input, params, &mut log);
+                log.finish().and_then(|mut err| err.flush()).map_err(|e| AocError::io("<stderr>", e))?;
+                outcome
+            } else if let Some(dir) = cache {
+                runner::run_part_cached(solution, *part, input, params, &Cache::new(dir))
+            } else {
+                runner::run_part_traced(solution, *part, input, params, &mut NoTrace)
+            };
+            match format {
+                Format::Text => writeln!(out, "{}", outcome.answer?).map_err(stdout_error)?,
+                Format::Json => {
+                    writeln!(out, "{}", outcome.to_json()).map_err(stdout_error)?;
+                    outcome.answer?;
+                },
+            }
+            Ok(true)
+        },
+        Command::All { input_dir, format, cache } => {
+            let outcomes = match cache {
+                Some(dir) => runner::run_all_cached(registry, input_dir, &Cache::new(dir)),
+                None => runner::run_all(registry, input_dir),
+            };
+            match format {
+                Format::Text => runner::write_table(&outcomes, out),
+                Format::Json => runner::write_json(&outcomes, out),
+            }.map_err(stdout_error)?;
+            Ok(outcomes.iter().all(runner::Outcome::is_ok))
+        },
+        Command::ClearCache { dir } => {
+            let removed = Cache::new(dir).clear()?;
+            writeln!(out, "{} cached answers removed from {}", removed, dir.display()).map_err(stdout_error)?;
+            Ok(true)
+        },
+        Command::Verify { answers } => {
+            let results: Vec<_> = verify::load_answers(answers)?
+                .into_iter()
+                .map(|check| {
+                    let verdict = verify::check(registry, &check);
+                    (check, verdict)
+                })
+                .collect();
+            verify::write_report(&results, out).map_err(stdout_error)
+        },
+        Command::Gen { day, size, seed } => {
+            let size = size.unwrap_or_else(|| gen::default_size(*day));
+            write!(out, "{}", gen::generate(*day, size, *seed)?).map_err(stdout_error)?;
+            Ok(true)
+        },
+        Command::Bench { day, iterations, input, params, baseline, save_baseline, threshold } => {
+            let solution = registry.get(*day)
+                .ok_or_else(|| AocError::UnknownDay(day.to_string()))?;
+            let text = match input {
+                Some(file) => input::read_input(file)?,
+                None => gen::generate(*day, gen::default_size(*day), 0)?,
+            };
+            let measurements = bench::bench_day(solution, &text, params, *iterations)?;
+            let compared = baseline.as_deref().map(Baseline::load).transpose()?;
+            let passed = bench::write_report(&measurements, compared.as_ref(), *threshold, out).map_err(stdout_error)?;
+            if let Some(file) = save_baseline {
+                // keep what the file recorded for other days and phases
+                let mut saved = match Baseline::load(file) {
+                    Ok(saved) => saved,
+                    Err(AocError::Io { .. }) => Baseline::default(),
+                    Err(e) => return Err(e),
+                };
+                saved.record(&measurements);
+                saved.save(file)?;
+            }
+            Ok(passed)
+        },
+        Command::Render { day, part, input, params, output, image, scale, delay } => {
+            let solution = registry.get(*day)
+                .ok_or_else(|| AocError::UnknownDay(day.to_string()))?;
+            let mut recorder = render::Recorder::new();
+            let answer = runner::run_part_traced(solution, *part, input, params, &mut recorder).answer?;
+            let frames = recorder.into_frames();
+            match image {
+                ImageFormat::Gif => render::save_gif(&frames, *scale, *delay, output)?,
+                _ => render::write_frames(&frames, *image, *scale, output)?,
+            }
+            writeln!(out, "{}", answer).map_err(stdout_error)?;
+            writeln!(out, "{} frames written to {}", frames.len(), output.display()).map_err(stdout_error)?;
+            Ok(true)
+        },
+        Command::List { format } => {
+            write_list(registry, *format, out).map_err(stdout_error)?;
+            Ok(true)
+        },
+        Command::Help => {
+            writeln!(out, "{}", USAGE).map_err(stdout_error)?;
+            Ok(true)
+        },
+    }
+}
+
+/**
+Print the days that can be solved, one per line, or as a JSON array.
+
+# Examples
+```
+# #[cfg(all(feature = "day01", feature = "day02", feature = "day03"))] {
+use aoc2021::runner::Format;
+
+let mut out = Vec::new();
+aoc2021::cli::write_list(&aoc2021::registry(), Format::Text, &mut out).unwrap();
+assert_eq!(Some("day  1"), String::from_utf8(out).unwrap().lines().next());
+let mut out = Vec::new();
+aoc2021::cli::write_list(&aoc2021::registry(), Format::Json, &mut out).unwrap();
+assert!(String::from_utf8(out).unwrap().starts_with("[1,2,3,"));
+# }
+```
+ */
+pub fn write_list(registry: &Registry, format: Format, out: &mut impl Write) -> std::io::Result<()> {
+    match format {
+        Format::Text => {
+            for day in registry.days() {
+                writeln!(out, "day {:>2}", day.day)?;
+            }
+            Ok(())
+        },
+        Format::Json => {
+            let days: Vec<u8> = registry.days().map(|day| day.day).collect();
+            writeln!(out, "{}", serde_json::json!(days))
+        },
+    }
+}
+
+/**
+The exit status for an error: usage errors, including an unknown day or
+part, are told apart from puzzles that failed.
+
+# Examples
+```
+use aoc2021::cli::{exit_code, EXIT_FAILED, EXIT_USAGE};
+use aoc2021::error::AocError;
+
+assert_eq!(EXIT_USAGE, exit_code(&AocError::Usage(String::from("missing --day"))));
+assert_eq!(EXIT_USAGE, exit_code(&AocError::UnknownDay(String::from("26"))));
+assert_eq!(EXIT_FAILED, exit_code(&AocError::Unsolvable(String::from("no basins"))));
+```
+ */
+pub fn exit_code(e: &AocError) -> i32 {
+    match e {
+        AocError::Usage(_) | AocError::UnknownDay(_) | AocError::UnknownPart(_) => EXIT_USAGE,
+        _ => EXIT_FAILED,
+    }
+}
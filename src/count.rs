@@ -0,0 +1,127 @@
+//! Counts of things that multiply, such as lanternfish and polymer pairs,
+//! which soon outgrow any machine integer.
+//!
+//! The days count with any [`Count`]: a `u128`, which reports an overflow
+//! rather than wrapping, or with the `bigint` feature, an exact `BigUint`.
+
+use std::fmt;
+use crate::error::AocError;
+use crate::solution::Answer;
+
+/**
+A count that can tell when it overflows.
+ */
+pub trait Count: Clone + Ord + fmt::Debug + fmt::Display + Into<Answer> {
+    fn from_u128(n: u128) -> Self;
+
+    /**
+    The sum, or `None` if it is too large to count.
+     */
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    /**
+    The difference. `other` must not be larger.
+     */
+    fn sub(&self, other: &Self) -> Self;
+
+    /**
+    Half the count, rounded down.
+     */
+    fn half(&self) -> Self;
+}
+
+impl Count for u128 {
+    fn from_u128(n: u128) -> u128 {
+        n
+    }
+
+    fn checked_add(&self, other: &u128) -> Option<u128> {
+        u128::checked_add(*self, *other)
+    }
+
+    fn sub(&self, other: &u128) -> u128 {
+        self - other
+    }
+
+    fn half(&self) -> u128 {
+        self / 2
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Count for num_bigint::BigUint {
+    fn from_u128(n: u128) -> num_bigint::BigUint {
+        n.into()
+    }
+
+    fn checked_add(&self, other: &num_bigint::BigUint) -> Option<num_bigint::BigUint> {
+        Some(self + other)
+    }
+
+    fn sub(&self, other: &num_bigint::BigUint) -> num_bigint::BigUint {
+        self - other
+    }
+
+    fn half(&self) -> num_bigint::BigUint {
+        self >> 1
+    }
+}
+
+/**
+A count grew too large for its type.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl Overflow {
+    /**
+    The error reporting that the count of `what` overflowed, and how to
+    count it exactly.
+
+    # Examples
+    ```
+    let e = aoc2021::count::Overflow.counting("the lanternfish after 2000 days");
+    assert!(e.to_string().starts_with("no solution: the lanternfish after 2000 days overflowed a 128-bit count"));
+    ```
+     */
+    pub fn counting(self, what: &str) -> AocError {
+        let hint = if cfg!(feature = "bigint") {
+            "--param exact=true counts exactly"
+        } else {
+            "build with the bigint feature to count exactly"
+        };
+        AocError::Unsolvable(format!("{} overflowed a 128-bit count ({})", what, hint))
+    }
+}
+
+/**
+Add up the counts.
+
+# Examples
+```
+use aoc2021::count::{sum, Overflow};
+
+assert_eq!(Ok(6), sum(&[1u128, 2, 3]));
+assert_eq!(Err(Overflow), sum(&[u128::MAX, 1]));
+```
+ */
+pub fn sum<'a, C: Count + 'a>(counts: impl IntoIterator<Item = &'a C>) -> Result<C, Overflow> {
+    counts.into_iter().try_fold(C::from_u128(0), |total, count| total.checked_add(count).ok_or(Overflow))
+}
+
+/**
+Check that exact counts can be had, when asked for with the `exact`
+parameter.
+
+# Examples
+```
+assert!(aoc2021::count::check_exact(false).is_ok());
+assert_eq!(cfg!(feature = "bigint"), aoc2021::count::check_exact(true).is_ok());
+```
+ */
+pub fn check_exact(exact: bool) -> Result<(), AocError> {
+    if exact && !cfg!(feature = "bigint") {
+        return Err(AocError::Usage(String::from("exact=true needs aoc2021 built with the bigint feature")));
+    }
+    Ok(())
+}
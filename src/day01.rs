@@ -1,10 +1,301 @@
-use std::fs::File;
-use std::error::Error;
-use std::io::BufReader;
+use std::collections::VecDeque;
+use std::fmt::Debug;
 use std::io::BufRead;
+use std::ops::{Add, Sub};
+use crate::error::{parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
-Counts the number of times the next number in the sequence increases.
+A sensor reading: any number that can be summed and compared. Windows of
+readings are summed in a wider type, so no window of integers overflows.
+ */
+pub trait Reading: Copy + PartialOrd + Debug {
+    type Sum: Copy + PartialOrd + Debug + Default + Add<Output = Self::Sum> + Sub<Output = Self::Sum>;
+
+    fn widen(self) -> Self::Sum;
+
+    fn sum_to_f64(sum: Self::Sum) -> f64;
+}
+
+macro_rules! reading {
+    ($($t:ty => $sum:ty),*) => {
+        $(
+            impl Reading for $t {
+                type Sum = $sum;
+
+                fn widen(self) -> $sum {
+                    self as $sum
+                }
+
+                fn sum_to_f64(sum: $sum) -> f64 {
+                    sum as f64
+                }
+            }
+        )*
+    };
+}
+
+reading!(i8 => i64, i16 => i64, i32 => i64, i64 => i128, isize => i128,
+    u8 => u64, u16 => u64, u32 => u64, u64 => u128, usize => u128, f32 => f64, f64 => f64);
+
+/**
+The readings in one position of a sliding window.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Window<T: Reading> {
+    pub sum: T::Sum,
+    pub min: T,
+    pub max: T,
+    pub len: usize,
+}
+
+impl<T: Reading> Window<T> {
+    pub fn mean(&self) -> f64 {
+        T::sum_to_f64(self.sum) / self.len as f64
+    }
+}
+
+/**
+How a reading compares with the one before it.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    Increase,
+    Decrease,
+    Plateau,
+}
+
+/**
+The changes over a stream of readings, and the longest run of consecutive
+increases.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub increases: usize,
+    pub decreases: usize,
+    pub plateaus: usize,
+    pub longest_run: usize,
+}
+
+/**
+Analytics over any stream of readings, holding no more of the stream than
+one window.
+
+# Examples
+```
+use aoc2021::day01::{Change, Readings, Tally};
+
+let depths = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+let windows: Vec<_> = depths.iter().copied().sliding(3).collect();
+assert_eq!(8, windows.len());
+// windows of i32 depths are summed as i64
+assert_eq!(607i64, windows[0].sum);
+assert_eq!((199, 208), (windows[0].min, windows[0].max));
+assert_eq!(206.0, windows[1].mean());
+
+let changes: Vec<_> = [1.5, 2.0, 2.0, 0.5].into_iter().changes().collect();
+assert_eq!(vec![Change::Increase, Change::Plateau, Change::Decrease], changes);
+
+assert_eq!(vec![3, 3, 1], depths.iter().copied().increasing_runs().collect::<Vec<_>>());
+assert_eq!(
+    Tally { increases: 7, decreases: 2, plateaus: 0, longest_run: 3 },
+    depths.iter().copied().tally(),
+);
+```
+ */
+pub trait Readings: Iterator + Sized
+where
+    Self::Item: Reading,
+{
+    /**
+    The sum, mean, minimum and maximum of each window of `size` consecutive
+    readings. Like [`slice::windows`], there are no windows if the stream is
+    shorter than one window. Each window costs the same whatever its size:
+    the sum is kept as a running total, so sums of floating-point readings
+    may drift by rounding.
+
+    # Panics
+
+    Panics if `size` is 0.
+     */
+    fn sliding(self, size: usize) -> Windows<Self> {
+        assert!(size > 0, "window size must be at least 1");
+        Windows {
+            readings: self,
+            size,
+            buffer: VecDeque::with_capacity(size),
+            sum: Default::default(),
+            lows: VecDeque::new(),
+            highs: VecDeque::new(),
+        }
+    }
+
+    /**
+    How each reading after the first compares with the one before it.
+     */
+    fn changes(self) -> Changes<Self> {
+        Changes { readings: self, last: None }
+    }
+
+    /**
+    The length of each run of consecutive increases.
+     */
+    fn increasing_runs(self) -> IncreasingRuns<Self> {
+        IncreasingRuns { changes: self.changes() }
+    }
+
+    /**
+    Count the increases, decreases and plateaus, and find the longest run
+    of increases.
+     */
+    fn tally(self) -> Tally {
+        let mut tally = Tally::default();
+        let mut run = 0;
+        for change in self.changes() {
+            match change {
+                Change::Increase => {
+                    tally.increases += 1;
+                    run += 1;
+                    tally.longest_run = tally.longest_run.max(run);
+                    continue;
+                },
+                Change::Decrease => tally.decreases += 1,
+                Change::Plateau => tally.plateaus += 1,
+            }
+            run = 0;
+        }
+        tally
+    }
+}
+
+impl<I> Readings for I
+where
+    I: Iterator,
+    I::Item: Reading,
+{
+}
+
+/**
+An iterator over the windows of a stream of readings. See
+[`Readings::sliding`].
+ */
+pub struct Windows<I>
+where
+    I: Iterator,
+    I::Item: Reading,
+{
+    readings: I,
+    size: usize,
+    buffer: VecDeque<I::Item>,
+    sum: <I::Item as Reading>::Sum,
+    // the readings in the window that could yet be its minimum or maximum,
+    // rising and falling from the front
+    lows: VecDeque<I::Item>,
+    highs: VecDeque<I::Item>,
+}
+
+impl<I> Iterator for Windows<I>
+where
+    I: Iterator,
+    I::Item: Reading,
+{
+    type Item = Window<I::Item>;
+
+    fn next(&mut self) -> Option<Window<I::Item>> {
+        loop {
+            let reading = self.readings.next()?;
+            self.buffer.push_back(reading);
+            self.sum = self.sum + reading.widen();
+            while self.lows.back().is_some_and(|low| *low > reading) {
+                self.lows.pop_back();
+            }
+            self.lows.push_back(reading);
+            while self.highs.back().is_some_and(|high| *high < reading) {
+                self.highs.pop_back();
+            }
+            self.highs.push_back(reading);
+            if self.buffer.len() > self.size {
+                let old = self.buffer.pop_front()?;
+                self.sum = self.sum - old.widen();
+                if self.lows.front() == Some(&old) {
+                    self.lows.pop_front();
+                }
+                if self.highs.front() == Some(&old) {
+                    self.highs.pop_front();
+                }
+            }
+            if self.buffer.len() == self.size {
+                return Some(Window { sum: self.sum, min: self.lows[0], max: self.highs[0], len: self.size });
+            }
+        }
+    }
+}
+
+/**
+An iterator over the changes in a stream of readings. See
+[`Readings::changes`].
+ */
+pub struct Changes<I: Iterator> {
+    readings: I,
+    last: Option<I::Item>,
+}
+
+impl<I> Iterator for Changes<I>
+where
+    I: Iterator,
+    I::Item: Reading,
+{
+    type Item = Change;
+
+    fn next(&mut self) -> Option<Change> {
+        loop {
+            let reading = self.readings.next()?;
+            if let Some(last) = self.last.replace(reading) {
+                return Some(if reading > last {
+                    Change::Increase
+                } else if reading < last {
+                    Change::Decrease
+                } else {
+                    Change::Plateau
+                });
+            }
+        }
+    }
+}
+
+/**
+An iterator over the runs of increases in a stream of readings. See
+[`Readings::increasing_runs`].
+ */
+pub struct IncreasingRuns<I: Iterator> {
+    changes: Changes<I>,
+}
+
+impl<I> Iterator for IncreasingRuns<I>
+where
+    I: Iterator,
+    I::Item: Reading,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let mut run = 0;
+        loop {
+            match self.changes.next() {
+                Some(Change::Increase) => run += 1,
+                Some(_) if run > 0 => return Some(run),
+                Some(_) => {},
+                None => return (run > 0).then_some(run),
+            }
+        }
+    }
+}
+
+/**
+Counts the number of times the sum of a window of depths is larger than the
+sum of the window before. An input shorter than two windows has no
+increases, and nor does a window of 0.
 
 # Examples
 
@@ -13,50 +304,148 @@ let v: Vec<i32> = vec![1, 10, 2, 20, 20];
 let answer = aoc2021::day01::count_increases(&v, 1);
 
 assert_eq!(2, answer);
+assert_eq!(0, aoc2021::day01::count_increases(&v[..3], 3));
 ```
 */
-pub fn count_increases(v: &Vec<i32>, window: usize) -> usize {
-    if v.len() < window + 1{
-        panic!("vec must be larger than window size");
+pub fn count_increases(v: &[i32], window: usize) -> usize {
+    count_increases_in(v.iter().copied(), window)
+}
+
+/**
+Counts the increases of a window's sum like [`count_increases`], over any
+stream of readings.
+
+# Examples
+```
+let depths = (0..1_000_000).map(|n| n % 7);
+assert_eq!(571428, aoc2021::day01::count_increases_in(depths, 3));
+// sums of these windows would overflow an i32
+assert_eq!(1, aoc2021::day01::count_increases_in([1, i32::MAX, i32::MAX, 2], 2));
+```
+ */
+pub fn count_increases_in<T: Reading>(depths: impl IntoIterator<Item = T>, window: usize) -> usize {
+    // Two windows in a row share all but their ends, so the later sum is
+    // larger exactly when the reading entering it is larger than the one
+    // leaving: no sums are needed, and none can overflow.
+    let mut count = 0;
+    // the last `window` readings
+    let mut recent = VecDeque::with_capacity(window + 1);
+    for depth in depths {
+        if window > 0 && recent.len() == window && depth > recent[0] {
+            count += 1;
+        }
+        recent.push_back(depth);
+        if recent.len() > window {
+            recent.pop_front();
+        }
     }
-    let next = &v[window..];
-    let prev = &v[..v.len() - window];
-    next.iter()
-        .zip(prev.iter())
-        .map(|(a, b)| a - b)
-        .filter(|x| x > &0)
-        .count()
+    count
+}
+
+/**
+Reads integers, one per line, into a vector.
+
+# Examples
+```
+assert_eq!(vec![199, 200, 208], aoc2021::day01::parse_ints("199\n200\n208\n").unwrap());
+let e = aoc2021::day01::parse_ints("199\nx\n").unwrap_err();
+assert_eq!("line 2, column 1: could not parse \"x\": invalid digit found in string", e.to_string());
+```
+*/
+pub fn parse_ints(input: &str) -> Result<Vec<i32>, AocError> {
+    read_ints(input.as_bytes(), "").collect()
+}
+
+/**
+Reads integers, one per line, from a reader as they are needed. Errors are
+reported against `file`.
+
+# Examples
+```
+let mut ints = aoc2021::day01::read_ints("199\nx\n".as_bytes(), "depths.txt");
+assert_eq!(199, ints.next().unwrap().unwrap());
+assert_eq!("depths.txt:2:1: could not parse \"x\": invalid digit found in string",
+    ints.next().unwrap().unwrap_err().to_string());
+assert!(ints.next().is_none());
+```
+*/
+pub fn read_ints<'a>(reader: impl BufRead + 'a, file: &'a str) -> impl Iterator<Item = Result<i32, AocError>> + 'a {
+    reader.lines().enumerate().map(move |(ndx, line)| {
+        let line = line.map_err(|e| AocError::io(file, e))?;
+        parse_token(&line, line.trim()).map_err(|e| e.on_line(ndx + 1).in_file(file))
+    })
 }
 
 /**
-Reads integers from a text file into a vector.
+Reads integers from a text file, or from stdin when the file is `-`, a line
+at a time, so inputs of any size are read in constant memory.
 
 # Examples
 ```
 let v = vec![199,200,208,210,200,207,240,269,260,263];
-assert_eq!(aoc2021::day01::load_ints("test_inputs/day01.txt").unwrap(), v);
+let ints = aoc2021::day01::load_ints("test_inputs/day01.txt").unwrap();
+assert_eq!(ints.collect::<Result<Vec<_>, _>>().unwrap(), v);
+```
+*/
+pub fn load_ints(file: &str) -> Result<impl Iterator<Item = Result<i32, AocError>> + '_, AocError> {
+    Ok(read_ints(input::open(file)?, input::name(file)))
+}
+
+/**
+Solve Day 1's puzzle from the text of the input.
+
+# Examples
+```
+let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+assert_eq!(7, aoc2021::day01::solve(input, 1).unwrap());
+assert_eq!(5, aoc2021::day01::solve(input, 3).unwrap());
 ```
 */
-pub fn load_ints(file: &str) -> Result<Vec<i32>, Box<dyn Error>> {
-    let file = File::open(file)?;
-    let buf_reader = BufReader::new(file);
-    let v = buf_reader.lines()
-        .map(|s| s.unwrap().parse::<i32>().unwrap())
-        .collect();
-    Ok(v)
+pub fn solve(input: &str, window: usize) -> Result<usize, AocError> {
+    Ok(count_increases(&parse_ints(input)?, window))
 }
 
 /**
-Run Day 1's puzzle.
+Run Day 1's puzzle, streaming the file.
 
 # Examples
 ```
-let result = aoc2021::day01::run("test_inputs/day01.txt", 1);
+let result = aoc2021::day01::run("test_inputs/day01.txt", 1).unwrap();
 assert_eq!(7, result);
 ```
 */
-pub fn run(file: &str, window: usize) -> usize {
-    count_increases(&load_ints(file).unwrap(), window)
+pub fn run(file: &str, window: usize) -> Result<usize, AocError> {
+    let mut error = None;
+    let depths = load_ints(file)?.map_while(|depth| depth.map_err(|e| error = Some(e)).ok());
+    let count = count_increases_in(depths, window);
+    error.map_or(Ok(count), Err)
+}
+
+/**
+Day 1's puzzle: part 1 compares single depths, part 2 compares
+sums of three depths. The depths are read a line at a time, but kept so
+that each part does its own counting; [`run`] counts as it reads instead.
+ */
+pub struct Day01 {
+    depths: Vec<i32>,
+}
+
+impl Solution for Day01 {
+    fn parse(input: &str) -> Result<Day01, AocError> {
+        Day01::read(&mut input.as_bytes())
+    }
+
+    fn read(reader: &mut dyn BufRead) -> Result<Day01, AocError> {
+        Ok(Day01 { depths: read_ints(reader, "").collect::<Result<_, _>>()? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_increases(&self.depths, 1).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(count_increases(&self.depths, 3).into())
+    }
 }
 
 #[cfg(test)]
@@ -71,23 +460,72 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn count_increases_empty_vec() {
         let v = vec![];
-        let _ = count_increases(&v, 1);
+        assert_eq!(0, count_increases(&v, 1));
     }
 
     #[test]
-    #[should_panic]
     fn count_increases_one_item_vec() {
         let v = vec![42];
-        let _ = count_increases(&v, 1);
+        assert_eq!(0, count_increases(&v, 1));
+    }
+
+    #[test]
+    fn count_increases_matches_lagged_difference() {
+        let v = vec![3, -1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9];
+        for window in 1..v.len() {
+            let lagged = v[window..].iter().zip(&v).filter(|(a, b)| a > b).count();
+            assert_eq!(lagged, count_increases(&v, window));
+        }
+    }
+
+    #[test]
+    fn sliding_window_statistics() {
+        let windows: Vec<_> = [2.5, -1.0, 4.0, 0.5].into_iter().sliding(2).collect();
+        assert_eq!(3, windows.len());
+        assert_eq!(Window { sum: 1.5, min: -1.0, max: 2.5, len: 2 }, windows[0]);
+        assert_eq!(1.5, windows[1].mean());
+        assert_eq!(4.0, windows[2].max);
+        assert_eq!(0, [1u8, 2].into_iter().sliding(3).count());
+    }
+
+    #[test]
+    fn sliding_matches_each_window() {
+        let v = [3i16, -1, 4, 1, 5, 9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3, 2, 3, 8, 4];
+        for size in 1..=v.len() {
+            let windows: Vec<_> = v.iter().copied().sliding(size).collect();
+            assert_eq!(v.len() - size + 1, windows.len());
+            for (window, expected) in windows.iter().zip(v.windows(size)) {
+                assert_eq!(expected.iter().map(|n| *n as i64).sum::<i64>(), window.sum);
+                assert_eq!(expected.iter().min(), Some(&window.min));
+                assert_eq!(expected.iter().max(), Some(&window.max));
+            }
+        }
+        let windows: Vec<_> = [i32::MAX, i32::MAX, i32::MAX].into_iter().sliding(3).collect();
+        assert_eq!(3 * i32::MAX as i64, windows[0].sum);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sliding_empty_window() {
+        let _ = [1, 2, 3].into_iter().sliding(0);
+    }
+
+    #[test]
+    fn tally_counts_runs() {
+        let readings = [1u64, 2, 3, 3, 4, 5, 6, 7, 1, 2];
+        assert_eq!(vec![2, 4, 1], readings.into_iter().increasing_runs().collect::<Vec<_>>());
+        let tally = readings.into_iter().tally();
+        assert_eq!(Tally { increases: 7, decreases: 1, plateaus: 1, longest_run: 4 }, tally);
+        assert_eq!(Tally::default(), [7i32].into_iter().tally());
     }
 
     #[test]
     fn load_ints_result() {
         let expected = vec![199,200,208,210,200,207,240,269,260,263];
-        assert_eq!(load_ints("test_inputs/day01.txt").unwrap(), expected);
+        let ints: Result<Vec<_>, _> = load_ints("test_inputs/day01.txt").unwrap().collect();
+        assert_eq!(ints.unwrap(), expected);
     }
 
     #[test]
@@ -99,14 +537,28 @@ mod tests {
     #[test]
     #[should_panic]
     fn load_ints_parsepanic() {
-        let _ = load_ints("test_inputs/day01_bad.txt").unwrap();
+        let _ = load_ints("test_inputs/day01_bad.txt").unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    }
+
+    #[test]
+    fn load_ints_parse_error_location() {
+        match load_ints("test_inputs/day01_bad.txt").unwrap().collect::<Result<Vec<_>, _>>() {
+            Err(AocError::Parse { file, line, column, .. }) => {
+                assert_eq!("test_inputs/day01_bad.txt", file);
+                assert_eq!(5, line);
+                assert_eq!(1, column);
+            },
+            other => panic!("expected parse error, got {:?}", other),
+        };
     }
 
     #[test]
     fn run_ut() {
-        let result = run("test_inputs/day01.txt", 1);
+        let result = run("test_inputs/day01.txt", 1).unwrap();
         assert_eq!(7, result);
-        let result = run("test_inputs/day01.txt", 3);
+        let result = run("test_inputs/day01.txt", 3).unwrap();
         assert_eq!(5, result);
+        let e = run("test_inputs/day01_bad.txt", 1).unwrap_err();
+        assert!(e.to_string().starts_with("test_inputs/day01_bad.txt:5:1:"));
     }
 }
@@ -1,71 +1,143 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::str::FromStr;
+use serde_json::{json, Value};
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
-#[derive(Debug, PartialEq, Eq)]
+/**
+What an Instruction tells the submarine to do. The puzzle only uses
+`forward`, `down` and `up`; scripts may also go `back`, `surface`, or
+`set aim` to a value.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
     Forward,
     Down,
     Up,
+    Back,
+    Surface,
+    SetAim,
 }
 
-impl Command {
+impl FromStr for Command {
+    type Err = AocError;
+
     /**
     Generate Command value from str.
 
     # Examples
     ```
+    use std::str::FromStr;
     use aoc2021::day02::Command;
 
     let s = "down";
-    assert_eq!(Command::Down, Command::from_str("down"));
+    assert_eq!(Command::Down, Command::from_str(s).unwrap());
+    assert!(Command::from_str("sideways").is_err());
     ```
     */
-    pub fn from_str(s: &str) -> Command {
-        let command = if s.eq_ignore_ascii_case("forward") {
-            Command::Forward
-        } else if s.eq_ignore_ascii_case("down") {
-            Command::Down
-        } else if s.eq_ignore_ascii_case("up") {
-            Command::Up
-        } else {
-            panic!("Unrecognized command {}", s);
-        };
-        command
+    fn from_str(s: &str) -> Result<Command, AocError> {
+        parse_command(s, s)
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/**
+Parse the verb of an Instruction found in a line. `set aim` is two words, so
+its verb is `set`.
+ */
+fn parse_command(line: &str, verb: &str) -> Result<Command, AocError> {
+    let command = if verb.eq_ignore_ascii_case("forward") {
+        Command::Forward
+    } else if verb.eq_ignore_ascii_case("down") {
+        Command::Down
+    } else if verb.eq_ignore_ascii_case("up") {
+        Command::Up
+    } else if verb.eq_ignore_ascii_case("back") {
+        Command::Back
+    } else if verb.eq_ignore_ascii_case("surface") {
+        Command::Surface
+    } else if verb.eq_ignore_ascii_case("set") {
+        Command::SetAim
+    } else {
+        return Err(AocError::parse(1, column_of(line, verb), format!("unrecognized command \"{}\"", verb)));
+    };
+    Ok(command)
+}
+
+/**
+An Instruction, with its units for every command but `surface`, which has
+none and so 0 units.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Instruction {
     pub command: Command,
     pub units: i32,
 }
 
-impl Instruction {
+impl FromStr for Instruction {
+    type Err = AocError;
+
     /**
     Generate Instruction from str.
 
     # Eamples
     ```
+    use std::str::FromStr;
     use aoc2021::day02::{Instruction,Command};
 
     let s = "up 100";
-    assert_eq!(Instruction {command: Command::Up, units: 100}, Instruction::from_str(s));
+    assert_eq!(Instruction {command: Command::Up, units: 100}, Instruction::from_str(s).unwrap());
+    let e = Instruction::from_str("up x").unwrap_err();
+    assert_eq!("line 1, column 4: could not parse \"x\": invalid digit found in string", e.to_string());
+    assert_eq!(Instruction {command: Command::SetAim, units: -3}, Instruction::from_str("set aim -3").unwrap());
+    assert_eq!(Instruction {command: Command::Surface, units: 0}, Instruction::from_str("surface").unwrap());
+    let e = Instruction::from_str("surface 3").unwrap_err();
+    assert_eq!("line 1, column 9: unexpected \"3\"", e.to_string());
     ```
     */
-    pub fn from_str(s: &str) -> Instruction {
-        let mut split_iter = s.split(' ');
-        let command = Command::from_str(split_iter.next().expect("direction missing"));
-        let units = split_iter
-            .next()
-            .expect("units missing")
-            .parse::<i32>()
-            .expect("units not parsable as integer");
-        Instruction { command, units }
+    fn from_str(s: &str) -> Result<Instruction, AocError> {
+        parse_instruction(s, s)
+    }
+}
+
+/**
+Parse an Instruction found in a line, reporting errors at their column in
+the line.
+ */
+fn parse_instruction(line: &str, text: &str) -> Result<Instruction, AocError> {
+    let mut words = text.split_whitespace();
+    let verb = words.next()
+        .ok_or_else(|| AocError::parse(1, column_of(line, text), "command missing"))?;
+    let command = parse_command(line, verb)?;
+    let mut last = verb;
+    if command == Command::SetAim {
+        match words.next() {
+            Some(word) if word.eq_ignore_ascii_case("aim") => last = word,
+            Some(word) => {
+                let reason = format!("expected \"aim\", found \"{}\"", word);
+                return Err(AocError::parse(1, column_of(line, word), reason));
+            },
+            None => return Err(AocError::parse(1, column_of(line, verb) + verb.len(), "expected \"aim\"")),
+        }
+    }
+    let units = match (command, words.next()) {
+        (Command::Surface, None) => 0,
+        (Command::Surface, Some(word)) => return Err(unexpected(line, word)),
+        (_, Some(units)) => parse_token::<i32>(line, units)?,
+        (_, None) => return Err(AocError::parse(1, column_of(line, last) + last.len(), "units missing")),
+    };
+    match words.next() {
+        Some(word) => Err(unexpected(line, word)),
+        None => Ok(Instruction { command, units }),
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+fn unexpected(line: &str, word: &str) -> AocError {
+    AocError::parse(1, column_of(line, word), format!("unexpected \"{}\"", word))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct State {
     pub horizontal: i32,
     pub depth: i32,
@@ -102,26 +174,39 @@ impl State {
     let i1 = Instruction{ command: Command::Forward, units: 1};
     let i2 = Instruction{ command: Command::Down, units: 2};
     let i3 = Instruction{ command: Command::Up, units: 4};
-    s.travel(&i1);
+    s.travel(&i1).unwrap();
     assert_eq!(s, State{horizontal: 1, depth: 0, aim: 0});
-    s.travel(&i2);
+    s.travel(&i2).unwrap();
     assert_eq!(s, State{horizontal: 1, depth: 2, aim: 0});
-    s.travel(&i3);
+    s.travel(&i3).unwrap();
+    assert_eq!(s, State{horizontal: 1, depth: -2, aim: 0});
+    let e = s.travel(&Instruction{ command: Command::Up, units: i32::MAX}).unwrap_err();
+    assert_eq!("no solution: depth overflowed", e.to_string());
     assert_eq!(s, State{horizontal: 1, depth: -2, aim: 0});
     ```
     */
-    pub fn travel(&mut self, i: &Instruction) {
+    pub fn travel(&mut self, i: &Instruction) -> Result<(), AocError> {
         match i.command {
             Command::Forward => {
-                self.horizontal += i.units;
+                self.horizontal = checked(self.horizontal.checked_add(i.units), "horizontal position")?;
             }
             Command::Down => {
-                self.depth += i.units;
+                self.depth = checked(self.depth.checked_add(i.units), "depth")?;
             }
             Command::Up => {
-                self.depth -= i.units;
+                self.depth = checked(self.depth.checked_sub(i.units), "depth")?;
+            }
+            Command::Back => {
+                self.horizontal = checked(self.horizontal.checked_sub(i.units), "horizontal position")?;
+            }
+            Command::Surface => {
+                self.depth = 0;
+            }
+            Command::SetAim => {
+                self.aim = i.units;
             }
         };
+        Ok(())
     }
 
     /**
@@ -136,30 +221,608 @@ impl State {
     let i2 = Instruction{ command: Command::Forward, units: 10};
     let i3 = Instruction{ command: Command::Up, units: 4};
     let i4 = Instruction{ command: Command::Forward, units: 100};
-    s.aim_or_travel(&i1);
+    s.aim_or_travel(&i1).unwrap();
     assert_eq!(s, State{horizontal: 0, depth: 0, aim: 2});
-    s.aim_or_travel(&i2);
+    s.aim_or_travel(&i2).unwrap();
     assert_eq!(s, State{horizontal: 10, depth: 20, aim: 2});
-    s.aim_or_travel(&i3);
+    s.aim_or_travel(&i3).unwrap();
     assert_eq!(s, State{horizontal: 10, depth: 20, aim: -2});
-    s.aim_or_travel(&i4);
+    s.aim_or_travel(&i4).unwrap();
+    assert_eq!(s, State{horizontal: 110, depth: -180, aim: -2});
+    let e = s.aim_or_travel(&Instruction{ command: Command::Forward, units: 1 << 30}).unwrap_err();
+    assert_eq!("no solution: depth overflowed", e.to_string());
     assert_eq!(s, State{horizontal: 110, depth: -180, aim: -2});
     ```
     */
-    pub fn aim_or_travel(&mut self, i: &Instruction) {
+    pub fn aim_or_travel(&mut self, i: &Instruction) -> Result<(), AocError> {
         match i.command {
             Command::Forward => {
-                self.horizontal += i.units;
-                self.depth += i.units * self.aim;
+                let depth = i.units.checked_mul(self.aim).and_then(|down| self.depth.checked_add(down));
+                let horizontal = checked(self.horizontal.checked_add(i.units), "horizontal position")?;
+                self.depth = checked(depth, "depth")?;
+                self.horizontal = horizontal;
             }
             Command::Down => {
-                self.aim += i.units;
+                self.aim = checked(self.aim.checked_add(i.units), "aim")?;
             }
             Command::Up => {
-                self.aim -= i.units;
+                self.aim = checked(self.aim.checked_sub(i.units), "aim")?;
+            }
+            Command::Back => {
+                let depth = i.units.checked_mul(self.aim).and_then(|up| self.depth.checked_sub(up));
+                let horizontal = checked(self.horizontal.checked_sub(i.units), "horizontal position")?;
+                self.depth = checked(depth, "depth")?;
+                self.horizontal = horizontal;
             }
+            Command::Surface => {
+                self.depth = 0;
+            }
+            Command::SetAim => {
+                self.aim = i.units;
+            }
+        };
+        Ok(())
+    }
+}
+
+/**
+The result of a checked step, or an error naming what overflowed.
+ */
+fn checked(value: Option<i32>, what: &str) -> Result<i32, AocError> {
+    value.ok_or_else(|| AocError::Unsolvable(format!("{} overflowed", what)))
+}
+
+/**
+How the submarine moves: in part 1 it travels directly, in part 2 up and
+down change its aim.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Travel,
+    Aim,
+}
+
+impl Mode {
+    /**
+    The mode of a part of the puzzle.
+
+    # Examples
+    ```
+    use aoc2021::day02::Mode;
+
+    assert_eq!(Mode::Aim, Mode::for_part(2).unwrap());
+    assert_eq!("unknown part 3", Mode::for_part(3).unwrap_err().to_string());
+    ```
+     */
+    pub fn for_part(part: u8) -> Result<Mode, AocError> {
+        match part {
+            1 => Ok(Mode::Travel),
+            2 => Ok(Mode::Aim),
+            _ => Err(AocError::UnknownPart(part.to_string())),
+        }
+    }
+
+}
+
+/**
+A rule for how the submarine moves when it follows an Instruction. Both
+puzzle rules are [`Mode`]s, and any function of a State and an Instruction
+is a rule too.
+
+# Examples
+```
+use aoc2021::day02::{Command, Instruction, Mode, Movement, State};
+
+let down = Instruction { command: Command::Down, units: 4 };
+let mut state = State::new();
+Mode::Travel.apply(&mut state, &down).unwrap();
+assert_eq!(4, state.depth);
+
+// a submarine that dives twice as far as it is told
+let eager = |state: &mut State, i: &Instruction| {
+    state.travel(&Instruction { units: if i.command == Command::Down { i.units * 2 } else { i.units }, ..*i })
+};
+eager.apply(&mut state, &down).unwrap();
+assert_eq!(12, state.depth);
+```
+ */
+pub trait Movement {
+    /**
+    Follow one Instruction, or fail if the State would overflow.
+     */
+    fn apply(&self, state: &mut State, i: &Instruction) -> Result<(), AocError>;
+}
+
+impl Movement for Mode {
+    fn apply(&self, state: &mut State, i: &Instruction) -> Result<(), AocError> {
+        match self {
+            Mode::Travel => state.travel(i),
+            Mode::Aim => state.aim_or_travel(i),
+        }
+    }
+}
+
+impl<F> Movement for F
+where
+    F: Fn(&mut State, &Instruction) -> Result<(), AocError>,
+{
+    fn apply(&self, state: &mut State, i: &Instruction) -> Result<(), AocError> {
+        self(state, i)
+    }
+}
+
+/**
+Every State the submarine passes through, from the start to the end of its
+Instructions.
+
+# Examples
+```
+use aoc2021::day02::{Mode, State, Trajectory};
+
+let instructions = aoc2021::day02::load_instructions("test_inputs/day02.txt").unwrap();
+let trajectory = Trajectory::record(&instructions, &Mode::Aim).unwrap();
+assert_eq!(7, trajectory.states().len());
+assert_eq!(State::new(), trajectory.states()[0]);
+assert_eq!(&State{ horizontal: 15, depth: 60, aim: 10 }, trajectory.last());
+assert_eq!(60, trajectory.max_depth());
+assert_eq!(Some(3), trajectory.first_reaching(40));
+assert_eq!(None, trajectory.first_reaching(61));
+assert_eq!(65.89, (trajectory.distance() * 100.0).round() / 100.0);
+```
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trajectory {
+    states: Vec<State>,
+}
+
+impl Trajectory {
+    pub fn record(instructions: &[Instruction], movement: &dyn Movement) -> Result<Trajectory, AocError> {
+        let mut trajectory = Trajectory { states: Vec::with_capacity(instructions.len() + 1) };
+        trajectory.states.push(State::new());
+        for i in instructions {
+            trajectory.follow(movement, i)?;
+        }
+        Ok(trajectory)
+    }
+
+    fn follow(&mut self, movement: &dyn Movement, i: &Instruction) -> Result<(), AocError> {
+        let mut state = *self.last();
+        movement.apply(&mut state, i)?;
+        self.states.push(state);
+        Ok(())
+    }
+
+    /**
+    The States in order, starting with the one before any Instruction.
+     */
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    pub fn last(&self) -> &State {
+        // a trajectory always has its starting State
+        &self.states[self.states.len() - 1]
+    }
+
+    /**
+    The deepest the submarine went.
+     */
+    pub fn max_depth(&self) -> i32 {
+        self.states.iter().map(|s| s.depth).max().unwrap_or(0)
+    }
+
+    /**
+    The step at which the submarine first reached `depth` or deeper, where
+    step 0 is the start and step n follows the nth Instruction.
+     */
+    pub fn first_reaching(&self, depth: i32) -> Option<usize> {
+        self.states.iter().position(|s| s.depth >= depth)
+    }
+
+    /**
+    The length of the path, moving in a straight line from each State to
+    the next.
+     */
+    pub fn distance(&self) -> f64 {
+        self.states.windows(2)
+            .map(|pair| {
+                let across = f64::from(pair[1].horizontal) - f64::from(pair[0].horizontal);
+                let down = f64::from(pair[1].depth) - f64::from(pair[0].depth);
+                across.hypot(down)
+            })
+            .sum()
+    }
+
+    /**
+    Write the trajectory as CSV, one step to a line.
+
+    # Examples
+    ```
+    use aoc2021::day02::{Mode, Trajectory};
+
+    let instructions = aoc2021::day02::parse_instructions("forward 5\ndown 5\nforward 8\n").unwrap();
+    let mut out = Vec::new();
+    Trajectory::record(&instructions, &Mode::Aim).unwrap().write_csv(&mut out).unwrap();
+    assert_eq!("step,horizontal,depth,aim\n0,0,0,0\n1,5,0,0\n2,5,0,5\n3,13,40,5\n",
+        String::from_utf8(out).unwrap());
+    ```
+     */
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "step,horizontal,depth,aim")?;
+        for (step, s) in self.states.iter().enumerate() {
+            writeln!(out, "{},{},{},{}", step, s.horizontal, s.depth, s.aim)?;
+        }
+        Ok(())
+    }
+
+    /**
+    The trajectory as a JSON array of States.
+
+    # Examples
+    ```
+    use aoc2021::day02::{Mode, Trajectory};
+
+    let instructions = aoc2021::day02::parse_instructions("down 5\n").unwrap();
+    let json = Trajectory::record(&instructions, &Mode::Travel).unwrap().to_json();
+    assert_eq!(r#"[{"aim":0,"depth":0,"horizontal":0,"step":0},{"aim":0,"depth":5,"horizontal":0,"step":1}]"#,
+        json.to_string());
+    ```
+     */
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.states.iter()
+            .enumerate()
+            .map(|(step, s)| json!({
+                "step": step,
+                "horizontal": s.horizontal,
+                "depth": s.depth,
+                "aim": s.aim,
+            }))
+            .collect())
+    }
+}
+
+/**
+One step of a Script: an Instruction, or a block of steps done `count` times.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Statement {
+    Do(Instruction),
+    Repeat(u32, Vec<Statement>),
+}
+
+/**
+A manoeuvre written in the submarine's command language: one Instruction
+per line, as in the puzzle input, with `#` comments, blocks of lines
+repeated by `repeat N` ... `end`, and macros defined by `macro NAME` ...
+`end` and used by their name. A macro must be defined before it is used.
+Keywords, commands and macro names are all matched ignoring case. A script
+may follow at most [`MAX_STEPS`] Instructions, going round its repeats.
+
+# Examples
+```
+use aoc2021::day02::{Mode, State};
+
+let script = aoc2021::day02::parse_script("\
+macro zigzag
+    down 2   # dive
+    forward 3
+    up 2
+end
+
+set aim 1
+repeat 3
+    zigzag
+    back 1
+end
+").unwrap();
+assert_eq!(Some(13), script.steps());
+assert_eq!(13, script.instructions().unwrap().len());
+assert_eq!(State{ horizontal: 6, depth: 0, aim: 1 }, script.run(&Mode::Travel).unwrap());
+assert_eq!(State{ horizontal: 6, depth: 24, aim: 1 }, script.run(&Mode::Aim).unwrap());
+
+let script = aoc2021::day02::parse_script("repeat 10000\n  down 1000000\nend\n").unwrap();
+let e = script.run(&Mode::Travel).unwrap_err();
+assert_eq!("no solution: depth overflowed", e.to_string());
+```
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Script {
+    pub statements: Vec<Statement>,
+}
+
+/**
+The most Instructions a Script may follow, going round its repeats: a
+thousand times a puzzle input.
+ */
+pub const MAX_STEPS: usize = 1_000_000;
+
+impl Script {
+    /**
+    The number of Instructions the script follows, going round each
+    repeated block, or `None` if there are more than `usize::MAX`.
+     */
+    pub fn steps(&self) -> Option<usize> {
+        steps(&self.statements)
+    }
+
+    /**
+    Call `f` with each Instruction in the order they are followed, going
+    round each repeated block, and stop at the first error. Scripts that
+    follow more than [`MAX_STEPS`] Instructions are not followed at all.
+     */
+    pub fn for_each(&self, f: &mut dyn FnMut(&Instruction) -> Result<(), AocError>) -> Result<(), AocError> {
+        if self.steps().is_none_or(|steps| steps > MAX_STEPS) {
+            return Err(AocError::Unsolvable(too_long()));
+        }
+        visit(&self.statements, f)
+    }
+
+    /**
+    Every Instruction in the order they are followed, with the repeated
+    blocks written out.
+     */
+    pub fn instructions(&self) -> Result<Vec<Instruction>, AocError> {
+        let mut instructions = Vec::new();
+        self.for_each(&mut |i| {
+            instructions.push(*i);
+            Ok(())
+        })?;
+        Ok(instructions)
+    }
+
+    /**
+    Follow the script from the starting State.
+     */
+    pub fn run(&self, movement: &dyn Movement) -> Result<State, AocError> {
+        let mut state = State::new();
+        self.for_each(&mut |i| movement.apply(&mut state, i))?;
+        Ok(state)
+    }
+
+    /**
+    Follow the script from the starting State, keeping every State on the
+    way.
+     */
+    pub fn record(&self, movement: &dyn Movement) -> Result<Trajectory, AocError> {
+        let mut trajectory = Trajectory { states: vec![State::new()] };
+        self.for_each(&mut |i| trajectory.follow(movement, i))?;
+        Ok(trajectory)
+    }
+}
+
+fn visit(statements: &[Statement], f: &mut dyn FnMut(&Instruction) -> Result<(), AocError>) -> Result<(), AocError> {
+    for statement in statements {
+        match statement {
+            Statement::Do(i) => f(i)?,
+            Statement::Repeat(count, body) => {
+                for _ in 0..*count {
+                    visit(body, f)?;
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+/**
+The Instructions followed by the statements, going round each repeated
+block, or `None` if there are more than `usize::MAX`.
+ */
+fn steps(statements: &[Statement]) -> Option<usize> {
+    statements.iter().try_fold(0usize, |total, statement| {
+        total.checked_add(match statement {
+            Statement::Do(_) => 1,
+            Statement::Repeat(count, body) => steps(body)?.checked_mul(*count as usize)?,
+        })
+    })
+}
+
+/**
+The number of statements, counting those inside blocks.
+ */
+fn size(statements: &[Statement]) -> usize {
+    statements.iter()
+        .map(|statement| match statement {
+            Statement::Do(_) => 1,
+            Statement::Repeat(_, body) => 1 + size(body),
+        })
+        .sum()
+}
+
+fn too_long() -> String {
+    format!("the script follows more than {} instructions", MAX_STEPS)
+}
+
+/**
+A block opened by `repeat` or `macro`, not yet ended.
+ */
+enum Block<'a> {
+    Repeat { line: usize, count: u32, body: Vec<Statement> },
+    Macro { line: usize, name: &'a str, body: Vec<Statement> },
+}
+
+/**
+The statements of the innermost open block, or of the script if no block
+is open.
+ */
+fn innermost<'a>(blocks: &'a mut [Block], statements: &'a mut Vec<Statement>) -> &'a mut Vec<Statement> {
+    match blocks.last_mut() {
+        Some(Block::Repeat { body, .. }) | Some(Block::Macro { body, .. }) => body,
+        None => statements,
+    }
+}
+
+/**
+Parse a Script in the command language.
+
+# Examples
+```
+use aoc2021::day02::parse_script;
+
+assert_eq!(6, parse_script("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n").unwrap().statements.len());
+let e = parse_script("repeat 2\n  forward 1\n  dive 3\nend\n").unwrap_err();
+assert_eq!("line 3, column 3: unrecognized command \"dive\"", e.to_string());
+let e = parse_script("repeat 2\n  forward 1\n").unwrap_err();
+assert_eq!("line 1, column 1: repeat without end", e.to_string());
+let e = parse_script("macro up\nend\n").unwrap_err();
+assert_eq!("line 1, column 7: \"up\" is a command, so cannot name a macro", e.to_string());
+let e = parse_script("repeat 1000\n  repeat 1001\n    surface\n  end\nend\n").unwrap_err();
+assert_eq!("line 1, column 1: the script follows more than 1000000 instructions", e.to_string());
+```
+*/
+pub fn parse_script(input: &str) -> Result<Script, AocError> {
+    let mut macros: HashMap<String, Vec<Statement>> = HashMap::new();
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut statements = Vec::new();
+    // the Instructions followed by the statements outside any block, and
+    // every statement held with the macros written out, both kept within
+    // MAX_STEPS so that neither parsing nor running can run away
+    let mut steps_so_far: usize = 0;
+    let mut written: usize = 0;
+    for (ndx, line) in input.lines().enumerate() {
+        let at = |e: AocError| e.on_line(ndx + 1);
+        let text = line.split('#').next().unwrap_or_default();
+        let mut words = text.split_whitespace();
+        let first = match words.next() {
+            Some(first) => first,
+            None => continue,
         };
+        let after_first = column_of(line, first) + first.len();
+        let within = |total: Option<usize>, reason: String| {
+            total.filter(|total| *total <= MAX_STEPS)
+                .ok_or_else(|| at(AocError::parse(1, column_of(line, first), reason)))
+        };
+        let too_big = || format!("the script holds more than {} statements with its macros written out", MAX_STEPS);
+        if first.eq_ignore_ascii_case("repeat") {
+            let count = words.next()
+                .ok_or_else(|| at(AocError::parse(1, after_first, "repeat count missing")))?;
+            let count = parse_token::<u32>(line, count).map_err(at)?;
+            written = within(written.checked_add(1), too_big())?;
+            blocks.push(Block::Repeat { line: ndx + 1, count, body: Vec::new() });
+        } else if first.eq_ignore_ascii_case("macro") {
+            let name = words.next()
+                .ok_or_else(|| at(AocError::parse(1, after_first, "macro name missing")))?;
+            let error = |reason: String| Err(at(AocError::parse(1, column_of(line, name), reason)));
+            if !blocks.is_empty() {
+                return error(String::from("a macro cannot be defined inside a block"));
+            }
+            let keyword = ["repeat", "macro", "end"].iter().any(|k| name.eq_ignore_ascii_case(k));
+            if keyword || parse_command(name, name).is_ok() {
+                return error(format!("\"{}\" is a command, so cannot name a macro", name));
+            }
+            if macros.contains_key(&name.to_ascii_lowercase()) {
+                return error(format!("macro \"{}\" is already defined", name));
+            }
+            blocks.push(Block::Macro { line: ndx + 1, name, body: Vec::new() });
+        } else if first.eq_ignore_ascii_case("end") {
+            match blocks.pop() {
+                Some(Block::Repeat { line, count, body }) => {
+                    let repeat = Statement::Repeat(count, body);
+                    if blocks.is_empty() {
+                        // blame the repeat, not its end
+                        steps_so_far = steps(std::slice::from_ref(&repeat))
+                            .and_then(|steps| steps_so_far.checked_add(steps))
+                            .filter(|steps| *steps <= MAX_STEPS)
+                            .ok_or_else(|| AocError::parse(line, 1, too_long()))?;
+                    }
+                    innermost(&mut blocks, &mut statements).push(repeat);
+                },
+                Some(Block::Macro { name, body, .. }) => {
+                    macros.insert(name.to_ascii_lowercase(), body);
+                },
+                None => return Err(at(AocError::parse(1, column_of(line, first), "end without repeat or macro"))),
+            }
+        } else if let Some(body) = macros.get(&first.to_ascii_lowercase()) {
+            written = within(written.checked_add(size(body)), too_big())?;
+            if blocks.is_empty() {
+                steps_so_far = within(steps(body).and_then(|steps| steps_so_far.checked_add(steps)), too_long())?;
+            }
+            innermost(&mut blocks, &mut statements).extend(body.iter().cloned());
+        } else {
+            let i = parse_instruction(line, text).map_err(at)?;
+            written = within(written.checked_add(1), too_big())?;
+            if blocks.is_empty() {
+                steps_so_far = within(steps_so_far.checked_add(1), too_long())?;
+            }
+            innermost(&mut blocks, &mut statements).push(Statement::Do(i));
+            continue;
+        }
+        // the keywords and macros have checked the words they take
+        if let Some(word) = words.next() {
+            return Err(at(unexpected(line, word)));
+        }
     }
+    match blocks.last() {
+        Some(Block::Repeat { line, .. }) => Err(AocError::parse(*line, 1, "repeat without end")),
+        Some(Block::Macro { line, name, .. }) => {
+            Err(AocError::parse(*line, 1, format!("macro \"{}\" without end", name)))
+        },
+        None => Ok(Script { statements }),
+    }
+}
+
+/**
+Load a Script from a file.
+
+# Examples
+```
+let script = aoc2021::day02::load_script("test_inputs/day02.txt").unwrap();
+assert_eq!(6, script.instructions().unwrap().len());
+```
+*/
+pub fn load_script(file: &str) -> Result<Script, AocError> {
+    input::from_file(file, parse_script)
+}
+
+/**
+Parse the Instructions, one per line.
+
+# Examples
+```
+use aoc2021::day02::{Instruction,Command};
+
+let instructions = aoc2021::day02::parse_instructions("forward 5\ndown 5\n").unwrap();
+assert_eq!(vec![Instruction {command: Command::Forward, units: 5},
+                Instruction {command: Command::Down, units: 5}], instructions);
+```
+*/
+pub fn parse_instructions(input: &str) -> Result<Vec<Instruction>, AocError> {
+    let mut instructions = Vec::new();
+    for (ndx, line) in input.lines().enumerate() {
+        let instruction = Instruction::from_str(line).map_err(|e| e.on_line(ndx + 1))?;
+        instructions.push(instruction);
+    }
+    Ok(instructions)
+}
+
+/**
+Load the Instructions from a file.
+
+# Examples
+```
+use aoc2021::day02::{Instruction,Command};
+
+let instructions = aoc2021::day02::load_instructions("test_inputs/day02.txt").unwrap();
+assert_eq!(6, instructions.len());
+assert_eq!(Instruction {command: Command::Forward, units: 5}, instructions[0]);
+```
+*/
+pub fn load_instructions(file: &str) -> Result<Vec<Instruction>, AocError> {
+    input::from_file(file, parse_instructions)
+}
+
+/**
+Follow the Instructions from the starting State. Part 1 travels
+directly, part 2 changes the aim or travels.
+*/
+pub fn navigate(instructions: &[Instruction], part: u8) -> Result<State, AocError> {
+    let mode = Mode::for_part(part)?;
+    instructions
+        .iter()
+        .try_fold(State::new(), |mut s: State, i| {
+            mode.apply(&mut s, i)?;
+            Ok(s)
+        })
 }
 
 /**
@@ -170,30 +833,81 @@ Run Day 2's puzzle.
 use aoc2021::day02::State;
 
 let expected = State{ horizontal: 15, depth: 10, aim: 0 };
-assert_eq!(expected, aoc2021::day02::run("test_inputs/day02.txt", 1));
+assert_eq!(expected, aoc2021::day02::run("test_inputs/day02.txt", 1).unwrap());
 let expected = State{ horizontal: 15, depth: 60, aim: 10 };
-assert_eq!(expected, aoc2021::day02::run("test_inputs/day02.txt", 2));
+assert_eq!(expected, aoc2021::day02::run("test_inputs/day02.txt", 2).unwrap());
 ```
 */
-pub fn run(file: &str, part: u8) -> State {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let closure = if part == 1 {
-        State::travel
-    } else if part == 2 {
-        State::aim_or_travel
-    } else {
-        panic!("Part must be 1 or 2");
-    };
+pub fn run(file: &str, part: u8) -> Result<State, AocError> {
+    input::from_file(file, |input| solve(input, part))
+}
 
-    buf_reader
-        .lines()
-        .map(|s| Instruction::from_str(&s.unwrap()))
-        .fold(State::new(), |s: State, i| {
-            let mut s = s;
-            closure(&mut s, &i);
-            s
-        })
+/**
+Solve Day 2's puzzle from the text of the input.
+
+# Examples
+```
+use aoc2021::day02::State;
+
+let input = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+let expected = State{ horizontal: 15, depth: 10, aim: 0 };
+assert_eq!(expected, aoc2021::day02::solve(input, 1).unwrap());
+```
+*/
+pub fn solve(input: &str, part: u8) -> Result<State, AocError> {
+    navigate(&parse_instructions(input)?, part)
+}
+
+/**
+The final State as an answer, with the product of its position.
+
+# Examples
+```
+use aoc2021::day02::State;
+use aoc2021::solution::Answer;
+
+let answer = Answer::from(State{ horizontal: 15, depth: 10, aim: 0 });
+assert_eq!("horizontal: 15, depth: 10, aim: 0, product: 150", answer.to_string());
+assert_eq!(Some(150), answer.to_json()["product"].as_i64());
+```
+*/
+impl From<State> for Answer {
+    fn from(state: State) -> Answer {
+        Answer::Record(vec![
+            (String::from("horizontal"), state.horizontal.into()),
+            (String::from("depth"), state.depth.into()),
+            (String::from("aim"), state.aim.into()),
+            (String::from("product"), (i64::from(state.horizontal) * i64::from(state.depth)).into()),
+        ])
+    }
+}
+
+/**
+Day 2's puzzle. The answer shows the final State and the product
+of its position. The input may be any Script in the command language.
+*/
+pub struct Day02 {
+    script: Script,
+}
+
+impl Day02 {
+    fn answer(&self, part: u8) -> Result<Answer, AocError> {
+        Ok(self.script.run(&Mode::for_part(part)?)?.into())
+    }
+}
+
+impl Solution for Day02 {
+    fn parse(input: &str) -> Result<Day02, AocError> {
+        Ok(Day02 { script: parse_script(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        self.answer(1)
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        self.answer(2)
+    }
 }
 
 #[cfg(test)]
@@ -207,7 +921,7 @@ mod tests {
             depth: 10,
             aim: 0,
         };
-        assert_eq!(expected, run("test_inputs/day02.txt", 1));
+        assert_eq!(expected, run("test_inputs/day02.txt", 1).unwrap());
     }
 
     #[test]
@@ -217,6 +931,78 @@ mod tests {
             depth: 60,
             aim: 10,
         };
-        assert_eq!(expected, run("test_inputs/day02.txt", 2));
+        assert_eq!(expected, run("test_inputs/day02.txt", 2).unwrap());
+    }
+
+    #[test]
+    fn trajectory_ends_where_navigate_does() {
+        let instructions = load_instructions("test_inputs/day02.txt").unwrap();
+        for (part, mode) in [(1, Mode::Travel), (2, Mode::Aim)] {
+            let trajectory = Trajectory::record(&instructions, &mode).unwrap();
+            assert_eq!(&navigate(&instructions, part).unwrap(), trajectory.last());
+        }
+    }
+
+    #[test]
+    fn script_of_puzzle_input_navigates() {
+        let input = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n";
+        let script = parse_script(input).unwrap();
+        let instructions = parse_instructions(input).unwrap();
+        assert_eq!(instructions, script.instructions().unwrap());
+        assert_eq!(navigate(&instructions, 2).unwrap(), script.run(&Mode::Aim).unwrap());
+        assert_eq!(Trajectory::record(&instructions, &Mode::Aim).unwrap(), script.record(&Mode::Aim).unwrap());
+    }
+
+    #[test]
+    fn nested_repeats_and_macros() {
+        let script = parse_script("\
+# a square dive
+macro step
+  forward 1
+end
+REPEAT 2
+  repeat 3
+    step
+  end
+  down 1   # deeper each time
+end
+repeat 0
+  surface
+end
+").unwrap();
+        assert_eq!(State { horizontal: 6, depth: 2, aim: 0 }, script.run(&Mode::Travel).unwrap());
+        assert_eq!(State { horizontal: 6, depth: 3, aim: 2 }, script.run(&Mode::Aim).unwrap());
+        let surfaced = parse_script("down 40\nsurface\nforward 1\n").unwrap();
+        assert_eq!(State { horizontal: 1, depth: 0, aim: 0 }, surfaced.run(&Mode::Travel).unwrap());
+        // macro names ignore case, like keywords and commands
+        let shouting = parse_script("Macro Dive\n  DOWN 2\nEnd\ndive\nDIVE\n").unwrap();
+        assert_eq!(State { horizontal: 0, depth: 4, aim: 0 }, shouting.run(&Mode::Travel).unwrap());
+    }
+
+    #[test]
+    fn script_errors() {
+        let error = |script: &str| parse_script(script).unwrap_err().to_string();
+        assert_eq!("line 2, column 1: end without repeat or macro", error("forward 1\nend\n"));
+        assert_eq!("line 1, column 7: repeat count missing", error("repeat\nend\n"));
+        assert_eq!("line 1, column 8: could not parse \"-2\": invalid digit found in string",
+            error("repeat -2\nend\n"));
+        assert_eq!("line 2, column 5: unexpected \"now\"", error("repeat 1\nend now\n"));
+        assert_eq!("line 2, column 9: a macro cannot be defined inside a block",
+            error("repeat 2\n  macro m\n  end\nend\n"));
+        assert_eq!("line 3, column 7: macro \"m\" is already defined", error("macro m\nend\nmacro m\nend\n"));
+        assert_eq!("line 3, column 7: macro \"M\" is already defined", error("macro m\nend\nmacro M\nend\n"));
+        assert_eq!("line 1, column 1: macro \"m\" without end", error("macro m\n  up 1\n"));
+        assert_eq!("line 4, column 3: unexpected \"twice\"", error("macro m\nend\nm\nm twice\n"));
+        assert_eq!("line 1, column 9: expected \"aim\", found \"course\"", error("set     course 3\n"));
+        assert_eq!("line 1, column 8: units missing", error("set aim    # to what?\n"));
+    }
+
+    #[test]
+    fn empty_trajectory() {
+        let trajectory = Trajectory::record(&[], &Mode::Travel).unwrap();
+        assert_eq!(&State::new(), trajectory.last());
+        assert_eq!(0, trajectory.max_depth());
+        assert_eq!(Some(0), trajectory.first_reaching(0));
+        assert_eq!(0.0, trajectory.distance());
     }
 }
@@ -1,176 +1,446 @@
-use ndarray::{arr1, Array1};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
-Convert &str to an ndarray::Array that can be summed.
-'0's become -1, '1's become 1.
+The widest binary number a report can hold.
+ */
+pub const MAX_WIDTH: usize = 128;
 
-# Examples
-```
-let s = "11010";
-let expected: ndarray::Array1<i32> = ndarray::arr1(&[1, 1, -1, 1, -1]);
-assert_eq!(expected, aoc2021::day03::str_as_ndarray(&s));
-```
-*/
-pub fn str_as_ndarray(s: &str) -> Array1<i32> {
-    let v: Vec<i32> = s
-        .chars()
-        .map(|c| match c {
-            '1' => 1,
-            '0' => -1,
-            _ => 0,
-        })
-        .collect();
+/**
+A diagnostic report: binary numbers of the same width, up to 128 bits.
+Each number is kept as an integer, and each bit column as a bitset of the
+numbers with a '1' there, so a column's '1's are counted with popcount.
+Columns are numbered from the left, the most significant bit.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    numbers: Vec<u128>,
+    width: usize,
+    columns: Vec<Vec<u64>>,
+}
+
+impl Report {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.numbers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.numbers.is_empty()
+    }
+
+    pub fn numbers(&self) -> &[u128] {
+        &self.numbers
+    }
+
+    /**
+    The number of '1's in a column.
 
-    arr1(&v)
+    # Examples
+    ```
+    let report = aoc2021::day03::parse_report("101\n011\n111\n").unwrap();
+    assert_eq!(vec![2, 2, 3], (0..3).map(|column| report.ones(column)).collect::<Vec<_>>());
+    ```
+     */
+    pub fn ones(&self, column: usize) -> usize {
+        count(&self.columns[column])
+    }
+
+    /**
+    The bitset with every number in it.
+     */
+    fn everyone(&self) -> Vec<u64> {
+        let mut set = vec![u64::MAX; self.numbers.len().div_ceil(64)];
+        if let Some(last) = set.last_mut() {
+            *last >>= (64 - self.numbers.len() % 64) % 64;
+        }
+        set
+    }
+
+    /**
+    The gamma rate: the most common bit of each column. There is none if
+    '0' and '1' are equally common in any column.
+
+    # Examples
+    ```
+    let report = aoc2021::day03::load_report("test_inputs/day03.txt").unwrap();
+    assert_eq!(0b10110, report.gamma().unwrap());
+    assert_eq!(0b01001, report.epsilon().unwrap());
+    let e = aoc2021::day03::parse_report("10\n01\n").unwrap().gamma().unwrap_err();
+    assert_eq!("no solution: no most common bit in column 1", e.to_string());
+    ```
+     */
+    pub fn gamma(&self) -> Result<u128, AocError> {
+        let mut gamma = 0;
+        for column in 0..self.width {
+            let ones = self.ones(column);
+            if ones * 2 == self.len() {
+                return Err(AocError::Unsolvable(format!("no most common bit in column {}", column + 1)));
+            }
+            gamma = gamma << 1 | u128::from(ones * 2 > self.len());
+        }
+        Ok(gamma)
+    }
+
+    /**
+    The epsilon rate: the least common bit of each column.
+     */
+    pub fn epsilon(&self) -> Result<u128, AocError> {
+        Ok(!self.gamma()? & mask(self.width))
+    }
 }
 
 /**
- Load a file of binary numbers into a Vec of ndarray::Arrays.
- '0's become -1, '1's become 1.
-*/
-pub fn file_to_varray(file: &str) -> Vec<Array1<i32>> {
-    let file = File::open(file).expect("could not open file");
+The lowest `width` bits set.
+ */
+fn mask(width: usize) -> u128 {
+    u128::MAX >> (MAX_WIDTH - width)
+}
 
-    BufReader::new(file)
-        .lines()
-        .map(|s| str_as_ndarray(&s.expect("could not read file")))
-        .collect()
+/**
+The number of members of a bitset.
+ */
+fn count(set: &[u64]) -> usize {
+    set.iter().map(|word| word.count_ones() as usize).sum()
 }
 
 /**
-Sum a slice of ndarray::Arrays.
+Parse binary numbers, one per line, into a Report. Every number must have
+the same number of bits, and no more than 128.
 
 # Examples
 ```
-let a1 = ndarray::arr1(&[1,2,3]);
-let a2 = ndarray::arr1(&[2,4,6]);
-let a3 = ndarray::arr1(&[3,6,9]);
-let a4 = ndarray::arr1(&[4,8,12]);
-let v = vec![a1, a2, a3, a4];
-let expected = ndarray::arr1(&[10,20,30]);
-assert_eq!(expected, aoc2021::day03::sum_arrays(&v));
+let report = aoc2021::day03::parse_report("101\n011\n").unwrap();
+assert_eq!(&[0b101, 0b011], report.numbers());
+assert_eq!(3, report.width());
+let e = aoc2021::day03::parse_report("101\n01\n").unwrap_err();
+assert_eq!("line 2, column 3: expected 3 bits, found 2", e.to_string());
+let e = aoc2021::day03::parse_report("101\n0x1\n").unwrap_err();
+assert_eq!("line 2, column 2: expected '0' or '1', found 'x'", e.to_string());
+let e = aoc2021::day03::parse_report(&"1".repeat(129)).unwrap_err();
+assert_eq!("line 1, column 129: expected at most 128 bits, found 129", e.to_string());
 ```
 */
-pub fn sum_arrays(arrays: &[Array1<i32>]) -> Array1<i32> {
-    let init = Array1::zeros(arrays[0].raw_dim());
-
-    arrays.iter().fold(init, |a, b| a + b)
+pub fn parse_report(input: &str) -> Result<Report, AocError> {
+    let mut report = Report { numbers: Vec::new(), width: 0, columns: Vec::new() };
+    for (ndx, line) in input.lines().enumerate() {
+        let error = |column, reason| Err(AocError::parse(ndx + 1, column, reason));
+        if report.is_empty() {
+            if line.is_empty() {
+                return error(1, String::from("expected '0' or '1', found an empty line"));
+            }
+            if line.len() > MAX_WIDTH {
+                return error(MAX_WIDTH + 1, format!("expected at most {} bits, found {}", MAX_WIDTH, line.len()));
+            }
+            report.width = line.len();
+            report.columns = vec![Vec::new(); line.len()];
+        }
+        let mut number = 0;
+        for (column, c) in line.chars().enumerate() {
+            let bit = match c {
+                '0' => 0,
+                '1' => 1,
+                _ => return error(column + 1, format!("expected '0' or '1', found '{}'", c)),
+            };
+            if column == report.width {
+                break;
+            }
+            let bits = &mut report.columns[column];
+            if ndx % 64 == 0 {
+                bits.push(0);
+            }
+            bits[ndx / 64] |= bit << (ndx % 64);
+            number = number << 1 | u128::from(bit);
+        }
+        if line.len() != report.width {
+            return error(line.len().min(report.width) + 1,
+                format!("expected {} bits, found {}", report.width, line.len()));
+        }
+        report.numbers.push(number);
+    }
+    if report.is_empty() {
+        return Err(AocError::parse(1, 1, "no binary numbers"));
+    }
+    Ok(report)
 }
 
 /**
-From a file of binary numbers with the same number of bits, find
-the difference of the number of times a '1' appears and the number
-of times a '0' appears, for each bit.
+Load a file of binary numbers into a Report.
 */
-pub fn file_sum_as_ndarray(file: &str) -> Array1<i32> {
-    sum_arrays(&file_to_varray(file))
+pub fn load_report(file: &str) -> Result<Report, AocError> {
+    input::from_file(file, parse_report)
 }
 
 /**
-Transform an array back into a binary String.
+How a rating chooses which numbers to keep in a column, from how many of
+the numbers left have a '1' there and how many a '0'.
 
 # Examples
 ```
-let a: ndarray::Array1<i32> = ndarray::arr1(&[1, -1, 2, -2, -3, 33, 0]);
-let expected = "101001X";
-assert_eq!(expected, aoc2021::day03::array_to_str(&a));
+use aoc2021::day03::{parse_report, rating, Criterion, Majority, Threshold};
+
+let report = parse_report("110\n101\n100\n011\n").unwrap();
+assert_eq!(Some(true), Majority { tie: true }.keep(3, 1));
+assert_eq!(0b101, rating(&report, &Majority { tie: true }));
+// keep the '1's unless they are under a third of what is left
+assert_eq!(0b110, rating(&report, &Threshold(1.0 / 3.0)));
+// any function of the counts will do
+let skip_ties = |ones: usize, zeros: usize| if ones == zeros { None } else { Some(ones < zeros) };
+assert_eq!(0b011, rating(&report, &skip_ties));
 ```
  */
-pub fn array_to_str(array: &Array1<i32>) -> String {
-    array
-        .iter()
-        .map(|n| {
-            if n > &0 {
-                '1'
-            } else if n < &0 {
-                '0'
-            } else {
-                'X'
-            }
-        })
-        .collect::<String>()
+pub trait Criterion {
+    /**
+    The bit to keep, or `None` to keep every number.
+     */
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool>;
+}
+
+impl<F> Criterion for F
+where
+    F: Fn(usize, usize) -> Option<bool>,
+{
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool> {
+        self(ones, zeros)
+    }
 }
 
 /**
-Find the gamma rate from a file of binary numbers.
+Keep the most common bit, or the `tie` bit when both are as common.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Majority {
+    pub tie: bool,
+}
 
-# Examples
-```
-let gamma = aoc2021::day03::file_as_gamma("test_inputs/day03.txt");
-assert_eq!(&gamma, "10110");
-```
-*/
-pub fn file_as_gamma(file: &str) -> String {
-    array_to_str(&file_sum_as_ndarray(&file))
+impl Criterion for Majority {
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool> {
+        Some(if ones == zeros { self.tie } else { ones > zeros })
+    }
 }
 
+/**
+Keep the least common bit, or the `tie` bit when both are as common.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Minority {
+    pub tie: bool,
+}
+
+impl Criterion for Minority {
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool> {
+        Some(if ones == zeros { self.tie } else { ones < zeros })
+    }
+}
+
+/**
+Keep the '1's when at least this fraction of the numbers left have a '1',
+and the '0's otherwise. `Threshold(0.5)` is a majority with ties going
+to 1.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Threshold(pub f64);
+
+impl Criterion for Threshold {
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool> {
+        Some(ones as f64 >= self.0 * (ones + zeros) as f64)
+    }
+}
+
+/**
+The puzzle's ratings: oxygen keeps the most common bit, ties going to 1,
+and CO2 the least common bit, ties going to 0.
+ */
 pub enum Gas {
     Oxygen,
     CO2
 }
 
+impl Criterion for Gas {
+    fn keep(&self, ones: usize, zeros: usize) -> Option<bool> {
+        match self {
+            Gas::Oxygen => Majority { tie: true }.keep(ones, zeros),
+            Gas::CO2 => Minority { tie: false }.keep(ones, zeros),
+        }
+    }
+}
+
 /**
-Find the rating.
+What happened in one column while finding a rating.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub column: usize,
+    pub ones: usize,
+    pub zeros: usize,
+    /**
+    The bit kept, or `None` if every number was kept.
+     */
+    pub kept: Option<bool>,
+    /**
+    The indexes of the numbers left after this column.
+     */
+    pub left: Vec<usize>,
+}
+
+/**
+How a rating was found: the numbers left after each column, until only one
+was.
+
+# Examples
+```
+use aoc2021::day03::{explain, load_report, Gas};
+
+let explanation = explain(&load_report("test_inputs/day03.txt").unwrap(), &Gas::CO2);
+assert_eq!(0b01010, explanation.rating);
+assert_eq!(3, explanation.steps.len());
+assert_eq!(vec![5, 11], explanation.steps[1].left);
+assert_eq!("\
+column 1: 7 '1's, 5 '0's, keeping '0': lines 1, 6, 7, 11, 12
+column 2: 2 '1's, 3 '0's, keeping '1': lines 6, 12
+column 3: 1 '1', 1 '0', keeping '0': line 12
+rating: 01010
+", explanation.to_string());
+```
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub steps: Vec<Step>,
+    pub rating: u128,
+    width: usize,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plural = |n: usize, what: &str| format!("{} {}{}", n, what, if n == 1 { "" } else { "s" });
+        for step in &self.steps {
+            let kept = match step.kept {
+                Some(bit) => format!("keeping '{}'", u8::from(bit)),
+                None => String::from("keeping all"),
+            };
+            let lines: Vec<String> = step.left.iter().map(|ndx| (ndx + 1).to_string()).collect();
+            writeln!(f, "column {}: {}, {}, {}: {} {}", step.column + 1, plural(step.ones, "'1'"),
+                plural(step.zeros, "'0'"), kept, if lines.len() == 1 { "line" } else { "lines" }, lines.join(", "))?;
+        }
+        writeln!(f, "rating: {:0width$b}", self.rating, width = self.width)
+    }
+}
+
+/**
+Find a rating like [`rating`], explaining which numbers were left after
+each column.
+
+# Examples
+```
+use aoc2021::day03::{explain, parse_report, Minority};
+
+let report = parse_report("11\n10\n").unwrap();
+let explanation = explain(&report, &|_, _| None);
+assert_eq!(0b11, explanation.rating);
+assert_eq!("\
+column 1: 2 '1's, 0 '0's, keeping all: lines 1, 2
+column 2: 1 '1', 1 '0', keeping all: lines 1, 2
+rating: 11
+", explanation.to_string());
+assert_eq!(0b10, explain(&report, &Minority { tie: false }).rating);
+```
+ */
+pub fn explain(report: &Report, criterion: &dyn Criterion) -> Explanation {
+    let mut steps = Vec::new();
+    let rating = filter(report, criterion, Some(&mut steps));
+    Explanation { steps, rating, width: report.width }
+}
+
+/**
+Find a rating: filter the numbers a column at a time, from the left,
+keeping those with the bit the criterion chooses, until one is left. A
+column where every number left has the same bit, or where the criterion
+chooses a bit none of them have, keeps them all. If several numbers are
+still left after the last column, the rating is the first of them.
+
+The puzzle's ratings are the [`Gas`]es.
 
 #Examples
 ```
-use aoc2021::day03::{file_to_varray, rating, Gas};
+use aoc2021::day03::{load_report, parse_report, rating, Gas};
+
+let report = load_report("test_inputs/day03.txt").unwrap();
+assert_eq!(0b10111, rating(&report, &Gas::Oxygen));
+assert_eq!(0b01010, rating(&report, &Gas::CO2));
 
-let v = file_to_varray("test_inputs/day03.txt");
-let expected = "10111";
-assert_eq!(expected, rating(&v, &Gas::Oxygen));
-let expected = "01010";
-assert_eq!(expected, rating(&v, &Gas::CO2));
+let wide = parse_report(&format!("{}\n{}\n{}\n", "1".repeat(100), "0".repeat(100), "01".repeat(50))).unwrap();
+assert_eq!((u128::MAX >> 28) / 3, rating(&wide, &Gas::Oxygen));
+assert_eq!(u128::MAX >> 28, rating(&wide, &Gas::CO2));
 ```
  */
-pub fn rating(varray: &Vec<Array1<i32>>, gas: &Gas) -> String {
-    let mut mut_varray = varray.clone();
-    let mut bit: usize = 0;
-    let array_len: usize = varray[0].len();
-
-    while mut_varray.len() > 1 {
-        let sum = sum_arrays(&mut_varray);
-        let keep: i32 = if sum[bit % array_len] >= 0 {
-            match gas {
-                Gas::Oxygen => 1,
-                Gas::CO2 => -1,
-            }
-        } else {
-            match gas {
-                Gas::Oxygen => -1,
-                Gas::CO2 => 1,
-            }
-        };
-        mut_varray.retain(|a| a[bit % array_len] == keep);
+pub fn rating(report: &Report, criterion: &dyn Criterion) -> u128 {
+    filter(report, criterion, None)
+}
 
-        bit += 1;
+fn filter(report: &Report, criterion: &dyn Criterion, mut steps: Option<&mut Vec<Step>>) -> u128 {
+    let mut left = report.everyone();
+    let mut count_left = report.len();
+    for (ndx, column) in report.columns.iter().enumerate() {
+        if count_left == 1 {
+            break;
+        }
+        let ones: usize = left.iter().zip(column).map(|(l, c)| (l & c).count_ones() as usize).sum();
+        let zeros = count_left - ones;
+        let kept = criterion.keep(ones, zeros)
+            .filter(|&keep_ones| if keep_ones { ones > 0 } else { zeros > 0 });
+        if let Some(keep_ones) = kept {
+            for (l, c) in left.iter_mut().zip(column) {
+                *l &= if keep_ones { *c } else { !*c };
+            }
+            count_left = if keep_ones { ones } else { zeros };
+        }
+        if let Some(steps) = steps.as_mut() {
+            steps.push(Step { column: ndx, ones, zeros, kept, left: members(&left).collect() });
+        }
     }
-    
-    array_to_str(&mut_varray[0])
+    let first = members(&left).next().unwrap();
+    report.numbers[first]
 }
 
 /**
-Convert a gamma rate value into an epsilon rate value by
-swapping '1's and '0's.
+The members of a bitset, in order.
+ */
+fn members(set: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    set.iter()
+        .enumerate()
+        .flat_map(|(word, bits)| (0..64).filter(move |bit| bits >> bit & 1 == 1).map(move |bit| word * 64 + bit))
+}
 
-# Example
+/**
+Multiply the gamma and epsilon rates of the binary numbers. There is no
+answer if '0' and '1' are equally common in any bit, or if the product is
+too large for 128 bits.
+*/
+pub fn power_consumption(report: &Report) -> Result<u128, AocError> {
+    report.gamma()?.checked_mul(report.epsilon()?)
+        .ok_or_else(|| AocError::Unsolvable(String::from("power consumption overflowed 128 bits")))
+}
+
+/**
+Multiply the oxygen and CO2 ratings of the binary numbers. There is no
+answer if the product is too large for 128 bits.
+
+# Examples
 ```
-let gamma = "10110";
-assert_eq!("01001", aoc2021::day03::gamma_as_epsilon(&gamma));
+let report = aoc2021::day03::parse_report(&format!("{}\n{}\n", "1".repeat(40), "0".repeat(39) + "1")).unwrap();
+assert_eq!((1 << 40) - 1, aoc2021::day03::life_support_rating(&report).unwrap());
 ```
 */
-pub fn gamma_as_epsilon(gamma: &str) -> String {
-    gamma
-        .chars()
-        .map(|c| match c {
-            '1' => '0',
-            '0' => '1',
-            _ => 'X',
-        })
-        .collect::<String>()
+pub fn life_support_rating(report: &Report) -> Result<u128, AocError> {
+    let oxygen = rating(report, &Gas::Oxygen);
+    let co2 = rating(report, &Gas::CO2);
+    oxygen.checked_mul(co2)
+        .ok_or_else(|| AocError::Unsolvable(String::from("life support rating overflowed 128 bits")))
 }
 
 /**
@@ -179,16 +449,23 @@ Run part 1 of the Day 3 exercise.
 # Examples
 ```
 let expected = 198;
-assert_eq!(expected, aoc2021::day03::run_part1("test_inputs/day03.txt"));
+assert_eq!(expected, aoc2021::day03::run_part1("test_inputs/day03.txt").unwrap());
 ```
 */
-pub fn run_part1(file: &str) -> i32 {
-    let gamma = file_as_gamma(file);
-    let epsilon = gamma_as_epsilon(&gamma);
-    let gamma = i32::from_str_radix(&gamma, 2);
-    let epsilon = i32::from_str_radix(&epsilon, 2);
+pub fn run_part1(file: &str) -> Result<u128, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of the Day 3 exercise from the text of the input.
 
-    gamma.unwrap() * epsilon.unwrap()
+# Examples
+```
+assert_eq!(3 * 4, aoc2021::day03::solve_part1("001\n011\n111\n").unwrap());
+```
+*/
+pub fn solve_part1(input: &str) -> Result<u128, AocError> {
+    power_consumption(&parse_report(input)?)
 }
 
 /**
@@ -197,15 +474,39 @@ Run part 2 of the Day 3 exercise.
 # Examples
 ```
 let expected = 230;
-assert_eq!(expected, aoc2021::day03::run_part2("test_inputs/day03.txt"));
+assert_eq!(expected, aoc2021::day03::run_part2("test_inputs/day03.txt").unwrap());
 ```
 */
-pub fn run_part2(file: &str) -> i32 {
-    let v = file_to_varray(file);
-    let oxygen = rating(&v, &Gas::Oxygen);
-    let co2 = rating(&v, &Gas::CO2);
-    let oxygen = i32::from_str_radix(&oxygen, 2);
-    let co2 = i32::from_str_radix(&co2, 2);
-
-    oxygen.unwrap() * co2.unwrap()
+pub fn run_part2(file: &str) -> Result<u128, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the Day 3 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(3 * 7, aoc2021::day03::solve_part2("001\n011\n111\n").unwrap());
+```
+*/
+pub fn solve_part2(input: &str) -> Result<u128, AocError> {
+    life_support_rating(&parse_report(input)?)
+}
+
+pub struct Day03 {
+    report: Report,
+}
+
+impl Solution for Day03 {
+    fn parse(input: &str) -> Result<Day03, AocError> {
+        Ok(Day03 { report: parse_report(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(power_consumption(&self.report)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(life_support_rating(&self.report)?.into())
+    }
 }
@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
 Convert a comma-separated string of ints to a Vec.
@@ -9,19 +9,22 @@ Convert a comma-separated string of ints to a Vec.
 # Examples
 ```
 let expected: Vec<u32> = vec![10, 1, 9, 2, 8, 3];
-assert_eq!(expected, aoc2021::day04::cs_str_to_vecint("10,1,9,2,8,3"));
+assert_eq!(expected, aoc2021::day04::cs_str_to_vecint("10,1,9,2,8,3").unwrap());
+assert!(aoc2021::day04::cs_str_to_vecint("10,1,,2").is_err());
 ```
  */
-pub fn cs_str_to_vecint(cs_numbers: &str) -> Vec<u32> {
+pub fn cs_str_to_vecint(cs_numbers: &str) -> Result<Vec<u32>, AocError> {
     cs_numbers
+        .trim_end()
         .split(',')
-        .map(|s| s.parse::<u32>().unwrap())
+        .map(|s| parse_token::<u32>(cs_numbers, s))
         .collect()
 }
 
 /**
 A representation of a bingo board, using BingoSpaces.
  */
+#[derive(Clone)]
 pub struct BingoBoard {
     space_map_by_loc: HashMap<(usize, usize), u32>,
     space_map_by_value: HashMap<u32, (usize, usize)>,
@@ -34,24 +37,36 @@ pub struct BingoBoard {
 impl BingoBoard {
     /**
     Create a BingoBoard from the space-separated values in the slice of strings.
+    Every row must have the same number of values, and no value may repeat.
+
+    # Examples
+    ```
+    use aoc2021::day04::BingoBoard;
+
+    assert!(BingoBoard::new(&["1 2", "3 4"]).is_ok());
+    let e = BingoBoard::new(&["1 2", "3 x"]).err().unwrap();
+    assert_eq!("line 2, column 3: could not parse \"x\": invalid digit found in string", e.to_string());
+    assert!(BingoBoard::new(&["1 2", "3"]).is_err());
+    assert!(BingoBoard::new(&["1 2", "3 1"]).is_err());
+    ```
      */
-    pub fn new(board_lines: &[&str]) -> BingoBoard {
+    pub fn new(board_lines: &[&str]) -> Result<BingoBoard, AocError> {
         let mut space_map_by_loc = HashMap::new();
         let mut space_map_by_value = HashMap::new();
         let mut rows = 0;
         let mut cols = 0;
 
-        let mut row_index: usize = 0;
-        for line in board_lines {
+        for (row_index, line) in board_lines.iter().enumerate() {
             if row_index >= rows {
                 rows = row_index + 1;
             }
-            let v_line: Vec<u32> = line
-                .split_whitespace()
-                .map(|s| s.parse::<u32>().unwrap())
-                .collect();
             let mut col_index: usize = 0;
-            for value in v_line {
+            for s in line.split_whitespace() {
+                let value = parse_token::<u32>(line, s).map_err(|e| e.on_line(row_index + 1))?;
+                if space_map_by_value.contains_key(&value) {
+                    return Err(AocError::parse(row_index + 1, column_of(line, s),
+                        format!("value {} repeats", value)));
+                }
                 if col_index >= cols {
                     cols = col_index + 1;
                 }
@@ -59,17 +74,23 @@ impl BingoBoard {
                 space_map_by_value.insert(value, (row_index, col_index));
                 col_index += 1;
             }
-            row_index += 1;
+            if row_index > 0 && col_index != cols {
+                return Err(AocError::parse(row_index + 1, line.len() + 1,
+                    format!("expected {} values, found {}", cols, col_index)));
+            }
+        }
+        if space_map_by_loc.is_empty() {
+            return Err(AocError::parse(1, 1, "empty board"));
         }
 
-        BingoBoard {
+        Ok(BingoBoard {
             space_map_by_loc,
             space_map_by_value,
             rows,
             cols,
             called: HashSet::new(),
             winner: false,
-        }
+        })
     }
 
     /**
@@ -134,57 +155,61 @@ impl BingoBoard {
 }
 
 /**
-Run Day 4 exercise.
+Parse the called numbers and the bingo boards. The boards are separated by
+blank lines.
 
 # Examples
 ```
-let result = aoc2021::day04::run(1, "test_inputs/day04.txt");
-assert_eq!(4512, result);
-let result = aoc2021::day04::run(2, "test_inputs/day04.txt");
-assert_eq!(1924, result);
+let (called, boards) = aoc2021::day04::parse_bingo("3,1,2\n\n1 2\n3 4\n").unwrap();
+assert_eq!(vec![3, 1, 2], called);
+assert_eq!(1, boards.len());
 ```
  */
-pub fn run(part: i32, file: &str) -> u32 {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
+pub fn parse_bingo(input: &str) -> Result<(Vec<u32>, Vec<BingoBoard>), AocError> {
+    let mut lines = input.lines();
 
-    let mut called = String::new();
-    buf_reader.read_line(&mut called).unwrap();
-    let called = cs_str_to_vecint(called.trim());
+    let called = cs_str_to_vecint(lines.next().unwrap_or(""))?;
 
     let mut boards = Vec::new();
-
-    let mut go = true;
-    while go {
-        let mut buf = String::new();
-        match buf_reader.read_line(&mut buf) {
-            Ok(n) => {
-                if n == 0 {
-                    go = false;
-                } else {
-                    let mut lines: Vec<String> = Vec::new();
-                    for _ in 0..5 {
-                        buf.clear();
-                        buf_reader.read_line(&mut buf).unwrap();
-                        let input = String::from(buf.trim());
-                        lines.push(input);
-                    }
-                    boards.push(BingoBoard::new(
-                        lines
-                            .iter()
-                            .map(|s| s.as_str())
-                            .collect::<Vec<&str>>()
-                            .as_slice(),
-                    ));
-                }
+    let mut board_lines: Vec<&str> = Vec::new();
+    let mut first_line = 0;
+    // the called numbers were line 1
+    for (ndx, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            if !board_lines.is_empty() {
+                boards.push(BingoBoard::new(&board_lines).map_err(|e| e.on_line(first_line))?);
+                board_lines.clear();
             }
-            Err(e) => {
-                panic!("{}", e);
+        } else {
+            if board_lines.is_empty() {
+                first_line = ndx + 2;
             }
+            board_lines.push(line);
         }
     }
+    if !board_lines.is_empty() {
+        boards.push(BingoBoard::new(&board_lines).map_err(|e| e.on_line(first_line))?);
+    }
 
-    for call in called {
+    Ok((called, boards))
+}
+
+/**
+Load the called numbers and the bingo boards from a file. The boards are
+separated by blank lines.
+ */
+pub fn load_bingo(file: &str) -> Result<(Vec<u32>, Vec<BingoBoard>), AocError> {
+    input::from_file(file, parse_bingo)
+}
+
+/**
+Call the numbers until the winning board is found, and return its score.
+For part 1 the first board to win is the winner; for part 2 it is the
+last board to win.
+ */
+pub fn play(part: i32, called: &[u32], boards: &[BingoBoard]) -> Result<u32, AocError> {
+    let mut boards = boards.to_vec();
+    for &call in called {
         for index in 0..boards.len() {
             if boards[index].accept_value(call) {
                 // For part 1 return the first winning board.
@@ -202,12 +227,61 @@ pub fn run(part: i32, file: &str) -> u32 {
                         .keys()
                         .filter(|space| !boards[index].get_called().contains(space))
                         .sum();
-                    return call * sum;
+                    return Ok(call * sum);
                 }
             }
         }
     }
-    panic!("Did not find winning board");
+    Err(AocError::Unsolvable(String::from("Did not find winning board")))
+}
+
+/**
+Run Day 4 exercise.
+
+# Examples
+```
+let result = aoc2021::day04::run(1, "test_inputs/day04.txt").unwrap();
+assert_eq!(4512, result);
+let result = aoc2021::day04::run(2, "test_inputs/day04.txt").unwrap();
+assert_eq!(1924, result);
+```
+ */
+pub fn run(part: i32, file: &str) -> Result<u32, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve Day 4 exercise from the text of the input.
+
+# Examples
+```
+let input = "7,4,9\n\n1 7\n4 9\n";
+assert_eq!(9 * 1, aoc2021::day04::solve(1, input).unwrap());
+```
+ */
+pub fn solve(part: i32, input: &str) -> Result<u32, AocError> {
+    let (called, boards) = parse_bingo(input)?;
+    play(part, &called, &boards)
+}
+
+pub struct Day04 {
+    called: Vec<u32>,
+    boards: Vec<BingoBoard>,
+}
+
+impl Solution for Day04 {
+    fn parse(input: &str) -> Result<Day04, AocError> {
+        let (called, boards) = parse_bingo(input)?;
+        Ok(Day04 { called, boards })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(play(1, &self.called, &self.boards)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(play(2, &self.called, &self.boards)?.into())
+    }
 }
 
 #[cfg(test)]
@@ -216,7 +290,7 @@ mod tests {
 
     #[test]
     fn test_game_row_winner() {
-        let mut board = BingoBoard::new(&["1 2 3", "11 12 13", "21 22 23"]);
+        let mut board = BingoBoard::new(&["1 2 3", "11 12 13", "21 22 23"]).unwrap();
         assert!(!board.accept_value(2));
         assert!(!board.is_winner());
         assert!(!board.accept_value(3));
@@ -229,7 +303,7 @@ mod tests {
 
     #[test]
     fn test_game_col_winner() {
-        let mut board = BingoBoard::new(&["1 2 3", "11 12 13", "21 22 23"]);
+        let mut board = BingoBoard::new(&["1 2 3", "11 12 13", "21 22 23"]).unwrap();
         assert!(!board.accept_value(2));
         assert!(!board.is_winner());
         assert!(!board.accept_value(12));
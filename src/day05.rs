@@ -1,9 +1,9 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
 Convert string to a tuple containing two (x,y) coordinates.
@@ -13,19 +13,21 @@ Convert string to a tuple containing two (x,y) coordinates.
 use std::collections::HashMap;
 
 let expected = (1,10,100,1000);
-assert_eq!(expected, aoc2021::day05::str_to_tuple("1,10 -> 100,1000"));
+assert_eq!(expected, aoc2021::day05::str_to_tuple("1,10 -> 100,1000").unwrap());
+assert!(aoc2021::day05::str_to_tuple("1,10 - 100,1000").is_err());
 ```
  */
-pub fn str_to_tuple(s: &str) -> (i32, i32, i32, i32) {
+pub fn str_to_tuple(s: &str) -> Result<(i32, i32, i32, i32), AocError> {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"(\d+),(\d+) -> (\d+),(\d+)").unwrap();
     }
-    let captures = RE.captures(s).unwrap();
+    let captures = RE.captures(s)
+        .ok_or_else(|| AocError::parse(1, 1, "expected \"x1,y1 -> x2,y2\""))?;
     let range = 1..5;
     let v: Vec<i32> = range
-        .map(|n| captures.get(n).unwrap().as_str().parse::<i32>().unwrap())
-        .collect();
-    (v[0], v[1], v[2], v[3])
+        .map(|n| parse_token::<i32>(s, captures.get(n).unwrap().as_str()))
+        .collect::<Result<Vec<i32>, AocError>>()?;
+    Ok((v[0], v[1], v[2], v[3]))
 }
 
 /**
@@ -125,34 +127,102 @@ pub fn line_coord_count(map_counts: &mut HashMap<(i32, i32), u32>, line: &(i32,
 }
 
 /**
-Run Day 5's exercise.
+Parse the vent lines, one per line of input. Each line must be horizontal,
+vertical or diagonal at 45 degrees.
 
 # Examples
 ```
-let expected = 5;
-let result = aoc2021::day05::run(1, "test_inputs/day05.txt");
-assert_eq!(expected, result);
-let expected = 12;
-let result = aoc2021::day05::run(2, "test_inputs/day05.txt");
-assert_eq!(expected, result);
+let lines = aoc2021::day05::parse_lines("0,9 -> 5,9\n8,0 -> 0,8\n").unwrap();
+assert_eq!(vec![(0, 9, 5, 9), (8, 0, 0, 8)], lines);
+assert!(aoc2021::day05::parse_lines("0,0 -> 1,2\n").is_err());
 ```
  */
-pub fn run(part: i32, file: &str) -> usize {
+pub fn parse_lines(input: &str) -> Result<Vec<(i32, i32, i32, i32)>, AocError> {
+    let mut lines = Vec::new();
+    for (ndx, s) in input.lines().enumerate() {
+        let t = str_to_tuple(s).map_err(|e| e.on_line(ndx + 1))?;
+        if t.0 != t.2 && t.1 != t.3 && (t.0 - t.2).abs() != (t.1 - t.3).abs() {
+            return Err(AocError::parse(ndx + 1, 1, "line is not horizontal, vertical or diagonal"));
+        }
+        lines.push(t);
+    }
+    Ok(lines)
+}
+
+/**
+Load the vent lines from a file. Each line must be horizontal, vertical
+or diagonal at 45 degrees.
+ */
+pub fn load_lines(file: &str) -> Result<Vec<(i32, i32, i32, i32)>, AocError> {
+    input::from_file(file, parse_lines)
+}
+
+/**
+Count the points where at least two lines overlap. Part 1 only considers
+horizontal and vertical lines; part 2 considers diagonal lines too.
+ */
+pub fn count_overlaps(part: i32, lines: &[(i32, i32, i32, i32)]) -> Result<usize, AocError> {
     let closure = if part == 1 {
-        |t: &(i32, i32, i32, i32)| t.0 == t.2 || t.1 == t.3
+        |t: &&(i32, i32, i32, i32)| t.0 == t.2 || t.1 == t.3
     } else if part == 2 {
-        |_: &(i32, i32, i32, i32)| true
+        |_: &&(i32, i32, i32, i32)| true
     } else {
-        panic!("part {} not implemented", part);
+        return Err(AocError::UnknownPart(part.to_string()));
     };
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
 
     let mut coord_counts = HashMap::new();
-    buf_reader.lines().map(|s| str_to_tuple(&s.unwrap()))
+    lines.iter()
         .filter(closure)
-        .for_each(|t| line_coord_count(&mut coord_counts, &t));
+        .for_each(|t| line_coord_count(&mut coord_counts, t));
     
-    coord_counts.values().filter(|v| v >= &&2).count()
+    Ok(coord_counts.values().filter(|v| v >= &&2).count())
+}
+
+/**
+Run Day 5's exercise.
+
+# Examples
+```
+let expected = 5;
+let result = aoc2021::day05::run(1, "test_inputs/day05.txt").unwrap();
+assert_eq!(expected, result);
+let expected = 12;
+let result = aoc2021::day05::run(2, "test_inputs/day05.txt").unwrap();
+assert_eq!(expected, result);
+```
+ */
+pub fn run(part: i32, file: &str) -> Result<usize, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve Day 5's exercise from the text of the input.
+
+# Examples
+```
+let input = "0,0 -> 2,2\n0,2 -> 2,0\n0,1 -> 2,1\n";
+assert_eq!(0, aoc2021::day05::solve(1, input).unwrap());
+assert_eq!(1, aoc2021::day05::solve(2, input).unwrap());
+```
+ */
+pub fn solve(part: i32, input: &str) -> Result<usize, AocError> {
+    count_overlaps(part, &parse_lines(input)?)
+}
+
+pub struct Day05 {
+    lines: Vec<(i32, i32, i32, i32)>,
+}
 
+impl Solution for Day05 {
+    fn parse(input: &str) -> Result<Day05, AocError> {
+        Ok(Day05 { lines: parse_lines(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_overlaps(1, &self.lines)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(count_overlaps(2, &self.lines)?.into())
+    }
 }
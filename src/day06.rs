@@ -1,46 +1,80 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::count::{self, Count, Overflow};
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::params::Params;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
+/**
+The days counted in each part, which can be changed with the `part1_days`
+and `part2_days` parameters, and whether to count exactly rather than with
+a `u128`, set with the `exact` parameter.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub part1_days: u32,
+    pub part2_days: u32,
+    pub exact: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { part1_days: 80, part2_days: 256, exact: false }
+    }
+}
+
+impl Options {
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["part1_days", "part2_days", "exact"])?;
+        let mut options = Options::default();
+        params.set("part1_days", &mut options.part1_days)?;
+        params.set("part2_days", &mut options.part2_days)?;
+        params.set("exact", &mut options.exact)?;
+        count::check_exact(options.exact)?;
+        Ok(options)
+    }
+}
 
 /**
 Represents a group of lanternfish that spawn on the same day.
  */
 #[derive(Copy, Clone, Debug)]
-pub struct FishTimer {
-    count: u128,
+pub struct FishTimer<C = u128> {
+    count: C,
     days_until_spawn: i8,
 }
 
-impl FishTimer {
-    pub fn new(count: u128, days_until_spawn: i8) -> FishTimer {
+impl<C: Count> FishTimer<C> {
+    pub fn new(count: C, days_until_spawn: i8) -> FishTimer<C> {
         FishTimer{ count, days_until_spawn }
     }
 
-    pub fn add_fish(&mut self, new: u128) {
-        self.count += new;
+    pub fn add_fish(&mut self, new: &C) -> Result<(), Overflow> {
+        self.count = self.count.checked_add(new).ok_or(Overflow)?;
+        Ok(())
     }
 
-    pub fn next_day(&mut self) -> Option<FishTimer> {
+    pub fn next_day(&mut self) -> Option<FishTimer<C>> {
         self.days_until_spawn -= 1;
         if self.days_until_spawn == -1 {
             self.days_until_spawn = 6;
-            Some(FishTimer::new(self.count, 8))
+            Some(FishTimer::new(self.count.clone(), 8))
         } else {
             None
         }
     }
 
-    pub fn merge(&mut self, other: &FishTimer) {
+    pub fn merge(&mut self, other: &FishTimer<C>) -> Result<(), Overflow> {
         if self.days_until_spawn != other.days_until_spawn {
             panic!("incompatible FishTimers");
         }
-        self.count += other.count
+        self.add_fish(&other.count)
     }
 
-    pub fn get_count(&self) -> u128 {
-        self.count
+    pub fn get_count(&self) -> C {
+        self.count.clone()
     }
 
     pub fn get_days_until_spawn(&self) -> i8 {
@@ -48,7 +82,11 @@ impl FishTimer {
     }
 }
 
-pub fn next_day(v_fish: &mut Vec<FishTimer>) {
+/**
+Move every group of lanternfish on a day. Fails if the fish that will next
+spawn together are too many to count.
+ */
+pub fn next_day<C: Count>(v_fish: &mut Vec<FishTimer<C>>) -> Result<(), Overflow> {
     let mut new_fish = Vec::new();
     for fish in v_fish.iter_mut() {
         let opt_fish = fish.next_day();
@@ -61,8 +99,7 @@ pub fn next_day(v_fish: &mut Vec<FishTimer>) {
     let mut ndx: usize = 0;
     while ndx < v_fish.len() {
         if v_fish[ndx].get_days_until_spawn() == 6 {
-            six_fish.push(v_fish[ndx]);
-            v_fish.remove(ndx);
+            six_fish.push(v_fish.remove(ndx));
         } else {
             ndx += 1;
         }
@@ -70,48 +107,182 @@ pub fn next_day(v_fish: &mut Vec<FishTimer>) {
     while six_fish.len() > 1 {
         let mut fish_one = six_fish.remove(0);
         let fish_two = six_fish.remove(0);
-        fish_one.merge(&fish_two);
+        fish_one.merge(&fish_two)?;
         six_fish.push(fish_one);        
     }
     v_fish.append(&mut six_fish);
+    Ok(())
 }
 
 /**
-Run day 6 problem.
+Parse the comma-separated lanternfish timers, grouped by the days until
+they spawn.
 
 # Examples
 ```
-let result = aoc2021::day06::run("test_inputs/day06.txt", 18);
-assert_eq!(26, result);
-let result = aoc2021::day06::run("test_inputs/day06.txt", 80);
-assert_eq!(5934, result);
+let fish = aoc2021::day06::parse_fish("3,4,3,1,2\n").unwrap();
+assert_eq!(4, fish.len());
+let e = aoc2021::day06::parse_fish("3,9").unwrap_err();
+assert_eq!("line 1, column 3: timer 9 is not between 0 and 8", e.to_string());
 ```
  */
-pub fn run(file: &str, days: u32) -> u128 {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
-    let mut line = String::new();
-    let result = buf_reader.read_line(&mut line);
-    if result.is_err() {
-        panic!("{}", result.err().unwrap());
-    }
-    let line = line.trim();
+pub fn parse_fish(input: &str) -> Result<Vec<FishTimer>, AocError> {
+    let line = input.lines().next().unwrap_or("").trim_end();
     let mut counts: HashMap<i8, u128> = HashMap::new();
-    let v_days: Vec<i8> = line.split(',').map(|s| s.parse::<i8>().unwrap()).collect();
-    for day in v_days {
-        if counts.contains_key(&day) {
-            counts.insert(day, *counts.get(&day).unwrap()+1);
-        } else {
-            counts.insert(day, 1);
+    for s in line.split(',') {
+        let day = parse_token::<i8>(line, s)?;
+        if !(0..=8).contains(&day) {
+            return Err(AocError::parse(1, column_of(line, s),
+                format!("timer {} is not between 0 and 8", day)));
         }
+        *counts.entry(day).or_insert(0) += 1;
     }
     let mut v_fish = Vec::new();
     for (day, count) in counts.iter() {
         v_fish.push(FishTimer::new(*count, *day));
     }
-    for _d in 0..days {
-        next_day(&mut v_fish);
+    Ok(v_fish)
+}
+
+/**
+Load the lanternfish from a file, grouped by the days until they spawn.
+ */
+pub fn load_fish(file: &str) -> Result<Vec<FishTimer>, AocError> {
+    input::from_file(file, parse_fish)
+}
+
+/**
+Count the lanternfish after the specified number of days.
+
+# Examples
+```
+let fish = aoc2021::day06::parse_fish("3,4,3,1,2").unwrap();
+assert_eq!(5934, aoc2021::day06::count_fish(&fish, 80).unwrap());
+let e = aoc2021::day06::count_fish(&fish, 2000).unwrap_err();
+assert!(e.to_string().starts_with("no solution: the lanternfish on day 1021 overflowed a 128-bit count"));
+```
+ */
+pub fn count_fish(fish: &[FishTimer], days: u32) -> Result<u128, AocError> {
+    count_fish_traced(fish, days, &mut NoTrace)
+}
+
+/**
+The lanternfish, drawn as the number of fish with each timer.
+ */
+struct School<'a, C>(&'a [FishTimer<C>]);
+
+impl<C: Count> fmt::Display for School<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts = vec![C::from_u128(0); 9];
+        for fish in self.0 {
+            let count = &mut counts[fish.get_days_until_spawn() as usize];
+            *count = count.checked_add(&fish.count).ok_or(fmt::Error)?;
+        }
+        match count::sum(&counts) {
+            Ok(total) => write!(f, "{} fish", total)?,
+            Err(Overflow) => write!(f, "too many fish to count")?,
+        }
+        let counts: Vec<String> = counts.iter().map(C::to_string).collect();
+        write!(f, ", by timer: [{}]", counts.join(", "))
     }
+}
+
+/**
+Count the lanternfish after the specified number of days, tracing each day.
 
-    v_fish.iter().map(|f| f.get_count()).sum()
+# Examples
+```
+let fish = aoc2021::day06::parse_fish("3,4,3,1,2").unwrap();
+let mut days = Vec::new();
+let mut tracer = |_: usize, _: &str, state: &dyn std::fmt::Display| days.push(state.to_string());
+assert_eq!(7, aoc2021::day06::count_fish_traced::<u128>(&fish, 3, &mut tracer).unwrap());
+assert_eq!("5 fish, by timer: [1, 1, 2, 1, 0, 0, 0, 0, 0]", days[0]);
+assert_eq!("6 fish, by timer: [1, 2, 1, 0, 0, 0, 1, 0, 1]", days[1]);
+
+// with the bigint feature, counts that overflow a u128 are exact
+#[cfg(feature = "bigint")]
+{
+    let mut no_trace = aoc2021::trace::NoTrace;
+    let fish: num_bigint::BigUint = aoc2021::day06::count_fish_traced(&fish, 2000, &mut no_trace).unwrap();
+    assert_eq!(77, fish.to_string().len());
+}
+```
+ */
+pub fn count_fish_traced<C: Count>(fish: &[FishTimer], days: u32, tracer: &mut dyn Tracer) -> Result<C, AocError> {
+    let mut v_fish: Vec<FishTimer<C>> = fish.iter()
+        .map(|f| FishTimer::new(C::from_u128(f.count), f.days_until_spawn))
+        .collect();
+    for day in 0..days {
+        next_day(&mut v_fish)
+            .map_err(|e| e.counting(&format!("the lanternfish on day {}", day + 1)))?;
+        tracer.step(day as usize + 1, "day", &School(&v_fish));
+    }
+
+    count::sum(v_fish.iter().map(|f| &f.count))
+        .map_err(|e| e.counting(&format!("the lanternfish after {} days", days)))
+}
+
+/**
+Run day 6 problem.
+
+# Examples
+```
+let result = aoc2021::day06::run("test_inputs/day06.txt", 18).unwrap();
+assert_eq!(26, result);
+let result = aoc2021::day06::run("test_inputs/day06.txt", 80).unwrap();
+assert_eq!(5934, result);
+```
+ */
+pub fn run(file: &str, days: u32) -> Result<u128, AocError> {
+    input::from_file(file, |input| solve(input, days))
+}
+
+/**
+Solve day 6 problem from the text of the input.
+
+# Examples
+```
+assert_eq!(26, aoc2021::day06::solve("3,4,3,1,2", 18).unwrap());
+```
+ */
+pub fn solve(input: &str, days: u32) -> Result<u128, AocError> {
+    count_fish(&parse_fish(input)?, days)
+}
+
+pub struct Day06 {
+    fish: Vec<FishTimer>,
+    options: Options,
+}
+
+impl Day06 {
+    fn count(&self, days: u32, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        #[cfg(feature = "bigint")]
+        if self.options.exact {
+            return Ok(count_fish_traced::<num_bigint::BigUint>(&self.fish, days, tracer)?.into());
+        }
+        Ok(count_fish_traced::<u128>(&self.fish, days, tracer)?.into())
+    }
+}
+
+impl Solution for Day06 {
+    fn parse(input: &str) -> Result<Day06, AocError> {
+        Ok(Day06 { fish: parse_fish(input)?, options: Options::default() })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        self.count(self.options.part1_days, &mut NoTrace)
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        self.count(self.options.part2_days, &mut NoTrace)
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        self.options = Options::from_params(params)?;
+        Ok(())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        self.count(if part == 1 { self.options.part1_days } else { self.options.part2_days }, tracer)
+    }
 }
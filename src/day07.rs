@@ -1,6 +1,6 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
 How much fuel is spent moving the crabs to the final position?
@@ -48,33 +48,82 @@ pub fn align_using_min_fuel<F>(crabs_pos: &[i32], f: &F) -> (i32, i32)
 }
 
 /**
-Run the Day 7 exercise.
+Parse the comma-separated crab positions.
 
 # Examples
 ```
-let expected = 37;
-assert_eq!(expected, aoc2021::day07::run(1, "test_inputs/day07.txt"));
-let expected = 168;
-assert_eq!(expected, aoc2021::day07::run(2, "test_inputs/day07.txt"));
+assert_eq!(vec![16, 1, 2], aoc2021::day07::parse_crabs("16,1,2\n").unwrap());
 ```
  */
-pub fn run(part: i32, file: &str) -> i32 {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
-    let mut line = String::new();
-    match buf_reader.read_line(&mut line) {
-        Err(e) => {panic!("{}", e)},
-        _ => (),
-    }
-    
+pub fn parse_crabs(input: &str) -> Result<Vec<i32>, AocError> {
+    let line = input.lines().next().unwrap_or("").trim_end();
+    line.split(',')
+        .map(|s| parse_token::<i32>(line, s))
+        .collect()
+}
+
+/**
+Load the crab positions from a file.
+ */
+pub fn load_crabs(file: &str) -> Result<Vec<i32>, AocError> {
+    input::from_file(file, parse_crabs)
+}
+
+/**
+The least fuel needed to align the crabs. In part 1 each step costs 1 fuel;
+in part 2 each step costs 1 more than the last.
+ */
+pub fn min_fuel(part: i32, crabs: &[i32]) -> Result<i32, AocError> {
     let closure: fn(i32, i32) -> i32 = match part {
         1 => |a, b| (a-b).abs(),
         2 => |a, b| (a-b).abs() * ((a-b).abs()+1) / 2,
-        _ => panic!("Unexpected part {}", part),
+        _ => return Err(AocError::UnknownPart(part.to_string())),
     };
-    let crabs: Vec<i32> = line.trim().split(',')
-        .map(|s| s.parse::<i32>().unwrap())
-        .collect();
-    
-    align_using_min_fuel(&crabs, &closure).0
+
+    Ok(align_using_min_fuel(crabs, &closure).0)
+}
+
+/**
+Run the Day 7 exercise.
+
+# Examples
+```
+let expected = 37;
+assert_eq!(expected, aoc2021::day07::run(1, "test_inputs/day07.txt").unwrap());
+let expected = 168;
+assert_eq!(expected, aoc2021::day07::run(2, "test_inputs/day07.txt").unwrap());
+```
+ */
+pub fn run(part: i32, file: &str) -> Result<i32, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve the Day 7 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(37, aoc2021::day07::solve(1, "16,1,2,0,4,2,7,1,2,14").unwrap());
+```
+ */
+pub fn solve(part: i32, input: &str) -> Result<i32, AocError> {
+    min_fuel(part, &parse_crabs(input)?)
+}
+
+pub struct Day07 {
+    crabs: Vec<i32>,
+}
+
+impl Solution for Day07 {
+    fn parse(input: &str) -> Result<Day07, AocError> {
+        Ok(Day07 { crabs: parse_crabs(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(min_fuel(1, &self.crabs)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(min_fuel(2, &self.crabs)?.into())
+    }
 }
@@ -1,34 +1,100 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{column_of, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
-Run part 1 of the Day 8 exercise:
-Count the number output characters with a unique number of segments.
+Parse the notes, one entry per line. Each entry holds the ten unique signal
+patterns followed by the four output digits.
 
-# Example
+# Examples
 ```
-assert_eq!(26, aoc2021::day08::run_part1("test_inputs/day08.txt"));
+let input = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+assert_eq!(14, aoc2021::day08::parse_entries(input).unwrap()[0].len());
+assert!(aoc2021::day08::parse_entries("ab | cd").is_err());
 ```
  */
-pub fn run_part1(file: &str) -> usize {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    buf_reader
-        .lines()
-        .map(|s| {
-            s.unwrap()
-                .split('|')
-                .nth(1)
-                .unwrap()
-                .split_whitespace()
-                .filter(|s| [2, 3, 4, 7].contains(&s.len()))
+pub fn parse_entries(input: &str) -> Result<Vec<Vec<Display>>, AocError> {
+    let mut entries = Vec::new();
+    for (ndx, line) in input.lines().enumerate() {
+        entries.push(parse_entry(line).map_err(|e| e.on_line(ndx + 1))?);
+    }
+    Ok(entries)
+}
+
+/**
+Load the notes from a file. Each entry holds the ten unique signal
+patterns followed by the four output digits.
+ */
+pub fn load_entries(file: &str) -> Result<Vec<Vec<Display>>, AocError> {
+    input::from_file(file, parse_entries)
+}
+
+/**
+Parse one entry of the notes: ten patterns, a '|', then four outputs.
+ */
+fn parse_entry(line: &str) -> Result<Vec<Display>, AocError> {
+    let halves: Vec<&str> = line.split('|').collect();
+    if halves.len() != 2 {
+        return Err(AocError::parse(1, 1, "expected one '|' between the patterns and the outputs"));
+    }
+    let mut displays = Vec::new();
+    for (half, expected) in halves.iter().zip([10, 4]) {
+        let segments: Vec<&str> = half.split_whitespace().collect();
+        if segments.len() != expected {
+            return Err(AocError::parse(1, column_of(line, half),
+                format!("expected {} displays, found {}", expected, segments.len())));
+        }
+        for s in segments {
+            if let Some(c) = s.chars().find(|c| !('a'..='g').contains(c)) {
+                return Err(AocError::parse(1, column_of(line, s),
+                    format!("unexpected character '{}'", c)));
+            }
+            displays.push(Display::new(s));
+        }
+    }
+    Ok(displays)
+}
+
+/**
+Count the output digits with a unique number of segments.
+ */
+pub fn count_unique_outputs(entries: &[Vec<Display>]) -> usize {
+    entries.iter()
+        .map(|v| {
+            v[10..].iter()
+                .filter(|d| [2, 3, 4, 7].contains(&d.get_lit_segments_as_u8().count_ones()))
                 .count()
         })
         .sum()
 }
 
+/**
+Run part 1 of the Day 8 exercise:
+Count the number output characters with a unique number of segments.
+
+# Example
+```
+assert_eq!(26, aoc2021::day08::run_part1("test_inputs/day08.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<usize, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of the Day 8 exercise from the text of the input.
+
+# Example
+```
+let input = "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe";
+assert_eq!(2, aoc2021::day08::solve_part1(input).unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<usize, AocError> {
+    Ok(count_unique_outputs(&parse_entries(input)?))
+}
+
 #[derive(PartialEq, Eq, Hash)]
 pub struct Display {
     segments: u8,
@@ -326,27 +392,57 @@ pub fn get_output(output: &[Display], display_map: &HashMap<&Display, u8>) -> u3
         .sum()
 }
 
+/**
+Decode every entry and sum the output values.
+ */
+pub fn sum_outputs(entries: &[Vec<Display>]) -> u32 {
+    entries.iter()
+        .map(|v| {
+            let display_map = analyze_signal(&v[..10]);
+            get_output(&v[10..], &display_map)
+        }).sum()
+}
+
 /**
 Run part 2 of the Day 8 exercise.
 
 # Examples
 ```
 let expected = 61229;
-assert_eq!(expected, aoc2021::day08::run_part2("test_inputs/day08.txt"));
+assert_eq!(expected, aoc2021::day08::run_part2("test_inputs/day08.txt").unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> u32 {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    
-    buf_reader.lines()
-        .map(|s| {
-            let v: Vec<Display> = s.unwrap()
-                .split(' ')
-                .filter(|st| st != &"|")
-                .map(|s| Display::new(s))
-                .collect();
-            let display_map = analyze_signal(&v[..10]);
-            get_output(&v[10..], &display_map)
-        }).sum()
+pub fn run_part2(file: &str) -> Result<u32, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the Day 8 exercise from the text of the input.
+
+# Examples
+```
+let input = "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf";
+assert_eq!(5353, aoc2021::day08::solve_part2(input).unwrap());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<u32, AocError> {
+    Ok(sum_outputs(&parse_entries(input)?))
+}
+
+pub struct Day08 {
+    entries: Vec<Vec<Display>>,
+}
+
+impl Solution for Day08 {
+    fn parse(input: &str) -> Result<Day08, AocError> {
+        Ok(Day08 { entries: parse_entries(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_unique_outputs(&self.entries).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(sum_outputs(&self.entries).into())
+    }
 }
@@ -1,51 +1,25 @@
-use std::collections::{HashMap,HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::collections::HashSet;
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors::Neighborhood;
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
 Find the coordinates of the low points in this cave.
 
 # Examples
 ```
-use std::collections::HashMap;
-let cave = HashMap::from([
-    ((0,0),2),
-    ((0,1),1),
-    ((0,2),9),
-    ((1,0),3),
-    ((1,1),9),
-    ((1,2),8),
-    ((2,0),9),
-    ((2,1),8),
-    ((2,2),5),
-]);
+let cave = aoc2021::day09::parse_cave("219\n398\n985\n").unwrap();
 let result = aoc2021::day09::find_low_points(&cave);
-assert_eq!(2, result.len());
-assert!(result.contains(&&(0,1)));
-assert!(result.contains(&&(2,2)));
+assert_eq!(vec![(0, 1), (2, 2)], result);
 ```
 */
-pub fn find_low_points<'a>(cave: &'a HashMap<(i32, i32), u8>) -> Vec<&'a (i32,i32)> {
-    let mut low_points = Vec::new();
-    for (coord, depth) in cave.iter() {
-        let neighbors = vec![
-            (coord.0 - 1, coord.1),
-            (coord.0 + 1, coord.1),
-            (coord.0, coord.1 - 1),
-            (coord.0, coord.1 + 1),
-        ];
-        let higher_or_equal = neighbors.iter()
-            .map(|n| cave.get(n))
-            .filter(|o| match o {
-                None => false,
-                Some(v) => depth >= v,
-            }).count();
-        if higher_or_equal == 0 {
-            low_points.push(coord);
-        }
-    }
-    low_points
+pub fn find_low_points(cave: &Grid<u8>) -> Vec<(usize, usize)> {
+    cave.iter()
+        .filter(|&(coord, depth)| cave.neighbors(coord, Neighborhood::Orthogonal).all(|n| cave[n] > *depth))
+        .map(|(coord, _)| coord)
+        .collect()
 }
 
 /**
@@ -53,62 +27,28 @@ Find all the coordinates in the basin containing this low point.
 
 # Examples
 ```
-use std::collections::HashMap;
-let cave = HashMap::from([
-    ((0,0),2),
-    ((0,1),1),
-    ((0,2),9),
-    ((1,0),3),
-    ((1,1),9),
-    ((1,2),8),
-    ((2,0),9),
-    ((2,1),8),
-    ((2,2),5),
-]);
-let low_point = (0,1);
-let result = aoc2021::day09::find_basin(&cave, &low_point);
+let cave = aoc2021::day09::parse_cave("219\n398\n985\n").unwrap();
+let result = aoc2021::day09::find_basin(&cave, (0, 1));
 assert_eq!(3, result.len());
-assert!(result.contains(&&(0,1)));
-assert!(result.contains(&&(0,0)));
-assert!(result.contains(&&(1,0)));
-let low_point = (2,2);
-let result = aoc2021::day09::find_basin(&cave, &low_point);
+assert!(result.contains(&(0,1)));
+assert!(result.contains(&(0,0)));
+assert!(result.contains(&(1,0)));
+let result = aoc2021::day09::find_basin(&cave, (2, 2));
 assert_eq!(3, result.len());
-assert!(result.contains(&&(2,2)));
-assert!(result.contains(&&(2,1)));
-assert!(result.contains(&&(1,2)));
+assert!(result.contains(&(2,2)));
+assert!(result.contains(&(2,1)));
+assert!(result.contains(&(1,2)));
 ```
  */
-pub fn find_basin<'a>(cave: &'a HashMap<(i32, i32), u8>, low_point: &'a(i32, i32)) -> HashSet<&'a(i32, i32)> {
+pub fn find_basin(cave: &Grid<u8>, low_point: (usize, usize)) -> HashSet<(usize, usize)> {
     let mut basin = HashSet::new();
     let mut process_queue = vec![low_point];
-    while process_queue.len() > 0 {
-        let coord = process_queue.pop().unwrap();
-        let coord_height = cave.get(coord).unwrap();
-        let mut neighbors = Vec::new();
-        for neighbor in [
-            (coord.0 - 1, coord.1),
-            (coord.0 + 1, coord.1),
-            (coord.0, coord.1 - 1),
-            (coord.0, coord.1 + 1),
-        ]{
-            match cave.get_key_value(&neighbor) {
-                None => (),
-                Some(kv) => {neighbors.push(kv.0);}
-            }
-        }
-        process_queue.extend(neighbors.iter()
+    while let Some(coord) = process_queue.pop() {
+        let coord_height = cave[coord];
+        process_queue.extend(cave.neighbors(coord, Neighborhood::Orthogonal)
             .filter(|neighbor| {
-                if basin.contains(*neighbor) {
-                    false
-                } else {
-                    match cave.get(&neighbor) {
-                        None => false,
-                        Some(neighbor_height) => 
-                            neighbor_height > coord_height && neighbor_height != &9
-
-                    }
-                }
+                let neighbor_height = cave[*neighbor];
+                !basin.contains(neighbor) && neighbor_height > coord_height && neighbor_height != 9
             }));
         basin.insert(coord);
     }
@@ -116,45 +56,99 @@ pub fn find_basin<'a>(cave: &'a HashMap<(i32, i32), u8>, low_point: &'a(i32, i32
 }
 
 /**
-Run the Day 9 exercise.
+Parse the cave heights, indexed by (row,col) coordinates.
 
 # Examples
 ```
-assert_eq!(15, aoc2021::day09::run(1, "test_inputs/day09.txt"));
-assert_eq!(1134, aoc2021::day09::run(2, "test_inputs/day09.txt"));
+let cave = aoc2021::day09::parse_cave("219\n398\n").unwrap();
+assert_eq!(Some(&8), cave.get(1, 2));
+let e = aoc2021::day09::parse_cave("219\n3x8\n").unwrap_err();
+assert_eq!("line 2, column 2: expected a digit, found 'x'", e.to_string());
 ```
  */
-pub fn run(part: i32, file: &str) -> u32 {
-    if part != 1 && part != 2 {
-        panic!("Unexpected part {}", part);
-    }
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
+pub fn parse_cave(input: &str) -> Result<Grid<u8>, AocError> {
+    Grid::parse_digits(input)
+}
+
+/**
+Load the cave heights from a file, indexed by (row,col) coordinates.
+ */
+pub fn load_cave(file: &str) -> Result<Grid<u8>, AocError> {
+    input::from_file(file, parse_cave)
+}
+
+/**
+Sum the risk levels of the low points.
+ */
+pub fn risk_level_sum(cave: &Grid<u8>) -> u32 {
+    find_low_points(cave).iter()
+        .map(|coord| cave[*coord] as u32 + 1)
+        .sum()
+}
 
-    let mut cave = HashMap::new();
-    let lines: Vec<String> = buf_reader.lines()
-        .map(|line| line.unwrap())
+/**
+Multiply the sizes of the three largest basins.
+ */
+pub fn basin_product(cave: &Grid<u8>) -> Result<u32, AocError> {
+    let mut basin_sizes: Vec<usize> = find_low_points(cave).iter()
+        .map(|low_point| find_basin(cave, *low_point).len())
         .collect();
-    for line_number in 0..lines.len() {
-        for char_number in 0..lines[line_number].len() {
-            let depth = lines[line_number].chars().nth(char_number).unwrap()
-                .to_digit(10).unwrap() as u8;
-            cave.insert((line_number as i32, char_number as i32), depth);
-        }
+    if basin_sizes.len() < 3 {
+        return Err(AocError::Unsolvable(format!("found {} basins, need 3", basin_sizes.len())));
+    }
+    basin_sizes.sort_by(|a,b| a.cmp(b).reverse());
+    let product = basin_sizes[0] * basin_sizes[1] * basin_sizes[2];
+
+    Ok(product as u32)
+}
+
+/**
+Run the Day 9 exercise.
+
+# Examples
+```
+assert_eq!(15, aoc2021::day09::run(1, "test_inputs/day09.txt").unwrap());
+assert_eq!(1134, aoc2021::day09::run(2, "test_inputs/day09.txt").unwrap());
+```
+ */
+pub fn run(part: i32, file: &str) -> Result<u32, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve the Day 9 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!((1 + 1) + (8 + 1), aoc2021::day09::solve(1, "219\n398\n").unwrap());
+```
+ */
+pub fn solve(part: i32, input: &str) -> Result<u32, AocError> {
+    if part != 1 && part != 2 {
+        return Err(AocError::UnknownPart(part.to_string()));
     }
-    
-    let low_points = find_low_points(&cave);
+    let cave = parse_cave(input)?;
     if part == 1 {
-        return low_points.iter()
-            .map(|coord| *cave.get(*coord).unwrap() as u32 + 1)
-            .sum();
+        Ok(risk_level_sum(&cave))
+    } else {
+        basin_product(&cave)
     }
+}
 
-    let mut basin_sizes: Vec<usize> = low_points.iter()
-        .map(|low_point| find_basin(&cave, &low_point).len())
-        .collect();
-    basin_sizes.sort_by(|a,b| a.cmp(b).reverse());
-    let product = basin_sizes[0] * basin_sizes[1] * basin_sizes[2];
+pub struct Day09 {
+    cave: Grid<u8>,
+}
 
-    product as u32
-}
\ No newline at end of file
+impl Solution for Day09 {
+    fn parse(input: &str) -> Result<Day09, AocError> {
+        Ok(Day09 { cave: parse_cave(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(risk_level_sum(&self.cave).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(basin_product(&self.cave)?.into())
+    }
+}
@@ -1,6 +1,6 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct Chunk {
@@ -142,19 +142,42 @@ impl Chunk {
 }
 
 /**
-Find the score of the corrupted lines.
+Parse the navigation subsystem lines. Lines may only hold the characters
+`()[]{}<>`.
+
 # Examples
 ```
-assert_eq!(26397, aoc2021::day10::run_part1("test_inputs/day10.txt"));
+assert_eq!(vec!["[({(<(())[]>[[{[]{<()<>>"], aoc2021::day10::parse_lines("[({(<(())[]>[[{[]{<()<>>\n").unwrap());
+let e = aoc2021::day10::parse_lines("()\n(x)\n").unwrap_err();
+assert_eq!("line 2, column 2: unexpected character 'x'", e.to_string());
 ```
  */
-pub fn run_part1(file: &str) -> i32 {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    buf_reader.lines()
-        .map(|wrapped_line| {
-            let line = wrapped_line.unwrap();
-            match Chunk::parse(&line) {
+pub fn parse_lines(input: &str) -> Result<Vec<String>, AocError> {
+    let mut lines = Vec::new();
+    for (ndx, line) in input.lines().enumerate() {
+        if let Some((col, c)) = line.chars().enumerate().find(|(_, c)| !"()[]{}<>".contains(*c)) {
+            return Err(AocError::parse(ndx + 1, col + 1, format!("unexpected character '{}'", c)));
+        }
+        lines.push(String::from(line));
+    }
+    Ok(lines)
+}
+
+/**
+Load the navigation subsystem lines from a file. Lines may only hold
+the characters `()[]{}<>`.
+ */
+pub fn load_lines(file: &str) -> Result<Vec<String>, AocError> {
+    input::from_file(file, parse_lines)
+}
+
+/**
+Sum the scores of the illegal characters in the corrupted lines.
+ */
+pub fn syntax_error_score(lines: &[String]) -> i32 {
+    lines.iter()
+        .map(|line| {
+            match Chunk::parse(line) {
                 ParseResult::Corrupt(ndx) => {
                     match line.chars().nth(ndx).unwrap() {
                         ')' => 3,
@@ -170,27 +193,83 @@ pub fn run_part1(file: &str) -> i32 {
 }
 
 /**
-Find the median score of the incomplete lines.
-# Examples
-```
-assert_eq!(288957, aoc2021::day10::run_part2("test_inputs/day10.txt"));
-```
+Find the median completion score of the incomplete lines.
  */
-
-pub fn run_part2(file: &str) -> i128 {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let mut scores: Vec<i128> = buf_reader.lines()
-        .map(|wrapped_line| {
-            let line = wrapped_line.unwrap();
-            match Chunk::parse(&line) {
+pub fn completion_score(lines: &[String]) -> Result<i128, AocError> {
+    let mut scores: Vec<i128> = lines.iter()
+        .filter_map(|line| {
+            match Chunk::parse(line) {
                 ParseResult::Incomplete(score) => Some(score),
                 _ => None,
             }
-        }).filter(|o| o.is_some())
-        .map(|o| o.unwrap())
+        })
         .collect();
-    scores.sort_by(|a,b| a.cmp(b));
+    if scores.is_empty() {
+        return Err(AocError::Unsolvable(String::from("no incomplete lines")));
+    }
+    scores.sort();
     
-    *scores.get(scores.len()/2 as usize).unwrap()
+    Ok(scores[scores.len()/2])
+}
+
+/**
+Find the score of the corrupted lines.
+# Examples
+```
+assert_eq!(26397, aoc2021::day10::run_part1("test_inputs/day10.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<i32, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Find the score of the corrupted lines in the text of the input.
+# Examples
+```
+assert_eq!(1197, aoc2021::day10::solve_part1("{([(<{}[<>[]}>{[]{[(<()>\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<i32, AocError> {
+    Ok(syntax_error_score(&parse_lines(input)?))
+}
+
+/**
+Find the median score of the incomplete lines.
+# Examples
+```
+assert_eq!(288957, aoc2021::day10::run_part2("test_inputs/day10.txt").unwrap());
+```
+ */
+pub fn run_part2(file: &str) -> Result<i128, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Find the median score of the incomplete lines in the text of the input.
+# Examples
+```
+assert_eq!(294, aoc2021::day10::solve_part2("<{([{{}}[<[[[<>{}]]]>[]]\n").unwrap());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<i128, AocError> {
+    completion_score(&parse_lines(input)?)
+}
+
+pub struct Day10 {
+    lines: Vec<String>,
+}
+
+impl Solution for Day10 {
+    fn parse(input: &str) -> Result<Day10, AocError> {
+        Ok(Day10 { lines: parse_lines(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(syntax_error_score(&self.lines).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(completion_score(&self.lines)?.into())
+    }
 }
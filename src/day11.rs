@@ -1,133 +1,202 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-
-struct NeighborCounter<'a> {
-    coord: &'a (i32, i32),
-    row: i32,
-    col: i32,
-}
-
-impl<'a> NeighborCounter<'a> {
-    fn new(coord: &'a (i32, i32)) -> NeighborCounter {
-        NeighborCounter {
-            coord,
-            row: coord.0 - 1,
-            col: coord.1 - 2,
-        }
-    }
-}
-
-impl<'a> Iterator for NeighborCounter<'a> {
-    type Item = (i32, i32);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.row >= self.coord.0 + 1 && self.col >= self.coord.1 + 1 {
-            None
-        } else {
-            if self.col <= self.coord.1 {
-                self.col += if self.row == self.coord.0 && self.col == self.coord.1 - 1 {
-                    2
-                } else {
-                    1
-                };
-            } else {
-                self.row += 1;
-                self.col = &self.coord.1 - 1;
-            }
-            Some((self.row, self.col))
-        }
-    }
-}
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors::Neighborhood;
+use crate::input;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
-pub fn step(octopuses: &mut HashMap<(i32, i32), i32>) -> u32 {
-    for (_, energy) in octopuses.iter_mut() {
+/**
+Advance the octopuses by one step, returning how many flashed.
+ */
+pub fn step(octopuses: &mut Grid<u8>) -> u32 {
+    for energy in octopuses.values_mut() {
         *energy += 1;
     }
     let mut flash_count = 0;
 
     // octopuses that will flash on this step
-    let mut yet_to_flash: HashSet<(i32, i32)> = octopuses
-        .iter()
-        .filter(|o| o.1 >= &10)
-        .map(|o| *o.0)
+    let mut yet_to_flash: Vec<(usize, usize)> = octopuses.iter()
+        .filter(|(_, energy)| **energy >= 10)
+        .map(|(coord, _)| coord)
         .collect();
-    let mut flashed: HashSet<(i32, i32)> = HashSet::new();
-    while yet_to_flash.len() > 0 {
-        let flasher = yet_to_flash.iter().next().unwrap().clone();
-        yet_to_flash.remove(&flasher);
+    while let Some(flasher) = yet_to_flash.pop() {
+        octopuses[flasher] = 0;
+        flash_count += 1;
         // increment the neighbors' energy, if they have not flashed
-        let neighbors: Vec<(i32, i32)> = NeighborCounter::new(&flasher)
-            .filter(|n| octopuses.contains_key(n) && !flashed.contains(n))
-            .collect();
-        for n in neighbors {
-            *octopuses.get_mut(&n).unwrap() += 1;
-            if octopuses.get(&n).unwrap() >= &10 {
-                yet_to_flash.insert(n);
+        for n in octopuses.neighbors(flasher, Neighborhood::All) {
+            let energy = &mut octopuses[n];
+            if *energy != 0 {
+                *energy += 1;
+                if *energy == 10 {
+                    yet_to_flash.push(n);
+                }
             }
         }
-        octopuses.insert(flasher, 0);
-        flashed.insert(flasher);
-        flash_count += 1;
     }
     flash_count
 }
 
-pub fn file_to_octopuses(file: &str) -> HashMap<(i32,i32),i32> {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let mut octopuses = HashMap::new();
-    let mut row = 0;
-    for line in buf_reader.lines() {
-        match line {
-            Ok(line_str) => {
-                for col in 0..line_str.len() {
-                    octopuses.insert((row, col as i32), line_str[col..col+1].parse::<i32>().unwrap());
-                }
-            },
-            _ => {panic!("Error reading line");},
-        };
-        row += 1;
-    }
-    octopuses
+/**
+Parse the energy levels of the octopuses, indexed by (row,col) coordinates.
+
+# Examples
+```
+let octopuses = aoc2021::day11::parse_octopuses("11111\n19991\n").unwrap();
+assert_eq!(Some(&9), octopuses.get(1, 2));
+```
+ */
+pub fn parse_octopuses(input: &str) -> Result<Grid<u8>, AocError> {
+    Grid::parse_digits(input)
 }
+
+pub fn file_to_octopuses(file: &str) -> Result<Grid<u8>, AocError> {
+    input::from_file(file, parse_octopuses)
+}
+
 /**
-Run part 1 of of day 11's exercise.
+Count the flashes over the specified number of steps.
+ */
+pub fn count_flashes(octopuses: &Grid<u8>, steps: u32) -> u32 {
+    count_flashes_traced(octopuses, steps, &mut NoTrace)
+}
+
+/**
+Count the flashes over the specified number of steps, tracing the energy
+levels after each step.
 
 # Examples
 ```
-let expected = 1656;
-assert_eq!(expected, aoc2021::day11::run_part1("test_inputs/day11.txt"));
+let octopuses = aoc2021::day11::parse_octopuses("11111\n19991\n19191\n19991\n11111\n").unwrap();
+let mut grids = Vec::new();
+let mut tracer = |_: usize, _: &str, state: &dyn std::fmt::Display| grids.push(state.to_string());
+assert_eq!(9, aoc2021::day11::count_flashes_traced(&octopuses, 2, &mut tracer));
+assert_eq!("34543\n40004\n50005\n40004\n34543", grids[0]);
+assert_eq!("45654\n51115\n61116\n51115\n45654", grids[1]);
 ```
  */
-pub fn run_part1(file: &str) -> u32 {
-    let mut octopuses = file_to_octopuses(file);
+pub fn count_flashes_traced(octopuses: &Grid<u8>, steps: u32, tracer: &mut dyn Tracer) -> u32 {
+    let mut octopuses = octopuses.clone();
     let mut flash_count = 0;
-    for _ in 0..100 {
+    for n in 0..steps {
         flash_count += step(&mut octopuses);
+        tracer.step(n as usize + 1, "flash", &octopuses);
     }
     flash_count
 }
 
 /**
-Run part 2 of of day 11's exercise.
+The most steps to wait for every octopus to flash at once. Some grids
+never flash together, and go round the same energy levels forever.
+ */
+pub const MAX_STEPS: u32 = 10_000;
+
+/**
+Count the steps until every octopus flashes at once, giving up after
+[`MAX_STEPS`] steps.
 
 # Examples
 ```
-let expected = 195;
-assert_eq!(expected, aoc2021::day11::run_part2("test_inputs/day11.txt"));
+let octopuses = aoc2021::day11::parse_octopuses("99\n99\n").unwrap();
+assert_eq!(1, aoc2021::day11::first_synchronized(&octopuses).unwrap());
+let octopuses = aoc2021::day11::parse_octopuses("02\n").unwrap();
+let e = aoc2021::day11::first_synchronized(&octopuses).unwrap_err();
+assert_eq!("no solution: the octopuses did not all flash at once within 10000 steps", e.to_string());
 ```
  */
-pub fn run_part2(file: &str) -> u32 {
-    let mut octopuses = file_to_octopuses(file);
+pub fn first_synchronized(octopuses: &Grid<u8>) -> Result<u32, AocError> {
+    first_synchronized_traced(octopuses, &mut NoTrace)
+}
+
+/**
+Count the steps until every octopus flashes at once, tracing the energy
+levels after each step.
+ */
+pub fn first_synchronized_traced(octopuses: &Grid<u8>, tracer: &mut dyn Tracer) -> Result<u32, AocError> {
+    let mut octopuses = octopuses.clone();
     let mut steps = 0;
-    loop {
-        if octopuses.values().filter(|v| v != &&0).count() == 0 {
-            return steps;
+    while !octopuses.values().all(|v| *v == 0) {
+        if steps == MAX_STEPS {
+            return Err(AocError::Unsolvable(format!("the octopuses did not all flash at once within {} steps", MAX_STEPS)));
         }
         step(&mut octopuses);
         steps += 1;
+        tracer.step(steps as usize, "flash", &octopuses);
+    }
+    Ok(steps)
+}
+
+/**
+Run part 1 of of day 11's exercise.
+
+# Examples
+```
+let expected = 1656;
+assert_eq!(expected, aoc2021::day11::run_part1("test_inputs/day11.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<u32, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of day 11's exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(40, aoc2021::day11::solve_part1("99\n99\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<u32, AocError> {
+    Ok(count_flashes(&parse_octopuses(input)?, 100))
+}
+
+/**
+Run part 2 of of day 11's exercise.
+
+# Examples
+```
+let expected = 195;
+assert_eq!(expected, aoc2021::day11::run_part2("test_inputs/day11.txt").unwrap());
+```
+ */
+pub fn run_part2(file: &str) -> Result<u32, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of day 11's exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(1, aoc2021::day11::solve_part2("99\n99\n").unwrap());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<u32, AocError> {
+    first_synchronized(&parse_octopuses(input)?)
+}
+
+pub struct Day11 {
+    octopuses: Grid<u8>,
+}
+
+impl Solution for Day11 {
+    fn parse(input: &str) -> Result<Day11, AocError> {
+        Ok(Day11 { octopuses: parse_octopuses(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_flashes(&self.octopuses, 100).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(first_synchronized(&self.octopuses)?.into())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        match part {
+            1 => Ok(count_flashes_traced(&self.octopuses, 100, tracer).into()),
+            _ => Ok(first_synchronized_traced(&self.octopuses, tracer)?.into()),
+        }
     }
 }
 
@@ -137,7 +206,7 @@ mod tests {
 
     #[test]
     fn test_iter() {
-        let result: Vec<(i32, i32)> = NeighborCounter::new(&(74, 90)).collect();
+        let result: Vec<(i64, i64)> = crate::neighbors::around((74, 90), Neighborhood::All).collect();
         let expected = vec![
             (73, 89),
             (73, 90),
@@ -153,33 +222,27 @@ mod tests {
 
     #[test]
     fn test_step() {
-        use super::*;
-        let mut octopuses: HashMap<(i32, i32), i32> = HashMap::new();
-        for row in 0..5 {
-            for col in 0..5 {
-                octopuses.insert((row, col), 1);
-            }
-        }
+        let mut octopuses = Grid::new(5, 5, 1);
         for row in 1..4 {
             for col in 1..4 {
                 if (row, col) != (2, 2) {
-                    octopuses.insert((row, col), 9);
+                    octopuses[(row, col)] = 9;
                 }
             }
         }
         assert_eq!(9, step(&mut octopuses));
         for coord in vec![(0,0),(0,4),(4,0),(4,4)] {
-            assert_eq!(&3, octopuses.get(&coord).unwrap());
+            assert_eq!(3, octopuses[coord]);
         }
         for coord in vec![(0,1),(0,3),(1,0),(1,4),(3,0),(3,4),(4,1),(4,3)] {
-            assert_eq!(&4, octopuses.get(&coord).unwrap());
+            assert_eq!(4, octopuses[coord]);
         }
         for coord in vec![(0,2),(2,0),(2,4),(4,2)] {
-            assert_eq!(&5, octopuses.get(&coord).unwrap());
+            assert_eq!(5, octopuses[coord]);
         }
         for row in 1..4 {
             for col in 1..4 {
-                assert_eq!(&0, octopuses.get(&(row,col)).unwrap());
+                assert_eq!(0, octopuses[(row,col)]);
             }
         }
         assert_eq!(0, step(&mut octopuses));
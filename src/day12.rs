@@ -1,17 +1,26 @@
 use std::collections::{HashMap,HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
-Load caves from file into HashMap of cave names and neighbors.
+Parse caves into HashMap of cave names and neighbors.
+
+# Examples
+```
+let caves = aoc2021::day12::parse_caves("start-A\nA-end\n").unwrap();
+assert_eq!(vec!["start", "end"], caves["A"]);
+let e = aoc2021::day12::parse_caves("start-A\nA\n").unwrap_err();
+assert_eq!("line 2, column 1: expected two caves joined by '-', found \"A\"", e.to_string());
+```
  */
-pub fn load_caves(file: &str) -> HashMap<String, Vec<String>> {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
+pub fn parse_caves(input: &str) -> Result<HashMap<String, Vec<String>>, AocError> {
     let mut caves: HashMap<String, Vec<String>> = HashMap::new();
-    for line in buf_reader.lines() {
-        let path: Vec<String> = line.unwrap().split('-').map(|s| String::from(s)).collect();
+    for (ndx, line) in input.lines().enumerate() {
+        let path: Vec<String> = line.split('-').map(String::from).collect();
+        if path.len() != 2 || path.iter().any(|cave| cave.is_empty()) {
+            return Err(AocError::parse(ndx + 1, 1, format!("expected two caves joined by '-', found \"{}\"", line)));
+        }
         for ndx in 0..2 {
             match caves.get_mut(&path[ndx]) {
                 Some(v) => {v.push(path[(ndx+1)%2].clone());},
@@ -19,7 +28,14 @@ pub fn load_caves(file: &str) -> HashMap<String, Vec<String>> {
             }
         }
     }
-    caves
+    Ok(caves)
+}
+
+/**
+Load caves from file into HashMap of cave names and neighbors.
+ */
+pub fn load_caves(file: &str) -> Result<HashMap<String, Vec<String>>, AocError> {
+    input::from_file(file, parse_caves)
 }
 
 /**
@@ -34,7 +50,7 @@ pub fn find_paths(caves: &HashMap<String, Vec<String>>, part: usize) -> HashSet<
         let this_path = incomplete_paths.iter().next().unwrap().clone();
         incomplete_paths.remove(&this_path);
         let last_cave: String = this_path.iter().next_back().unwrap().clone();
-        caves.get(&last_cave).unwrap().iter()
+        caves.get(&last_cave).into_iter().flatten()
             .for_each(|cave| {
                 if cave == "start" {
                     return;
@@ -77,16 +93,48 @@ Run Day 12's exercise.
 
 # Examples
 ```
-assert_eq!(10, aoc2021::day12::run("test_inputs/day12_1.txt", 1));
-assert_eq!(19, aoc2021::day12::run("test_inputs/day12_2.txt", 1));
-assert_eq!(226, aoc2021::day12::run("test_inputs/day12_3.txt", 1));
-assert_eq!(36, aoc2021::day12::run("test_inputs/day12_1.txt", 2));
-assert_eq!(103, aoc2021::day12::run("test_inputs/day12_2.txt", 2));
-assert_eq!(3509, aoc2021::day12::run("test_inputs/day12_3.txt", 2));
+assert_eq!(10, aoc2021::day12::run("test_inputs/day12_1.txt", 1).unwrap());
+assert_eq!(19, aoc2021::day12::run("test_inputs/day12_2.txt", 1).unwrap());
+assert_eq!(226, aoc2021::day12::run("test_inputs/day12_3.txt", 1).unwrap());
+assert_eq!(36, aoc2021::day12::run("test_inputs/day12_1.txt", 2).unwrap());
+assert_eq!(103, aoc2021::day12::run("test_inputs/day12_2.txt", 2).unwrap());
+assert_eq!(3509, aoc2021::day12::run("test_inputs/day12_3.txt", 2).unwrap());
+```
+ */
+pub fn run(file: &str, part: usize) -> Result<usize, AocError> {
+    input::from_file(file, |input| solve(input, part))
+}
+
+/**
+Solve Day 12's exercise from the text of the input.
+
+# Examples
+```
+let input = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end\n";
+assert_eq!(10, aoc2021::day12::solve(input, 1).unwrap());
+assert_eq!(36, aoc2021::day12::solve(input, 2).unwrap());
 ```
  */
-pub fn run(file: &str, part: usize) -> usize {
-    find_paths(&load_caves(file), part).len()
+pub fn solve(input: &str, part: usize) -> Result<usize, AocError> {
+    Ok(find_paths(&parse_caves(input)?, part).len())
+}
+
+pub struct Day12 {
+    caves: HashMap<String, Vec<String>>,
+}
+
+impl Solution for Day12 {
+    fn parse(input: &str) -> Result<Day12, AocError> {
+        Ok(Day12 { caves: parse_caves(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(find_paths(&self.caves, 1).len().into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(find_paths(&self.caves, 2).len().into())
+    }
 }
 
 #[cfg(test)]
@@ -95,7 +143,7 @@ mod tests {
 
     #[test]
     fn test_load_caves() {
-        let result = load_caves("test_inputs/day12_1.txt");
+        let result = load_caves("test_inputs/day12_1.txt").unwrap();
         assert_eq!(6, result.len());
         match result.get("start") {
             None => panic!("expected cave named \"start\""),
@@ -151,7 +199,7 @@ mod tests {
 
     #[test]
     fn test_find_paths() {
-        let caves = load_caves("test_inputs/day12_1.txt");
+        let caves = load_caves("test_inputs/day12_1.txt").unwrap();
         let paths = find_paths(&caves, 1);
         assert_eq!(10, paths.len());
         let expected_paths =
@@ -1,48 +1,83 @@
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
 /**
-Read dot coordinates and fold instructions from file.
+The dots on the transparent paper, and the folds to make.
+ */
+pub type Paper = (HashSet<(u32, u32)>, Vec<(char, u32)>);
+
+/**
+Parse dot coordinates, a blank line, then fold instructions.
 
 # Examples
 ```
-let (dots, folds) = aoc2021::day13::load_dots_and_folds("test_inputs/day13.txt");
-assert_eq!(18, dots.len());
-assert!(dots.contains(&(6,10)));
-assert!(dots.contains(&(9,0)));
-assert_eq!(folds, vec![('y',7),('x',5)]);
+let (dots, folds) = aoc2021::day13::parse_dots_and_folds("6,10\n0,14\n\nfold along y=7\n").unwrap();
+assert_eq!(2, dots.len());
+assert_eq!(folds, vec![('y',7)]);
+let e = aoc2021::day13::parse_dots_and_folds("6,10\n\nfold along z=7\n").unwrap_err();
+assert_eq!("line 3, column 12: illegal axis \"z\"", e.to_string());
 ```
  */
-pub fn load_dots_and_folds(file: &str) -> (HashSet<(u32, u32)>, Vec<(char, u32)>) {
+pub fn parse_dots_and_folds(input: &str) -> Result<Paper, AocError> {
     let mut dots = HashSet::new();
     let mut folds = Vec::new();
     let mut read_dots = true;
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    for line in buf_reader.lines() {
-        let line_text = line.unwrap();
+    for (ndx, line_text) in input.lines().enumerate() {
         if read_dots {
-            if line_text == "" {
+            if line_text.is_empty() {
                 read_dots = false;
             } else {
-                let coord: Vec<u32> = line_text
-                    .split(',')
-                    .map(|s| s.parse::<u32>().unwrap())
-                    .collect();
-                dots.insert((coord[0], coord[1]));
+                let dot = parse_dot(line_text).map_err(|e| e.on_line(ndx + 1))?;
+                dots.insert(dot);
             }
         } else {
-            let fold: Vec<&str> = line_text.split(' ').nth(2).unwrap().split('=').collect();
-            folds.push((
-                fold[0].chars().nth(0).unwrap(),
-                fold[1].parse::<u32>().unwrap(),
-            ));
+            let fold = parse_fold(line_text).map_err(|e| e.on_line(ndx + 1))?;
+            folds.push(fold);
         }
     }
 
-    (dots, folds)
+    Ok((dots, folds))
+}
+
+/**
+Read dot coordinates and fold instructions from file.
+
+# Examples
+```
+let (dots, folds) = aoc2021::day13::load_dots_and_folds("test_inputs/day13.txt").unwrap();
+assert_eq!(18, dots.len());
+assert!(dots.contains(&(6,10)));
+assert!(dots.contains(&(9,0)));
+assert_eq!(folds, vec![('y',7),('x',5)]);
+```
+ */
+pub fn load_dots_and_folds(file: &str) -> Result<Paper, AocError> {
+    input::from_file(file, parse_dots_and_folds)
+}
+
+fn parse_dot(line: &str) -> Result<(u32, u32), AocError> {
+    let coord: Vec<&str> = line.split(',').collect();
+    if coord.len() != 2 {
+        return Err(AocError::parse(1, 1, format!("expected \"x,y\", found \"{}\"", line)));
+    }
+    Ok((parse_token(line, coord[0])?, parse_token(line, coord[1])?))
+}
+
+fn parse_fold(line: &str) -> Result<(char, u32), AocError> {
+    let instruction = line.strip_prefix("fold along ")
+        .ok_or_else(|| AocError::parse(1, 1, "expected \"fold along\""))?;
+    match instruction.split_once('=') {
+        Some((axis @ ("x" | "y"), value)) => {
+            Ok((axis.chars().next().unwrap(), parse_token(line, value)?))
+        },
+        Some((axis, _)) => Err(AocError::parse(1, column_of(line, axis),
+            format!("illegal axis \"{}\"", axis))),
+        None => Err(AocError::parse(1, column_of(line, instruction), "expected axis=value")),
+    }
 }
 
 /**
@@ -50,7 +85,7 @@ Perform a fold on the paper containing the specified dots.
 
 # Examples
 ```
-let (dots, folds) = aoc2021::day13::load_dots_and_folds("test_inputs/day13.txt");
+let (dots, folds) = aoc2021::day13::load_dots_and_folds("test_inputs/day13.txt").unwrap();
 let dots = aoc2021::day13::fold(&dots, &folds[0]);
 assert_eq!(17, dots.len());
 assert!(dots.contains(&(0,0)));
@@ -113,27 +148,75 @@ Run part 1 of the Day 13 exercise.
 
 # Examples
 ```
-assert_eq!(17, aoc2021::day13::run_part1("test_inputs/day13.txt"));
+assert_eq!(17, aoc2021::day13::run_part1("test_inputs/day13.txt").unwrap());
 ```
  */
-pub fn run_part1(file: &str) -> usize {
-    let (dots, folds) = load_dots_and_folds(file);
-    fold(&dots, &folds[0]).len()
+pub fn run_part1(file: &str) -> Result<usize, AocError> {
+    input::from_file(file, solve_part1)
 }
 
 /**
-Run part 2 of the Day 13 exercise.
+Solve part 1 of the Day 13 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(1, aoc2021::day13::solve_part1("0,0\n0,2\n\nfold along y=1\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<usize, AocError> {
+    let (dots, folds) = parse_dots_and_folds(input)?;
+    let first = folds.first()
+        .ok_or_else(|| AocError::Unsolvable(String::from("no fold instructions")))?;
+    Ok(fold(&dots, first).len())
+}
+
+/**
+Perform every fold, then draw the remaining dots.
  */
-pub fn run_part2(file: &str) -> Vec<String> {
-    let (mut dots, folds) = load_dots_and_folds(file);
-    for this_fold in folds {
-        let new_dots = fold(&dots, &this_fold);
-        dots.clear();
-        dots.extend(new_dots.iter());
+pub fn fold_all(dots: &HashSet<(u32, u32)>, folds: &[(char, u32)]) -> Vec<String> {
+    fold_all_traced(dots, folds, &mut NoTrace)
+}
+
+/**
+Perform every fold like [`fold_all`], tracing the paper after each fold.
+
+# Examples
+```
+let (dots, folds) = aoc2021::day13::parse_dots_and_folds("0,0\n2,4\n\nfold along y=2\nfold along x=1\n").unwrap();
+let mut steps = Vec::new();
+let mut tracer = |_: usize, action: &str, state: &dyn std::fmt::Display| steps.push(format!("{}\n{}", action, state));
+assert_eq!(vec!["*"], aoc2021::day13::fold_all_traced(&dots, &folds, &mut tracer));
+assert_eq!(vec!["fold along y=2\n#.#", "fold along x=1\n#"], steps);
+```
+ */
+pub fn fold_all_traced(dots: &HashSet<(u32, u32)>, folds: &[(char, u32)], tracer: &mut dyn Tracer) -> Vec<String> {
+    let mut dots = dots.clone();
+    for (ndx, this_fold) in folds.iter().enumerate() {
+        dots = fold(&dots, this_fold);
+        tracer.step(ndx + 1, &format!("fold along {}={}", this_fold.0, this_fold.1), &Dots(&dots));
+    }
+    draw(&dots, '*', ' ')
+}
+
+/**
+The dots on the paper, drawn as in the puzzle.
+ */
+struct Dots<'a>(&'a HashSet<(u32, u32)>);
+
+impl fmt::Display for Dots<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", draw(self.0, '#', '.').join("\n"))
     }
+}
+
+/**
+Draw the dots, with `dot` where there is a dot and `blank` where there is
+not.
+ */
+fn draw(dots: &HashSet<(u32, u32)>, dot: char, blank: char) -> Vec<String> {
     let mut max_x = 0;
     let mut max_y = 0;
-    for coord in &dots {
+    for coord in dots {
         if coord.0 > max_x {
             max_x = coord.0;
         }
@@ -143,10 +226,66 @@ pub fn run_part2(file: &str) -> Vec<String> {
     }
     let mut print: Vec<Vec<char>> = Vec::new();
     for _ in 0 .. max_y+1 {
-        print.push(vec![' '; max_x as usize+1]);
+        print.push(vec![blank; max_x as usize+1]);
     }
-    for coord in &dots {
-        print[coord.1 as usize][coord.0 as usize] = '*';
+    for coord in dots {
+        print[coord.1 as usize][coord.0 as usize] = dot;
     }
     print.iter().map(|v| v.iter().collect::<String>()).collect()
-}
\ No newline at end of file
+}
+
+/**
+Run part 2 of the Day 13 exercise.
+ */
+pub fn run_part2(file: &str) -> Result<Vec<String>, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the Day 13 exercise from the text of the input.
+
+# Examples
+```
+let drawing = aoc2021::day13::solve_part2("0,0\n2,2\n\nfold along y=1\n").unwrap();
+assert_eq!(vec!["* *"], drawing);
+```
+ */
+pub fn solve_part2(input: &str) -> Result<Vec<String>, AocError> {
+    let (dots, folds) = parse_dots_and_folds(input)?;
+    Ok(fold_all(&dots, &folds))
+}
+
+pub struct Day13 {
+    dots: HashSet<(u32, u32)>,
+    folds: Vec<(char, u32)>,
+}
+
+impl Solution for Day13 {
+    fn parse(input: &str) -> Result<Day13, AocError> {
+        let (dots, folds) = parse_dots_and_folds(input)?;
+        Ok(Day13 { dots, folds })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        let first = self.folds.first()
+            .ok_or_else(|| AocError::Unsolvable(String::from("no fold instructions")))?;
+        Ok(fold(&self.dots, first).len().into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(fold_all(&self.dots, &self.folds).into())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        match part {
+            1 => {
+                let first = self.folds.first()
+                    .ok_or_else(|| AocError::Unsolvable(String::from("no fold instructions")))?;
+                let dots = fold(&self.dots, first);
+                tracer.step(1, &format!("fold along {}={}", first.0, first.1), &Dots(&dots));
+                Ok(dots.len().into())
+            },
+            _ => Ok(fold_all_traced(&self.dots, &self.folds, tracer).into()),
+        }
+    }
+}
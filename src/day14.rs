@@ -1,47 +1,72 @@
-use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::count::{self, Count, Overflow};
+use crate::error::{column_of, AocError};
+use crate::input;
+use crate::params::Params;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
 /**
-Load polymer template and transformation rules from file.
+The steps taken in each part, which can be changed with the `part1_steps`
+and `part2_steps` parameters, and whether to count exactly rather than with
+a `u128`, set with the `exact` parameter.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub part1_steps: u32,
+    pub part2_steps: u32,
+    pub exact: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { part1_steps: 10, part2_steps: 40, exact: false }
+    }
+}
+
+impl Options {
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["part1_steps", "part2_steps", "exact"])?;
+        let mut options = Options::default();
+        params.set("part1_steps", &mut options.part1_steps)?;
+        params.set("part2_steps", &mut options.part2_steps)?;
+        params.set("exact", &mut options.exact)?;
+        count::check_exact(options.exact)?;
+        Ok(options)
+    }
+}
+
+/**
+The first and last elements of the template, the count of each pair in the
+template, and the two pairs each pair becomes.
+ */
+pub type Polymers = (char, char, HashMap<String, u128>, HashMap<String, Vec<String>>);
+
+/**
+Parse the polymer template, a blank line, then the transformation rules.
 
 # Examples
 ```
-let (first, last, template, rules) = aoc2021::day14::load_polymers("test_inputs/day14.txt");
-assert_eq!('N', first);
-assert_eq!('B', last);
+let (first, last, template, rules) = aoc2021::day14::parse_polymers("NNCB\n\nCH -> B\n").unwrap();
+assert_eq!(('N', 'B'), (first, last));
 assert_eq!(3, template.len());
-assert_eq!(&1, template.get(&String::from("NN")).unwrap());
-assert_eq!(&1, template.get(&String::from("NC")).unwrap());
-assert_eq!(&1, template.get(&String::from("CB")).unwrap());
-assert_eq!(16, rules.len());
 assert_eq!(&vec![String::from("CB"),String::from("BH")], rules.get("CH").unwrap());
-assert_eq!(&vec![String::from("CC"),String::from("CN")], rules.get("CN").unwrap());
+let e = aoc2021::day14::parse_polymers("NNCB\n\nCH -> b\n").unwrap_err();
+assert_eq!("line 3, column 7: expected one element, found \"b\"", e.to_string());
 ```
  */
-pub fn load_polymers(
-    file: &str,
-) -> (
-    char,
-    char,
-    HashMap<String, u128>,
-    HashMap<String, Vec<String>>,
-) {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
-    // load polymer template
-    let mut template_string = String::new();
-    match buf_reader.read_line(&mut template_string) {
-        Ok(_) => (),
-        Err(e) => {
-            panic!("could not read polymer template: {:?}", e);
-        }
+pub fn parse_polymers(input: &str) -> Result<Polymers, AocError> {
+    let mut lines = input.lines();
+    // parse polymer template
+    let template_string = lines.next().unwrap_or("").trim();
+    if !template_string.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(AocError::parse(1, 1, "template must be uppercase letters"));
     }
-    let template_string = template_string.trim();
-    let first_char = template_string.chars().nth(0).unwrap();
-    let last_char = template_string.chars().nth_back(0).unwrap();
+    let (first_char, last_char) = match (template_string.chars().next(), template_string.chars().next_back()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(AocError::parse(1, 1, "missing polymer template")),
+    };
     let mut template: HashMap<String, u128> = HashMap::new();
     for ndx in 1..template_string.len() {
         let c = template
@@ -49,21 +74,16 @@ pub fn load_polymers(
             .or_insert(0);
         *c += 1;
     }
-    // load insertion rules
+    // parse insertion rules
     let mut rules: HashMap<String, Vec<String>> = HashMap::new();
-    let mut _buf = String::new();
-    match buf_reader.read_line(&mut _buf) {
-        // blank line
-        Err(e) => {
-            panic!("error reading expected blank line: {:?}", e);
-        }
-        _ => (),
+    if !lines.next().unwrap_or("").trim().is_empty() {
+        return Err(AocError::parse(2, 1, "expected a blank line after the template"));
     }
-    for line in buf_reader.lines() {
-        let line_str = line.unwrap();
+    for (ndx, line_str) in lines.enumerate() {
+        check_rule(line_str).map_err(|e| e.on_line(ndx + 3))?;
         let key = String::from(&line_str[0..2]);
         let mut s1 = String::new();
-        s1.push(line_str.chars().nth(0).unwrap());
+        s1.push(line_str.chars().next().unwrap());
         s1.push(line_str.chars().nth_back(0).unwrap());
         let mut s2 = String::new();
         s2.push(line_str.chars().nth_back(0).unwrap());
@@ -71,17 +91,58 @@ pub fn load_polymers(
         rules.insert(key, vec![s1, s2]);
     }
 
-    (first_char, last_char, template, rules)
+    Ok((first_char, last_char, template, rules))
 }
 
 /**
-Update the polymer template using the given rules.
+Load polymer template and transformation rules from file.
 
 # Examples
 ```
-let (_, _, mut template, rules) = aoc2021::day14::load_polymers("test_inputs/day14.txt");
+let (first, last, template, rules) = aoc2021::day14::load_polymers("test_inputs/day14.txt").unwrap();
+assert_eq!('N', first);
+assert_eq!('B', last);
+assert_eq!(3, template.len());
+assert_eq!(&1, template.get(&String::from("NN")).unwrap());
+assert_eq!(&1, template.get(&String::from("NC")).unwrap());
+assert_eq!(&1, template.get(&String::from("CB")).unwrap());
+assert_eq!(16, rules.len());
+assert_eq!(&vec![String::from("CB"),String::from("BH")], rules.get("CH").unwrap());
+assert_eq!(&vec![String::from("CC"),String::from("CN")], rules.get("CN").unwrap());
+```
+ */
+pub fn load_polymers(file: &str) -> Result<Polymers, AocError> {
+    input::from_file(file, parse_polymers)
+}
+
+/**
+Check that a line is an insertion rule of the form `AB -> C`.
+ */
+fn check_rule(line: &str) -> Result<(), AocError> {
+    match line.split_once(" -> ") {
+        Some((pair, element)) => {
+            if pair.len() != 2 || !pair.chars().all(|c| c.is_ascii_uppercase()) {
+                Err(AocError::parse(1, 1, format!("expected a pair of elements, found \"{}\"", pair)))
+            } else if element.len() != 1 || !element.chars().all(|c| c.is_ascii_uppercase()) {
+                Err(AocError::parse(1, column_of(line, element),
+                    format!("expected one element, found \"{}\"", element)))
+            } else {
+                Ok(())
+            }
+        },
+        None => Err(AocError::parse(1, 1, "expected \"AB -> C\"")),
+    }
+}
+
+/**
+Update the polymer template using the given rules. Fails if a pair becomes
+too common to count.
+
+# Examples
+```
+let (_, _, mut template, rules) = aoc2021::day14::load_polymers("test_inputs/day14.txt").unwrap();
 for _ in 0..3 {
-    let new_template = aoc2021::day14::step(&template, &rules);
+    let new_template = aoc2021::day14::step(&template, &rules).unwrap();
     template.clear();
     template.extend(new_template.iter().map(|t| (String::from(t.0), *t.1)));
 }
@@ -99,29 +160,29 @@ assert_eq!(&1, template.get("BH").unwrap());
 assert_eq!(&1, template.get("HH").unwrap());
 ```
  */
-pub fn step(
-    template: &HashMap<String, u128>,
+pub fn step<C: Count>(
+    template: &HashMap<String, C>,
     rules: &HashMap<String, Vec<String>>,
-) -> HashMap<String, u128> {
-    let mut new_template: HashMap<String, u128> = template.clone();
+) -> Result<HashMap<String, C>, Overflow> {
+    // Polymers without a rule are left alone, and the rest replaced. Only
+    // adding to the counts means they overflow only if the result would.
+    let mut new_template: HashMap<String, C> = template.iter()
+        .filter(|(polymer, _)| !rules.contains_key(*polymer))
+        .map(|(polymer, count)| (polymer.clone(), count.clone()))
+        .collect();
     for (polymer, count) in template {
-        // This polymer will be replaced.
-        match new_template.entry(String::from(polymer)) {
-            Entry::Occupied(mut e) => {
-                *e.get_mut() -= count;
-            }
-            Entry::Vacant(_) => {
-                panic!("missing expected polymer");
-            }
-        }
+        let new_polymers = match rules.get(polymer) {
+            Some(new_polymers) => new_polymers,
+            None => continue,
+        };
         // These polymers will replace it.
-        for new_polymer in rules.get(polymer).unwrap() {
-            let c = new_template.entry(String::from(new_polymer)).or_insert(0);
-            *c += count;
+        for new_polymer in new_polymers {
+            let c = new_template.entry(String::from(new_polymer)).or_insert(C::from_u128(0));
+            *c = c.checked_add(count).ok_or(Overflow)?;
         }
     }
 
-    new_template
+    Ok(new_template)
 }
 
 /**
@@ -131,13 +192,13 @@ Requires the first and last characters in the template for an accurate count.
 # Examples
 ```
 let (first, last, mut template, rules) =
-    aoc2021::day14::load_polymers("test_inputs/day14.txt");
+    aoc2021::day14::load_polymers("test_inputs/day14.txt").unwrap();
 for _ in 0..10 {
-    let new_template = aoc2021::day14::step(&template, &rules);
+    let new_template = aoc2021::day14::step(&template, &rules).unwrap();
     template.clear();
     template.extend(new_template.iter().map(|t| (String::from(t.0), *t.1)));
 }
-let result = aoc2021::day14::element_counts(&template, first, last);
+let result = aoc2021::day14::element_counts(&template, first, last).unwrap();
 assert_eq!(4, result.len());
 assert_eq!(&1749, result.get(&'B').unwrap());
 assert_eq!(&298, result.get(&'C').unwrap());
@@ -145,46 +206,173 @@ assert_eq!(&161, result.get(&'H').unwrap());
 assert_eq!(&865, result.get(&'N').unwrap());
 ```
  */
-pub fn element_counts(
-    template: &HashMap<String, u128>,
+pub fn element_counts<C: Count>(
+    template: &HashMap<String, C>,
     first: char,
     last: char,
-) -> HashMap<char, u128> {
+) -> Result<HashMap<char, C>, Overflow> {
     let mut elements = HashMap::new();
     for c in [first, last] {
-        *elements.entry(c).or_insert(0) += 1;
+        let e = elements.entry(c).or_insert(C::from_u128(0));
+        *e = e.checked_add(&C::from_u128(1)).ok_or(Overflow)?;
     }
     for (polymer, count) in template {
         for c in polymer.chars() {
-            *elements.entry(c).or_insert(0) += count;
+            let e = elements.entry(c).or_insert(C::from_u128(0));
+            *e = e.checked_add(count).ok_or(Overflow)?;
         }
     }
     for count in elements.values_mut() {
-        *count /= 2;
+        *count = count.half();
     }
 
-    elements
+    Ok(elements)
 }
 
 /**
-Run Day 14's exercise.
+Apply the rules for the specified number of steps, then find the difference
+between the most and least common elements.
 
 # Examples
 ```
-assert_eq!(1588, aoc2021::day14::run("test_inputs/day14.txt", 10));
-assert_eq!(2188189693529, aoc2021::day14::run("test_inputs/day14.txt", 40));
+let (first, last, template, rules) = aoc2021::day14::load_polymers("test_inputs/day14.txt").unwrap();
+assert_eq!(1588, aoc2021::day14::polymerize(first, last, &template, &rules, 10).unwrap());
+let e = aoc2021::day14::polymerize(first, last, &template, &rules, 200).unwrap_err();
+assert!(e.to_string().starts_with("no solution: the pairs after step 129 overflowed a 128-bit count"));
 ```
  */
-pub fn run(file: &str, steps: u32) -> u128 {
-    let (first, last, mut template, rules) = load_polymers(file);
-    for _ in 0..steps {
-        let new_template = step(&template, &rules);
-        template.clear();
-        template.extend(new_template.iter().map(|t| (String::from(t.0), *t.1)));
-    }
-    let elements = element_counts(&template, first, last);
+pub fn polymerize(
+    first: char,
+    last: char,
+    template: &HashMap<String, u128>,
+    rules: &HashMap<String, Vec<String>>,
+    steps: u32,
+) -> Result<u128, AocError> {
+    polymerize_traced(first, last, template, rules, steps, &mut NoTrace)
+}
+
+/**
+The polymer, drawn as its length and the count of each pair in it.
+ */
+struct Pairs<'a, C>(&'a HashMap<String, C>);
+
+impl<C: Count> fmt::Display for Pairs<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let zero = C::from_u128(0);
+        let mut pairs: Vec<_> = self.0.iter().filter(|(_, count)| **count > zero).collect();
+        pairs.sort();
+        match count::sum(self.0.values()).map(|pairs| pairs.checked_add(&C::from_u128(1))) {
+            Ok(Some(length)) => write!(f, "length {}: ", length)?,
+            _ => write!(f, "too long to count: ")?,
+        }
+        let pairs: Vec<String> = pairs.iter().map(|(pair, count)| format!("{} {}", pair, count)).collect();
+        write!(f, "{}", pairs.join(", "))
+    }
+}
+
+/**
+Polymerize like [`polymerize`], tracing the pairs after each step.
+
+# Examples
+```
+let (first, last, template, rules) = aoc2021::day14::parse_polymers("NNCB\n\nNC -> B\nCB -> C\nNB -> B\n").unwrap();
+let mut steps = Vec::new();
+let mut tracer = |_: usize, _: &str, state: &dyn std::fmt::Display| steps.push(state.to_string());
+aoc2021::day14::polymerize_traced::<u128>(first, last, &template, &rules, 1, &mut tracer).unwrap();
+// NNCB -> NNBCCB
+assert_eq!(vec!["length 6: BC 1, CB 1, CC 1, NB 1, NN 1"], steps);
+```
+ */
+pub fn polymerize_traced<C: Count>(
+    first: char,
+    last: char,
+    template: &HashMap<String, u128>,
+    rules: &HashMap<String, Vec<String>>,
+    steps: u32,
+    tracer: &mut dyn Tracer,
+) -> Result<C, AocError> {
+    let mut template: HashMap<String, C> = template.iter()
+        .map(|(pair, count)| (pair.clone(), C::from_u128(*count)))
+        .collect();
+    for n in 0..steps {
+        template = step(&template, rules)
+            .map_err(|e| e.counting(&format!("the pairs after step {}", n + 1)))?;
+        tracer.step(n as usize + 1, "insert", &Pairs(&template));
+    }
+    let elements = element_counts(&template, first, last)
+        .map_err(|e| e.counting(&format!("the elements after {} steps", steps)))?;
     let max = elements.values().max().unwrap();
     let min = elements.values().min().unwrap();
 
-    max-min
+    Ok(max.sub(min))
+}
+
+/**
+Run Day 14's exercise.
+
+# Examples
+```
+assert_eq!(1588, aoc2021::day14::run("test_inputs/day14.txt", 10).unwrap());
+assert_eq!(2188189693529, aoc2021::day14::run("test_inputs/day14.txt", 40).unwrap());
+```
+ */
+pub fn run(file: &str, steps: u32) -> Result<u128, AocError> {
+    input::from_file(file, |input| solve(input, steps))
+}
+
+/**
+Solve Day 14's exercise from the text of the input.
+
+# Examples
+```
+// NNCB -> NNBCCB -> NNBBCCCB
+assert_eq!(3 - 2, aoc2021::day14::solve("NNCB\n\nNC -> B\nCB -> C\nNB -> B\n", 2).unwrap());
+```
+ */
+pub fn solve(input: &str, steps: u32) -> Result<u128, AocError> {
+    let (first, last, template, rules) = parse_polymers(input)?;
+    polymerize(first, last, &template, &rules, steps)
+}
+
+pub struct Day14 {
+    first: char,
+    last: char,
+    template: HashMap<String, u128>,
+    rules: HashMap<String, Vec<String>>,
+    options: Options,
+}
+
+impl Day14 {
+    fn polymerize(&self, steps: u32, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        #[cfg(feature = "bigint")]
+        if self.options.exact {
+            return Ok(polymerize_traced::<num_bigint::BigUint>(
+                self.first, self.last, &self.template, &self.rules, steps, tracer)?.into());
+        }
+        Ok(polymerize_traced::<u128>(self.first, self.last, &self.template, &self.rules, steps, tracer)?.into())
+    }
+}
+
+impl Solution for Day14 {
+    fn parse(input: &str) -> Result<Day14, AocError> {
+        let (first, last, template, rules) = parse_polymers(input)?;
+        Ok(Day14 { first, last, template, rules, options: Options::default() })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        self.polymerize(self.options.part1_steps, &mut NoTrace)
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        self.polymerize(self.options.part2_steps, &mut NoTrace)
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        self.options = Options::from_params(params)?;
+        Ok(())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        self.polymerize(if part == 1 { self.options.part1_steps } else { self.options.part2_steps }, tracer)
+    }
 }
@@ -1,155 +1,172 @@
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-
-struct NeighborCounter<'a> {
-    coord: &'a (i32, i32),
-    count: u8,
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors::Neighborhood;
+use crate::params::{self, Params};
+use crate::search::{self, Path};
+use crate::input;
+use crate::solution::{Answer, Solution};
+
+/**
+How many times the map is tiled across and down in part 2, which can be
+changed with the `tiles` parameter.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub tiles: usize,
 }
 
-impl<'a> NeighborCounter<'a> {
-    fn new(coord: &'a (i32, i32)) -> NeighborCounter {
-        NeighborCounter { coord, count: 0 }
+impl Default for Options {
+    fn default() -> Options {
+        Options { tiles: 5 }
     }
 }
 
-impl<'a> Iterator for NeighborCounter<'a> {
-    type Item = (i32, i32);
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.count >= 4 {
-            None
-        } else {
-            self.count += 1;
-            match self.count {
-                1 => Some((self.coord.0 - 1, self.coord.1)),
-                2 => Some((self.coord.0, self.coord.1 - 1)),
-                3 => Some((self.coord.0, self.coord.1 + 1)),
-                4 => Some((self.coord.0 + 1, self.coord.1)),
-                _ => None,
-            }
+/**
+The most cells the tiled map of part 2 may have.
+ */
+pub const MAX_CELLS: usize = 25_000_000;
+
+impl Options {
+    /**
+    The puzzle's options, overridden by any parameters. Even a map of one
+    cell must tile into at most [`MAX_CELLS`] cells.
+
+    # Examples
+    ```
+    let mut params = aoc2021::params::Params::new();
+    params.add("tiles=4294967296").unwrap();
+    let e = aoc2021::day15::Options::from_params(&params).unwrap_err();
+    assert_eq!("tiles=4294967296 would tile the map into more than 25000000 cells", e.to_string());
+    ```
+     */
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["tiles"])?;
+        let mut options = Options::default();
+        params.set("tiles", &mut options.tiles)?;
+        params::at_least("tiles", options.tiles, 1)?;
+        options.check_size(1)?;
+        Ok(options)
+    }
+
+    /**
+    Check that a square map with `rows` rows tiles into at most
+    [`MAX_CELLS`] cells.
+
+    # Examples
+    ```
+    let options = aoc2021::day15::Options { tiles: 50 };
+    assert!(options.check_size(100).is_ok());
+    let e = options.check_size(101).unwrap_err();
+    assert_eq!("tiles=50 would tile the map into more than 25000000 cells", e.to_string());
+    ```
+     */
+    pub fn check_size(&self, rows: usize) -> Result<(), AocError> {
+        match rows.checked_mul(self.tiles).and_then(|side| side.checked_mul(side)) {
+            Some(cells) if cells <= MAX_CELLS => Ok(()),
+            _ => Err(AocError::Usage(format!("tiles={} would tile the map into more than {} cells", self.tiles, MAX_CELLS))),
         }
     }
 }
 
 /**
-Load risks from the file, mapped by (row,col) coordinates.
+Parse risks, indexed by (row,col) coordinates. The risks must form a square,
+and each is a digit from 1 to 9.
 
 # Examples
 ```
-let risks = aoc2021::day15::load_risks("test_inputs/day15.txt");
-assert_eq!(100, risks.len());
-let expected_diag: Vec<u32> = vec![1,3,3,4,4,2,2,6,2,1];
-let mut ndx: i32 = 0;
-for value in expected_diag {
-    assert_eq!(&value, risks.get(&(ndx,ndx)).unwrap());
-    ndx += 1;
-}
+let risks = aoc2021::day15::parse_risks("12\n34\n").unwrap();
+assert_eq!(Some(&3), risks.get(1, 0));
+let e = aoc2021::day15::parse_risks("12\n34\n56\n").unwrap_err();
+assert_eq!("line 3, column 1: expected a square, found 3 rows of 2", e.to_string());
+let e = aoc2021::day15::parse_risks("10\n11\n").unwrap_err();
+assert_eq!("line 1, column 2: expected a digit from 1 to 9, found '0'", e.to_string());
 ```
  */
-pub fn load_risks(file: &str) -> HashMap<(i32, i32), u32> {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let mut risks = HashMap::new();
-    let mut row: i32 = 0;
-    for line in buf_reader.lines() {
-        let line_str = line.unwrap();
-        for col in 0..line_str.len() {
-            let risk = &line_str[col..col + 1].parse::<u32>().unwrap();
-            risks.insert((row, col as i32), *risk);
-        }
-        row += 1;
+pub fn parse_risks(input: &str) -> Result<Grid<u32>, AocError> {
+    let risks = Grid::parse(input, |c| c.to_digit(10).filter(|d| *d > 0), "a digit from 1 to 9")?;
+    if risks.rows() != risks.cols() {
+        return Err(AocError::parse(risks.rows(), 1,
+            format!("expected a square, found {} rows of {}", risks.rows(), risks.cols())));
     }
-    risks
+    Ok(risks)
 }
 
 /**
-Build a traversal graph from the risks.
+Load risks from the file, indexed by (row,col) coordinates. The risks must
+form a square.
 
 # Examples
 ```
-let risks = aoc2021::day15::load_risks("test_inputs/day15.txt");
-let graph = aoc2021::day15::build_graph(&risks);
-assert_eq!(360, graph.len());
-assert_eq!(&9, graph.get(&(&(4,4),&(3,4))).unwrap());
-assert_eq!(&3, graph.get(&(&(4,4),&(4,3))).unwrap());
-assert_eq!(&1, graph.get(&(&(4,4),&(4,5))).unwrap());
-assert_eq!(&1, graph.get(&(&(4,4),&(5,4))).unwrap());
+let risks = aoc2021::day15::load_risks("test_inputs/day15.txt").unwrap();
+assert_eq!((10, 10), (risks.rows(), risks.cols()));
+let expected_diag: Vec<u32> = vec![1,3,3,4,4,2,2,6,2,1];
+for (ndx, value) in expected_diag.into_iter().enumerate() {
+    assert_eq!(value, risks[(ndx, ndx)]);
+}
 ```
  */
-pub fn build_graph<'a>(
-    risks: &'a HashMap<(i32, i32), u32>,
-) -> HashMap<(&'a (i32, i32), &'a (i32, i32)), u32> {
-    let mut graph = HashMap::new();
-    for (coord, risk) in risks {
-        for neighbor in NeighborCounter::new(coord) {
-            match risks.get_key_value(&neighbor) {
-                Some(n) => {
-                    graph.insert((n.0, coord), *risk);
-                }
-                None => (),
-            }
-        }
-    }
-
-    graph
+pub fn load_risks(file: &str) -> Result<Grid<u32>, AocError> {
+    input::from_file(file, parse_risks)
 }
 
 /**
-Find shortest path from source to destination.
+Find the path of lowest total risk from source to destination, counting the
+risk of every cell entered. Returns `None` when either end is outside the
+grid.
 
 # Examples
 ```
-use std::collections::HashSet;
+let risks = aoc2021::day15::load_risks("test_inputs/day15.txt").unwrap();
+let path = aoc2021::day15::shortest_path((0,0), (9,9), &risks).unwrap();
+assert_eq!(40, path.cost);
+assert_eq!(Some(&(0,0)), path.states.first());
+assert_eq!(Some(&(9,9)), path.states.last());
+assert_eq!(None, aoc2021::day15::shortest_path((0,0), (10,10), &risks));
+```
+ */
+pub fn shortest_path(source: (usize, usize), dest: (usize, usize), risks: &Grid<u32>) -> Option<Path<(usize, usize), u32>> {
+    risks.get(source.0, source.1)?;
+    risks.get(dest.0, dest.1)?;
+    // every step costs at least 1, so the Manhattan distance never overestimates
+    search::astar(
+        source,
+        |coord| risks.neighbors(*coord, Neighborhood::Orthogonal).map(|n| (n, risks[n])),
+        |coord| (coord.0.abs_diff(dest.0) + coord.1.abs_diff(dest.1)) as u32,
+        |coord| *coord == dest,
+    )
+}
+
+/**
+Tile the risks right and down, increasing the risk of each tile by one and
+wrapping from 9 back to 1. See [`Options::check_size`] for how far a map
+may be tiled.
 
-let risks = aoc2021::day15::load_risks("test_inputs/day15.txt");
-let graph = aoc2021::day15::build_graph(&risks);
-let nodes: HashSet<&(i32,i32)> = risks.keys().collect();
-assert_eq!(40, aoc2021::day15::shortest_path(&(0,0), &(9,9), &nodes, &graph));
+# Examples
+```
+let risks = aoc2021::day15::parse_risks("18\n39\n").unwrap();
+let expanded = aoc2021::day15::expand_risks(&risks, 5);
+assert_eq!((10, 10), (expanded.rows(), expanded.cols()));
+assert_eq!(&[1, 8, 2, 9, 3, 1, 4, 2, 5, 3], expanded.row(0));
+assert_eq!(8, expanded[(9, 9)]);
 ```
  */
-pub fn shortest_path(
-    source: &(i32, i32),
-    dest: &(i32, i32),
-    nodes: &HashSet<&(i32, i32)>,
-    graph: &HashMap<(&(i32, i32), &(i32, i32)), u32>,
-) -> u32 {
-    let mut unvisited = nodes.clone();
-    let mut distances: HashMap<&(i32, i32), Option<u32>> =
-        unvisited.iter().map(|coord| (*coord, None)).collect();
-    distances.insert(source, Some(0));
-    loop {
-        // choose current node as the unvisited node with the least distance.
-        let mut potential_currents: Vec<(&(i32, i32), u32)> = distances
-            .iter()
-            .filter(|(k, v)| unvisited.contains(*k) && v.is_some())
-            .map(|(k, v)| 
-                 (*k, v.unwrap() + (dest.0-k.0).abs() as u32 + (dest.1-k.1).abs() as u32))
-            .collect();
-        potential_currents.sort_by(|a, b| a.1.cmp(&b.1));
-        
-        let current = potential_currents[0].0;
-        let current_distance = distances.get(potential_currents[0].0).unwrap().unwrap();
-        for neighbor in NeighborCounter::new(current).filter(|n| unvisited.contains(n)) {
-            match distances.get(&neighbor).unwrap() {
-                None => {
-                    let new_distance = current_distance + graph.get(&(current, &neighbor)).unwrap();
-                    *distances.get_mut(&neighbor).unwrap() = Some(new_distance);
-                }
-                Some(d) => {
-                    let potl_dist = current_distance + graph.get(&(current, &neighbor)).unwrap();
-                    if &potl_dist < d {
-                        *distances.get_mut(&neighbor).unwrap() = Some(potl_dist);
-                    }
-                }
-            }
-        }
-        unvisited.remove(current);
-        if !unvisited.contains(dest) {
-            return distances.get(dest).unwrap().unwrap();
-        }
+pub fn expand_risks(risks: &Grid<u32>, tiles: usize) -> Grid<u32> {
+    let (rows, cols) = (risks.rows(), risks.cols());
+    Grid::from_fn(rows * tiles, cols * tiles, |row, col| {
+        let tile = (row / rows + col / cols) as u32;
+        (risks[(row % rows, col % cols)] + tile - 1) % 9 + 1
+    })
+}
+
+/**
+Find the lowest total risk from the top left to the bottom right.
+ */
+pub fn lowest_risk(risks: &Grid<u32>) -> u32 {
+    if risks.rows() == 0 {
+        return 0;
     }
+    let dest = (risks.rows() - 1, risks.cols() - 1);
+    shortest_path((0, 0), dest, risks).expect("the grid is connected").cost
 }
 
 /**
@@ -157,41 +174,71 @@ Run the Day 15 exercise.
 
 # Examples
 ```
-assert_eq!(40, aoc2021::day15::run(1, "test_inputs/day15.txt"));
-assert_eq!(315, aoc2021::day15::run(2, "test_inputs/day15.txt"));
+assert_eq!(40, aoc2021::day15::run(1, "test_inputs/day15.txt").unwrap());
+assert_eq!(315, aoc2021::day15::run(2, "test_inputs/day15.txt").unwrap());
 ```
  */
-pub fn run(part: u8, file: &str) -> u32 {
-    let mut risks = load_risks(file);
-    if part == 2 {
-        // expand risks right
-        let length = risks.keys().map(|coord| coord.0).max().unwrap() + 1;
-        for row in 0..length {
-            for col in length..length * 5 {
-                let last_risk = risks.get(&(row, col-length)).unwrap();
-                let this_risk = if last_risk >= &9 {
-                    1
-                } else {
-                    last_risk + 1
-                };
-                risks.insert((row, col), this_risk);
-            }
-        }
-        // expand risks down
-        for row in length..length * 5 {
-            for col in 0..length * 5 {
-                let last_risk = risks.get(&(row-length, col)).unwrap();
-                let this_risk = if last_risk >= &9 {
-                    1
-                } else {
-                    last_risk + 1
-                };
-                risks.insert((row, col), this_risk);
-            }
-        }
+pub fn run(part: u8, file: &str) -> Result<u32, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve the Day 15 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(3 + 9, aoc2021::day15::solve(1, "18\n39\n").unwrap());
+assert!(aoc2021::day15::solve(3, "18\n39\n").is_err());
+```
+ */
+pub fn solve(part: u8, input: &str) -> Result<u32, AocError> {
+    solve_with(part, input, &Options::default())
+}
+
+/**
+Solve the Day 15 exercise with options other than the puzzle's.
+
+# Examples
+```
+use aoc2021::day15::{solve_with, Options};
+
+assert_eq!(3 + 2 + 4 + 1 + 5 + 2, solve_with(2, "18\n39\n", &Options { tiles: 2 }).unwrap());
+```
+ */
+pub fn solve_with(part: u8, input: &str, options: &Options) -> Result<u32, AocError> {
+    let risks = parse_risks(input)?;
+    match part {
+        1 => Ok(lowest_risk(&risks)),
+        2 => {
+            options.check_size(risks.rows())?;
+            Ok(lowest_risk(&expand_risks(&risks, options.tiles)))
+        },
+        _ => Err(AocError::UnknownPart(part.to_string())),
+    }
+}
+
+pub struct Day15 {
+    risks: Grid<u32>,
+    options: Options,
+}
+
+impl Solution for Day15 {
+    fn parse(input: &str) -> Result<Day15, AocError> {
+        Ok(Day15 { risks: parse_risks(input)?, options: Options::default() })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(lowest_risk(&self.risks).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(lowest_risk(&expand_risks(&self.risks, self.options.tiles)).into())
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        let options = Options::from_params(params)?;
+        options.check_size(self.risks.rows())?;
+        self.options = options;
+        Ok(())
     }
-    let graph = build_graph(&risks);
-    let nodes: HashSet<&(i32, i32)> = risks.keys().collect();
-    let max_ndx = nodes.iter().map(|coord| coord.0).max().unwrap();
-    shortest_path(&(0, 0), &(max_ndx, max_ndx), &nodes, &graph)
 }
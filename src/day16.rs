@@ -1,17 +1,18 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::{Answer, Solution};
 
 /**
 Convert a hexadecimal string to binary.
 
 # Examples
 ```
-assert_eq!("10100101", aoc2021::day16::hex_to_bin("A5"));
+assert_eq!("10100101", aoc2021::day16::hex_to_bin("A5").unwrap());
+assert!(aoc2021::day16::hex_to_bin("A5G").is_err());
 ```
  */
-pub fn hex_to_bin(s: &str) -> String {
-    s.chars().map(|c| match c {
+pub fn hex_to_bin(s: &str) -> Result<String, AocError> {
+    s.chars().enumerate().map(|(ndx, c)| Ok(match c {
         '0' => "0000",
         '1' => "0001",
         '2' => "0010",
@@ -28,8 +29,22 @@ pub fn hex_to_bin(s: &str) -> String {
         'D' => "1101",
         'E' => "1110",
         'F' => "1111",
-        _ => {panic!("illegal hex char {}", c);},
-    }).collect::<String>()
+        _ => {
+            return Err(AocError::parse(1, ndx + 1, format!("illegal hex char '{}'", c)));
+        },
+    })).collect::<Result<String, AocError>>()
+}
+
+/**
+Read `len` bits starting at `start` as an unsigned integer.
+ */
+fn read_bits(s: &str, start: usize, len: usize) -> Result<u64, AocError> {
+    match s.get(start..start + len) {
+        Some(bits) if bits.len() == len => u64::from_str_radix(bits, 2)
+            .map_err(|_| AocError::parse(1, start + 1, "expected binary digits")),
+        _ => Err(AocError::parse(1, start + 1,
+            format!("packet ends early, needed {} more bits", len))),
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -76,12 +91,13 @@ impl Packet {
 
     /**
     Build a packet from a binary string. Returns a tuple containing the
-    Packet and the number of chars consumed from the string.
+    Packet and the number of chars consumed from the string. The column of a
+    parse error is the position of the offending bit.
 
     # Examples
     ```
-    let s = aoc2021::day16::hex_to_bin("D2FE28");
-    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s);
+    let s = aoc2021::day16::hex_to_bin("D2FE28").unwrap();
+    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s).unwrap();
     assert_eq!(6, packet.get_version());
     assert_eq!(4, packet.get_type_id());
     match packet.get_kind() {
@@ -91,8 +107,8 @@ impl Packet {
     assert!(packet.get_subpackets().is_empty());
     assert_eq!(21, consumed);
 
-    let s = aoc2021::day16::hex_to_bin("38006F45291200");
-    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s);
+    let s = aoc2021::day16::hex_to_bin("38006F45291200").unwrap();
+    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s).unwrap();
     assert_eq!(1, packet.get_version());
     assert_eq!(6, packet.get_type_id());
     match packet.get_kind() {
@@ -110,8 +126,8 @@ impl Packet {
     });
     assert_eq!(49, consumed);
 
-    let s = aoc2021::day16::hex_to_bin("EE00D40C823060");
-    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s);
+    let s = aoc2021::day16::hex_to_bin("EE00D40C823060").unwrap();
+    let (packet, consumed) = aoc2021::day16::Packet::from_binary_str(&s).unwrap();
     assert_eq!(7, packet.get_version());
     assert_eq!(3, packet.get_type_id());
     match packet.get_kind() {
@@ -132,58 +148,99 @@ impl Packet {
         _ => {panic!("expected Literal");},
     });
     assert_eq!(51, consumed);
+
+    let s = aoc2021::day16::hex_to_bin("D2FE").unwrap();
+    let e = aoc2021::day16::Packet::from_binary_str(&s).unwrap_err();
+    assert_eq!("line 1, column 17: packet ends early, needed 5 more bits", e.to_string());
     ```
      */
-    pub fn from_binary_str(s: &str) -> (Packet, usize) {
-        let version = u8::from_str_radix(&s[0..3], 2).unwrap();
-        let type_id = u8::from_str_radix(&s[3..6], 2).unwrap();
+    pub fn from_binary_str(s: &str) -> Result<(Packet, usize), AocError> {
+        let (packet, end) = Packet::parse_at(s, 0, s.len())?;
+        Ok((packet, end))
+    }
+
+    /**
+    Parse the packet starting at bit `start`, reading no further than `end`.
+    Returns the packet and the index just past it.
+     */
+    fn parse_at(s: &str, start: usize, end: usize) -> Result<(Packet, usize), AocError> {
+        let s = &s[..end];
+        let version = read_bits(s, start, 3)? as u8;
+        let type_id = read_bits(s, start + 3, 3)? as u8;
         let kind: PacketKind;
         let mut subpackets = Vec::new();
-        let mut ndx: usize = 6;
+        let mut ndx: usize = start + 6;
         if type_id == 4 {
             let mut value: u64 = 0;
             loop {
-                value *= 16;
-                value += u64::from_str_radix(&s[ndx+1..ndx+5], 2).unwrap();
+                let group = read_bits(s, ndx, 5)?;
+                if value > u64::MAX >> 4 {
+                    return Err(AocError::parse(1, start + 1, "literal value is too large"));
+                }
+                value = value * 16 + (group & 0xf);
                 ndx += 5;
-                if &s[ndx-5..ndx-4] == "0" {
+                if group & 0x10 == 0 {
                     break;
                 }
             }
             kind = PacketKind::Literal(value);
         } else {
-            let length_type_id = &s[ndx..ndx+1];
+            let length_type_id = read_bits(s, ndx, 1)?;
             ndx += 1;
-            if length_type_id == "0" {
-                let subpackets_length = usize::from_str_radix(&s[ndx..ndx+15], 2).unwrap();
+            if length_type_id == 0 {
+                let subpackets_length = read_bits(s, ndx, 15)? as usize;
                 ndx += 15;
                 let end_ndx = ndx + subpackets_length;
+                if end_ndx > s.len() {
+                    return Err(AocError::parse(1, ndx - 14,
+                        format!("subpackets need {} bits, only {} remain", subpackets_length, s.len() - ndx)));
+                }
                 while ndx < end_ndx {
-                    let (subpacket, consumed) = Packet::from_binary_str(&s[ndx..end_ndx]);
+                    let (subpacket, next) = Packet::parse_at(s, ndx, end_ndx)?;
                     subpackets.push(subpacket);
-                    ndx += consumed;
+                    ndx = next;
                 }
             } else {
-                let subpackets_count = usize::from_str_radix(&s[ndx..ndx+11], 2).unwrap();
+                let subpackets_count = read_bits(s, ndx, 11)?;
                 ndx += 11;
                 for _ in 0..subpackets_count {
-                    let (subpacket, consumed) = Packet::from_binary_str(&s[ndx..]);
+                    let (subpacket, next) = Packet::parse_at(s, ndx, s.len())?;
                     subpackets.push(subpacket);
-                    ndx += consumed;
+                    ndx = next;
                 }
             }
-            kind = PacketKind::Operator(match type_id {
+            let op_kind = match type_id {
                 0 => OperatorKind::Sum,
                 1 => OperatorKind::Product,
                 2 => OperatorKind::Min,
                 3 => OperatorKind::Max,
                 5 => OperatorKind::Gt,
                 6 => OperatorKind::Lt,
-                7 => OperatorKind::Eq,
-                _ => panic!("illegal type_id {}", type_id),
-            });
+                _ => OperatorKind::Eq,
+            };
+            let comparison = [OperatorKind::Gt, OperatorKind::Lt, OperatorKind::Eq].contains(&op_kind);
+            if comparison && subpackets.len() != 2 {
+                return Err(AocError::parse(1, start + 1,
+                    format!("{:?} needs 2 subpackets, found {}", op_kind, subpackets.len())));
+            } else if subpackets.is_empty() {
+                return Err(AocError::parse(1, start + 1, format!("{:?} has no subpackets", op_kind)));
+            }
+            kind = PacketKind::Operator(op_kind);
         }
-        (Packet{ version, type_id, kind, subpackets }, ndx)
+        Ok((Packet{ version, type_id, kind, subpackets }, ndx))
+    }
+
+    /**
+    Build a packet from a hexadecimal string. The column of a parse error is
+    the hex character holding the offending bit.
+     */
+    pub fn from_hex_str(hex_str: &str) -> Result<Packet, AocError> {
+        let (packet, _) = Packet::from_binary_str(&hex_to_bin(hex_str)?).map_err(|e| match e {
+            AocError::Parse { file, line, column, reason } =>
+                AocError::Parse { file, line, column: (column - 1) / 4 + 1, reason },
+            e => e,
+        })?;
+        Ok(packet)
     }
 }
 
@@ -193,16 +250,14 @@ return the sum of their versions.
 
 # Examples
 ```
-assert_eq!(16, aoc2021::day16::sum_versions_from_hex_str("8A004A801A8002F478"));
-assert_eq!(12, aoc2021::day16::sum_versions_from_hex_str("620080001611562C8802118E34"));
-assert_eq!(23, aoc2021::day16::sum_versions_from_hex_str("C0015000016115A2E0802F182340"));
-assert_eq!(31, aoc2021::day16::sum_versions_from_hex_str("A0016C880162017C3686B18A3D4780"));
+assert_eq!(16, aoc2021::day16::sum_versions_from_hex_str("8A004A801A8002F478").unwrap());
+assert_eq!(12, aoc2021::day16::sum_versions_from_hex_str("620080001611562C8802118E34").unwrap());
+assert_eq!(23, aoc2021::day16::sum_versions_from_hex_str("C0015000016115A2E0802F182340").unwrap());
+assert_eq!(31, aoc2021::day16::sum_versions_from_hex_str("A0016C880162017C3686B18A3D4780").unwrap());
 ```
  */
-pub fn sum_versions_from_hex_str(hex_str: &str) -> u64 {
-    let (packet, _) = Packet::from_binary_str(&hex_to_bin(hex_str));
-
-    sum_versions(&packet)
+pub fn sum_versions_from_hex_str(hex_str: &str) -> Result<u64, AocError> {
+    Ok(sum_versions(&Packet::from_hex_str(hex_str)?))
 }
 
 pub fn sum_versions(packet: &Packet) -> u64 {
@@ -211,22 +266,36 @@ pub fn sum_versions(packet: &Packet) -> u64 {
     subpacket_sum + packet.get_version() as u64
 }
 
+/**
+Find the hexadecimal transmission: the first line of the input.
+
+# Examples
+```
+assert_eq!("D2FE28", aoc2021::day16::parse_transmission("D2FE28\n"));
+assert_eq!("", aoc2021::day16::parse_transmission(""));
+```
+ */
+pub fn parse_transmission(input: &str) -> &str {
+    input.lines().next().unwrap_or("").trim()
+}
+
+/**
+Load the hexadecimal transmission from a file.
+ */
+pub fn load_transmission(file: &str) -> Result<String, AocError> {
+    input::from_file(file, |input| Ok(String::from(parse_transmission(input))))
+}
+
 /**
 Run part 1 of Day 16's exercise.
 
 # Examples
 ```
-assert_eq!(31, aoc2021::day16::run_part1("test_inputs/day16_1.txt"));
+assert_eq!(31, aoc2021::day16::run_part1("test_inputs/day16_1.txt").unwrap());
 ```
  */
-pub fn run_part1(file: &str) -> u64 {
-    let file = File::open(file).expect("could not open file");
-    let mut sbuf = String::new();
-    match BufReader::new(file).read_line(&mut sbuf) {
-        Err(e) => {panic!("Error reading input: {:?}", e);},
-        _ => (),
-    };
-    sum_versions_from_hex_str(sbuf.trim())
+pub fn run_part1(file: &str) -> Result<u64, AocError> {
+    input::from_file(file, |input| sum_versions_from_hex_str(parse_transmission(input)))
 }
 
 /**
@@ -234,20 +303,18 @@ Evaluate the value of a packet.
 
 # Examples
 ```
-assert_eq!(3, aoc2021::day16::eval_packet_from_hex_str("C200B40A82"));
-assert_eq!(54, aoc2021::day16::eval_packet_from_hex_str("04005AC33890"));
-assert_eq!(7, aoc2021::day16::eval_packet_from_hex_str("880086C3E88112"));
-assert_eq!(9, aoc2021::day16::eval_packet_from_hex_str("CE00C43D881120"));
-assert_eq!(1, aoc2021::day16::eval_packet_from_hex_str("D8005AC2A8F0"));
-assert_eq!(0, aoc2021::day16::eval_packet_from_hex_str("F600BC2D8F"));
-assert_eq!(0, aoc2021::day16::eval_packet_from_hex_str("9C005AC2F8F0"));
-assert_eq!(1, aoc2021::day16::eval_packet_from_hex_str("9C0141080250320F1802104A08"));
+assert_eq!(3, aoc2021::day16::eval_packet_from_hex_str("C200B40A82").unwrap());
+assert_eq!(54, aoc2021::day16::eval_packet_from_hex_str("04005AC33890").unwrap());
+assert_eq!(7, aoc2021::day16::eval_packet_from_hex_str("880086C3E88112").unwrap());
+assert_eq!(9, aoc2021::day16::eval_packet_from_hex_str("CE00C43D881120").unwrap());
+assert_eq!(1, aoc2021::day16::eval_packet_from_hex_str("D8005AC2A8F0").unwrap());
+assert_eq!(0, aoc2021::day16::eval_packet_from_hex_str("F600BC2D8F").unwrap());
+assert_eq!(0, aoc2021::day16::eval_packet_from_hex_str("9C005AC2F8F0").unwrap());
+assert_eq!(1, aoc2021::day16::eval_packet_from_hex_str("9C0141080250320F1802104A08").unwrap());
 ```
  */
-pub fn eval_packet_from_hex_str(hex_str: &str) -> u64 {
-    let (packet, _) = Packet::from_binary_str(&hex_to_bin(hex_str));
-
-    eval_packet(&packet)
+pub fn eval_packet_from_hex_str(hex_str: &str) -> Result<u64, AocError> {
+    Ok(eval_packet(&Packet::from_hex_str(hex_str)?))
 }
 
 pub fn eval_packet(packet: &Packet) -> u64 {
@@ -286,16 +353,29 @@ Run part 2 of Day 16's exercise.
 
 # Examples
 ```
-assert_eq!(1, aoc2021::day16::run_part2("test_inputs/day16_2.txt"));
+assert_eq!(1, aoc2021::day16::run_part2("test_inputs/day16_2.txt").unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> u64 {
-    let file = File::open(file).expect("could not open file");
-    let mut sbuf = String::new();
-    match BufReader::new(file).read_line(&mut sbuf) {
-        Err(e) => {panic!("Error reading input: {:?}", e);},
-        _ => (),
-    };
-    eval_packet_from_hex_str(sbuf.trim())
+pub fn run_part2(file: &str) -> Result<u64, AocError> {
+    input::from_file(file, |input| eval_packet_from_hex_str(parse_transmission(input)))
+}
+
+pub struct Day16 {
+    packet: Packet,
+}
+
+impl Solution for Day16 {
+    fn parse(input: &str) -> Result<Day16, AocError> {
+        let packet = Packet::from_hex_str(parse_transmission(input))?;
+        Ok(Day16 { packet })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(sum_versions(&self.packet).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(eval_packet(&self.packet).into())
+    }
 }
 
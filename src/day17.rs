@@ -1,32 +1,64 @@
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
+
+/**
+The (low, high) x range and (low, high) y range of the target area.
+ */
+pub type Target = ((i32,i32),(i32,i32));
+
+/**
+Parse the x and y coordinates of the target into a 2-tuple of 2-tuple ints.
+The target must lie to the right of and below the launcher.
+
+```
+let input = "target area: x=20..30, y=-10..-5\n";
+assert_eq!(((20,30),(-10,-5)), aoc2021::day17::parse_target_coord(input).unwrap());
+let e = aoc2021::day17::parse_target_coord("target area: x=20..30, y=-5..-10\n").unwrap_err();
+assert_eq!("line 1, column 26: range is backwards", e.to_string());
+```
+ */
+pub fn parse_target_coord(input: &str) -> Result<Target, AocError> {
+    parse_target(input.lines().next().unwrap_or("").trim())
+}
 
 /**
 Load the x and y coordinates of the target into a 2-tuple of 2-tuple ints.
+The target must lie to the right of and below the launcher.
 
 ```
-assert_eq!(((20,30),(-10,-5)), aoc2021::day17::load_target_coord("test_inputs/day17.txt"));
+assert_eq!(((20,30),(-10,-5)), aoc2021::day17::load_target_coord("test_inputs/day17.txt").unwrap());
 ```
  */
-pub fn load_target_coord(file: &str) -> ((i32,i32),(i32,i32)) {
-    let file = File::open(file).expect("could not open file");
-    let mut sbuf = String::new();
-    match BufReader::new(file).read_line(&mut sbuf) {
-        Err(e) => {panic!("Error reading input: {:?}", e);},
-        _ => (),
-    };
-    let sbuf = sbuf.trim();
-    // trim leading "target area: "
-    let s: &str = &sbuf[13..];
-    let v: Vec<(i32,i32)> = s.split(", ")
-        .map(|ss| {
-            let vss: &Vec<&str> = &ss[2..].split('.').collect();
-            (vss.iter().nth(0).unwrap().parse::<i32>().unwrap(), 
-             vss.iter().nth_back(0).unwrap().parse::<i32>().unwrap())
-        }).collect();
-    (v[0],v[1])
+pub fn load_target_coord(file: &str) -> Result<Target, AocError> {
+    input::from_file(file, parse_target_coord)
+}
+
+fn parse_target(line: &str) -> Result<Target, AocError> {
+    let s = line.strip_prefix("target area: ")
+        .ok_or_else(|| AocError::parse(1, 1, "expected \"target area: \""))?;
+    let (x, y) = s.split_once(", ")
+        .ok_or_else(|| AocError::parse(1, column_of(line, s), "expected \"x=..., y=...\""))?;
+    let x = parse_range(line, x, "x=")?;
+    let y = parse_range(line, y, "y=")?;
+    if x.0 <= 0 || y.1 >= 0 {
+        return Err(AocError::parse(1, column_of(line, s),
+            "target must be right of and below the launcher"));
+    }
+    Ok((x, y))
+}
+
+fn parse_range(line: &str, s: &str, prefix: &str) -> Result<(i32,i32), AocError> {
+    let range = s.strip_prefix(prefix)
+        .ok_or_else(|| AocError::parse(1, column_of(line, s), format!("expected \"{}\"", prefix)))?;
+    let (low, high) = range.split_once("..")
+        .ok_or_else(|| AocError::parse(1, column_of(line, range), "expected \"low..high\""))?;
+    let range = (parse_token(line, low)?, parse_token(line, high)?);
+    if range.0 > range.1 {
+        return Err(AocError::parse(1, column_of(line, low), "range is backwards"));
+    }
+    Ok(range)
 }
 
 /**
@@ -35,7 +67,7 @@ y coordinate in the target area.
 
 # Examples
 ```
-let (_, y_target) = aoc2021::day17::load_target_coord("test_inputs/day17.txt");
+let (_, y_target) = aoc2021::day17::load_target_coord("test_inputs/day17.txt").unwrap();
 assert_eq!(20, aoc2021::day17::max_steps(y_target.0));
 ```
  */
@@ -50,52 +82,117 @@ pub fn init_vel(steps: i32, target: i32, ) -> f64 {
     target as f64 / steps as f64 + (steps - 1) as f64 / 2.0
 }
 
+/**
+What initial x velocity leaves the probe stopped at the target?
+ */
+fn stop_vel(target: i32) -> f64 {
+    ((1.0 + 8.0 * target as f64).sqrt() - 1.0) / 2.0
+}
+
 /**
 Return all starting trajectories that will end with the probe in the target
 range after the specified number of steps.
+
+# Examples
+```
+use aoc2021::day17::trajectories;
+
+// after 3 steps, x velocity 9 is still moving, at 24
+let found = trajectories(3, &(21, 24), &(-10, -5));
+assert!(found.contains(&(9, -1)));
+assert!(!found.contains(&(7, -1)));
+// after 7 steps, x velocity 6 has stopped at 21
+assert!(trajectories(7, &(21, 24), &(-10, -5)).contains(&(6, 2)));
+```
  */
 pub fn trajectories(steps: i32, x_range: &(i32,i32), y_range: &(i32, i32)) -> HashSet<(i32,i32)> {
     if steps == 0 {
         panic!("Expected positive number of steps");
     }
-    // Find initial x velocities to reach the beginning and end of the target range
-    let mut x_v_init_range: Vec<f64> = [x_range.0, x_range.1].iter()
-        .map(|x| init_vel(steps, *x))
-        .collect();
-    // Note that the function above only works if the number of steps is less than
-    // or equal to the initial velocity
-    if steps as f64 > x_v_init_range[1]{
-        x_v_init_range = [x_range.0, x_range.1].iter()
-            .map(|x| ((1.0 + 8.0 * *x as f64).sqrt() - 1.0) / 2.0)
-            .collect();
-    }
-    let x_v_init_range = [x_v_init_range[0].ceil() as i32, x_v_init_range[1].floor() as i32];
-    let y_v_init_range: Vec<f64> = [y_range.0, y_range.1].iter()
-        .map(|y| init_vel(steps, *y))
-        .collect();
-    let y_v_init_range = [y_v_init_range[0].ceil() as i32, y_v_init_range[1].floor() as i32];
+    // The probe is still moving after the steps if its x velocity was at
+    // least the number of steps, or has stopped if it was less
+    let moving = (init_vel(steps, x_range.0).ceil() as i32).max(steps) ..= init_vel(steps, x_range.1).floor() as i32;
+    let stopped = stop_vel(x_range.0).ceil() as i32 ..= (stop_vel(x_range.1).floor() as i32).min(steps - 1);
+    let y_v_init_range = init_vel(steps, y_range.0).ceil() as i32 ..= init_vel(steps, y_range.1).floor() as i32;
     let mut all_trajectories = HashSet::new();
-    for x_v in x_v_init_range[0] .. x_v_init_range[1]+1 {
-        for y_v in y_v_init_range[0] .. y_v_init_range[1]+1 {
+    for x_v in moving.chain(stopped) {
+        for y_v in y_v_init_range.clone() {
             all_trajectories.insert((x_v,y_v));
-
         }
     }
     all_trajectories
 }
 
+/**
+All the initial velocities that land the probe in the target.
+ */
+pub fn velocities(x_target: &(i32, i32), y_target: &(i32, i32)) -> HashSet<(i32, i32)> {
+    let mut all_trajectories: HashSet<(i32,i32)> = HashSet::new();
+    for steps in 1 .. max_steps(y_target.0) + 1 {
+        all_trajectories.extend(trajectories(steps, x_target, y_target).iter());
+    }
+    all_trajectories
+}
+
+/**
+The highest y position reachable while still landing in the target. A
+probe launched upward at y velocity v peaks at v(v+1)/2, but only reaches
+the target with that velocity if some x velocity brings it there too.
+
+# Examples
+```
+use aoc2021::day17::highest_y;
+
+assert_eq!(45, highest_y(&(20, 30), &(-10, -5)));
+// no x velocity stops the probe above x=23..24, so it must fire downward
+assert_eq!(0, highest_y(&(23, 24), &(-23, -13)));
+```
+ */
+pub fn highest_y(x_target: &(i32, i32), y_target: &(i32, i32)) -> i32 {
+    velocities(x_target, y_target).iter()
+        .map(|(_, y_v)| if *y_v > 0 { y_v * (y_v + 1) / 2 } else { 0 })
+        .max()
+        .unwrap_or(0)
+}
+
+/**
+Count the initial velocities that land the probe in the target.
+
+# Examples
+```
+assert_eq!(112, aoc2021::day17::count_velocities(&(20, 30), &(-10, -5)));
+assert_eq!(55, aoc2021::day17::count_velocities(&(1, 11), &(-4, -2)));
+```
+ */
+pub fn count_velocities(x_target: &(i32, i32), y_target: &(i32, i32)) -> usize {
+    velocities(x_target, y_target).len()
+}
+
 /**
 Run part 1 of Day 17's exercise.
 
 # Examples
 
 ```
-assert_eq!(45, aoc2021::day17::run_part1("test_inputs/day17.txt"));
+assert_eq!(45, aoc2021::day17::run_part1("test_inputs/day17.txt").unwrap());
 ```
  */
-pub fn run_part1(file: &str) -> i32 {
-    let (_, y_target) = load_target_coord(file);
-    y_target.0 * (y_target.0 + 1) / 2
+pub fn run_part1(file: &str) -> Result<i32, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of Day 17's exercise from the text of the input.
+
+# Examples
+
+```
+assert_eq!(45, aoc2021::day17::solve_part1("target area: x=20..30, y=-10..-5\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<i32, AocError> {
+    let (x_target, y_target) = parse_target_coord(input)?;
+    Ok(highest_y(&x_target, &y_target))
 }
 
 /**
@@ -104,15 +201,43 @@ Run part 2 of Day 17's exercise.
 # Examples
 
 ```
-assert_eq!(112, aoc2021::day17::run_part2("test_inputs/day17.txt"));
+assert_eq!(112, aoc2021::day17::run_part2("test_inputs/day17.txt").unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> usize {
-    let (x_target, y_target) = load_target_coord(file);
-    let mut all_trajectories: HashSet<(i32,i32)> = HashSet::new();
-    for steps in 1 .. max_steps(y_target.0) + 1 {
-        all_trajectories.extend(trajectories(steps, &x_target, &y_target).iter());
+pub fn run_part2(file: &str) -> Result<usize, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of Day 17's exercise from the text of the input.
+
+# Examples
+
+```
+assert_eq!(112, aoc2021::day17::solve_part2("target area: x=20..30, y=-10..-5\n").unwrap());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<usize, AocError> {
+    let (x_target, y_target) = parse_target_coord(input)?;
+    Ok(count_velocities(&x_target, &y_target))
+}
+
+pub struct Day17 {
+    x_target: (i32, i32),
+    y_target: (i32, i32),
+}
+
+impl Solution for Day17 {
+    fn parse(input: &str) -> Result<Day17, AocError> {
+        let (x_target, y_target) = parse_target_coord(input)?;
+        Ok(Day17 { x_target, y_target })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(highest_y(&self.x_target, &self.y_target).into())
     }
-    all_trajectories.len()
 
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(count_velocities(&self.x_target, &self.y_target).into())
+    }
 }
@@ -1,6 +1,8 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Side {
@@ -34,7 +36,7 @@ pub struct Entry {
     pub next: Option<usize>,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PFNumber {
     pub entries: Vec<Entry>,
     pub head: Option<usize>,
@@ -55,9 +57,20 @@ impl PFNumber {
 
     /**
     Parse string into a representation of snailfish numbers.
+
+    # Examples
+    ```
+    use aoc2021::day18::PFNumber;
+    assert!(PFNumber::parse("[[1,2],3]").is_ok());
+    let e = PFNumber::parse("[[1,2],3").unwrap_err();
+    assert_eq!("line 1, column 9: expected ']'", e.to_string());
     ```
      */
-    pub fn parse(s: &str) -> PFNumber {
+    pub fn parse(s: &str) -> Result<PFNumber, AocError> {
+        let end = PFNumber::check_element(s.as_bytes(), 0)?;
+        if end != s.len() {
+            return Err(AocError::parse(1, end + 1, "unexpected text after number"));
+        }
         let mut entries: Vec<Entry> = Vec::new();
         let mut level: usize = 0;
         let mut brac_stac: Vec<usize> = Vec::new();
@@ -116,11 +129,34 @@ impl PFNumber {
         }
 
         let entries_len = entries.len();
-        PFNumber {
+        Ok(PFNumber {
             entries,
             head: Option::Some(0),
             tail: Option::Some(entries_len - 1),
             open_entries: Vec::new(),
+        })
+    }
+
+    /**
+    Check that a digit or a pair starts at `ndx`. Returns the index just past it.
+     */
+    fn check_element(s: &[u8], ndx: usize) -> Result<usize, AocError> {
+        let expect = |ndx: usize, c: u8| -> Result<usize, AocError> {
+            if s.get(ndx) == Some(&c) {
+                Ok(ndx + 1)
+            } else {
+                Err(AocError::parse(1, ndx + 1, format!("expected '{}'", c as char)))
+            }
+        };
+        match s.get(ndx) {
+            Some(c) if c.is_ascii_digit() => Ok(ndx + 1),
+            Some(b'[') => {
+                let ndx = PFNumber::check_element(s, ndx + 1)?;
+                let ndx = expect(ndx, b',')?;
+                let ndx = PFNumber::check_element(s, ndx)?;
+                expect(ndx, b']')
+            }
+            _ => Err(AocError::parse(1, ndx + 1, "expected a digit or '['")),
         }
     }
 
@@ -131,32 +167,32 @@ impl PFNumber {
     # Examples
     ```
     use aoc2021::day18::PFNumber;
-    let mut pfn = PFNumber::parse("[[1,2],[[3,4],5]]");
+    let mut pfn = PFNumber::parse("[[1,2],[[3,4],5]]").unwrap();
     assert!(!pfn.reduce());
-    let mut pfn = PFNumber::parse("[[[[[9,8],1],2],3],4]");
+    let mut pfn = PFNumber::parse("[[[[[9,8],1],2],3],4]").unwrap();
     assert!(pfn.reduce());
-    let expected = PFNumber::parse("[[[[0,9],2],3],4]");
+    let expected = PFNumber::parse("[[[[0,9],2],3],4]").unwrap();
     assert_eq!(expected, pfn);
-    let mut pfn = PFNumber::parse("[7,[6,[5,[4,[3,2]]]]]");
+    let mut pfn = PFNumber::parse("[7,[6,[5,[4,[3,2]]]]]").unwrap();
     assert!(pfn.reduce());
-    let expected = PFNumber::parse("[7,[6,[5,[7,0]]]]");
+    let expected = PFNumber::parse("[7,[6,[5,[7,0]]]]").unwrap();
     assert_eq!(expected, pfn);
-    let mut pfn = PFNumber::parse("[[6,[5,[4,[3,2]]]],1]");
+    let mut pfn = PFNumber::parse("[[6,[5,[4,[3,2]]]],1]").unwrap();
     assert!(pfn.reduce());
-    let expected = PFNumber::parse("[[6,[5,[7,0]]],3]");
+    let expected = PFNumber::parse("[[6,[5,[7,0]]],3]").unwrap();
     assert_eq!(expected, pfn);
-    let mut pfn = PFNumber::parse("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]");
+    let mut pfn = PFNumber::parse("[[3,[2,[1,[7,3]]]],[6,[5,[4,[3,2]]]]]").unwrap();
     assert!(pfn.reduce());
-    let expected = PFNumber::parse("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]");
+    let expected = PFNumber::parse("[[3,[2,[8,0]]],[9,[5,[4,[3,2]]]]]").unwrap();
     assert_eq!(expected, pfn);
     assert!(pfn.reduce());
-    let expected = PFNumber::parse("[[3,[2,[8,0]]],[9,[5,[7,0]]]]");
+    let expected = PFNumber::parse("[[3,[2,[8,0]]],[9,[5,[7,0]]]]").unwrap();
     assert_eq!(expected, pfn);
 
-    let mut pfn = PFNumber::parse("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+    let mut pfn = PFNumber::parse("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]").unwrap();
     assert!(pfn.reduce());
     println!("{}", pfn.to_string());
-    let expected = PFNumber::parse("[[[[0,7],4],[7,[[8,4],9]]],[1,1]]");
+    let expected = PFNumber::parse("[[[[0,7],4],[7,[[8,4],9]]],[1,1]]").unwrap();
     assert_eq!(expected, pfn);
     assert!(pfn.reduce());
     println!("{}", pfn.to_string());
@@ -164,11 +200,11 @@ impl PFNumber {
     println!("{}", pfn.to_string());
     assert!(pfn.reduce());
     println!("{}", pfn.to_string());
-    let expected = PFNumber::parse("[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]");
+    let expected = PFNumber::parse("[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]").unwrap();
     assert_eq!(expected, pfn);
     assert!(pfn.reduce());
     println!("{}", pfn.to_string());
-    let expected = PFNumber::parse("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    let expected = PFNumber::parse("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]").unwrap();
     assert_eq!(expected, pfn);
     assert!(!pfn.reduce());
     println!("{}", pfn.to_string());
@@ -382,36 +418,18 @@ impl PFNumber {
         }
     }
 
-    pub fn to_string(&self) -> String {
-        let mut s = String::new();
-        let mut ndx = self.head;
-        while ndx.is_some() {
-            let app: String = match self.entries[ndx.unwrap()].data {
-                EntryType::Bracket(bd) => match bd.side {
-                    Side::Left => format!("["),
-                    Side::Right => format!("]"),
-                },
-                EntryType::Number(nd) => format!("{} ", nd.value),
-                EntryType::Comma => ",".to_string(),
-            };
-            s.push_str(&app);
-            ndx = self.entries[ndx.unwrap()].next;
-        }
-        s
-    }
-
     /**
     Reduce this snailfish number until only the magnitude remains.
 
     # Examples
     ```
     use aoc2021::day18::PFNumber;
-    assert_eq!(143, PFNumber::parse("[[1,2],[[3,4],5]]").magnitude_reduce());
-    assert_eq!(1384, PFNumber::parse("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]").magnitude_reduce());
-    assert_eq!(445, PFNumber::parse("[[[[1,1],[2,2]],[3,3]],[4,4]]").magnitude_reduce());
-    assert_eq!(791, PFNumber::parse("[[[[3,0],[5,3]],[4,4]],[5,5]]").magnitude_reduce());
-    assert_eq!(1137, PFNumber::parse("[[[[5,0],[7,4]],[5,5]],[6,6]]").magnitude_reduce());
-    assert_eq!(3488, PFNumber::parse("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude_reduce());
+    assert_eq!(143, PFNumber::parse("[[1,2],[[3,4],5]]").unwrap().magnitude_reduce());
+    assert_eq!(1384, PFNumber::parse("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]").unwrap().magnitude_reduce());
+    assert_eq!(445, PFNumber::parse("[[[[1,1],[2,2]],[3,3]],[4,4]]").unwrap().magnitude_reduce());
+    assert_eq!(791, PFNumber::parse("[[[[3,0],[5,3]],[4,4]],[5,5]]").unwrap().magnitude_reduce());
+    assert_eq!(1137, PFNumber::parse("[[[[5,0],[7,4]],[5,5]],[6,6]]").unwrap().magnitude_reduce());
+    assert_eq!(3488, PFNumber::parse("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").unwrap().magnitude_reduce());
     ```
      */
     pub fn magnitude_reduce(&mut self) -> u64 {
@@ -481,9 +499,9 @@ impl PFNumber {
     # Examples
     ```
     use aoc2021::day18::PFNumber;
-    let mut accumulator = PFNumber::parse("[1,2]");
-    let addend = PFNumber::parse("[[3,4],5]");
-    let expected = PFNumber::parse("[[1,2],[[3,4],5]]");
+    let mut accumulator = PFNumber::parse("[1,2]").unwrap();
+    let addend = PFNumber::parse("[[3,4],5]").unwrap();
+    let expected = PFNumber::parse("[[1,2],[[3,4],5]]").unwrap();
     accumulator.add_assign(&addend);
     assert_eq!(expected, accumulator);
     ```
@@ -580,6 +598,51 @@ impl PFNumber {
     }
 }
 
+/**
+Write the snailfish number as it is written in the input.
+
+# Examples
+```
+use aoc2021::day18::PFNumber;
+assert_eq!("[[1,2],[[3,4],5]]", PFNumber::parse("[[1,2],[[3,4],5]]").unwrap().to_string());
+```
+ */
+impl fmt::Display for PFNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // parsing drops the commas, so one goes before any element that
+        // follows another
+        let mut after_element = false;
+        let mut ndx = self.head;
+        while let Some(n) = ndx {
+            match self.entries[n].data {
+                EntryType::Bracket(bd) => match bd.side {
+                    Side::Left => {
+                        if after_element {
+                            write!(f, ",")?;
+                        }
+                        write!(f, "[")?;
+                        after_element = false;
+                    },
+                    Side::Right => {
+                        write!(f, "]")?;
+                        after_element = true;
+                    },
+                },
+                EntryType::Number(nd) => {
+                    if after_element {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", nd.value)?;
+                    after_element = true;
+                },
+                EntryType::Comma => {},
+            };
+            ndx = self.entries[n].next;
+        }
+        Ok(())
+    }
+}
+
 impl PartialEq for PFNumber {
     fn eq(&self, other: &Self) -> bool {
         let mut self_ndx = self.head;
@@ -631,52 +694,94 @@ impl PartialEq for PFNumber {
 impl Eq for PFNumber {}
 
 /**
-Run part 1 of the Day 18 exercise.
+Parse one snailfish number per line.
 
 # Examples
 ```
-assert_eq!(4140, aoc2021::day18::run_part1("test_inputs/day18.txt"));
+assert_eq!(2, aoc2021::day18::parse_numbers("[1,2]\n[[3,4],5]\n").unwrap().len());
+let e = aoc2021::day18::parse_numbers("[1,2]\n[3,4]]\n").unwrap_err();
+assert_eq!("line 2, column 6: unexpected text after number", e.to_string());
 ```
  */
-pub fn run_part1(file: &str) -> u64 {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
-    let mut s: String = String::new();
-    match buf_reader.read_line(&mut s) {
-        Err(e) => panic!("{}", e),
-        _ => (),
-    };
-    let s = s.trim();
-    let mut pfn = PFNumber::parse(&s);
-    let pf_numbers: Vec<PFNumber> = buf_reader
+pub fn parse_numbers(input: &str) -> Result<Vec<PFNumber>, AocError> {
+    input
         .lines()
-        .map(|line| PFNumber::parse(&line.unwrap()))
-        .collect();
-    for addend in pf_numbers.iter() {
-        pfn.add_assign(addend);
-        while pfn.reduce() { }
+        .enumerate()
+        .map(|(ndx, line)| PFNumber::parse(line.trim()).map_err(|e| e.on_line(ndx + 1)))
+        .collect()
+}
+
+/**
+Load one snailfish number per line from a file.
+ */
+pub fn load_numbers(file: &str) -> Result<Vec<PFNumber>, AocError> {
+    input::from_file(file, parse_numbers)
+}
+
+/**
+Add a snailfish number and reduce the sum, tracing the addition and each
+reduction, and counting them in `step`.
+ */
+fn add_traced(pfn: &mut PFNumber, addend: &PFNumber, step: &mut usize, tracer: &mut dyn Tracer) {
+    pfn.add_assign(addend);
+    *step += 1;
+    tracer.step(*step, "add", pfn);
+    while pfn.reduce() {
+        *step += 1;
+        tracer.step(*step, "reduce", pfn);
     }
-    pfn.magnitude_reduce()
 }
 
 /**
-Run part 2 of the Day 18 exercise.
+Add up all the snailfish numbers in order and find the magnitude of the sum.
+ */
+pub fn sum_magnitude(pf_numbers: &[PFNumber]) -> Result<u64, AocError> {
+    sum_magnitude_traced(pf_numbers, &mut NoTrace)
+}
+
+/**
+Add up the snailfish numbers like [`sum_magnitude`], tracing each addition
+and each reduction.
 
 # Examples
 ```
-assert_eq!(3993, aoc2021::day18::run_part2("test_inputs/day18.txt"));
+let numbers = aoc2021::day18::parse_numbers("[[[[4,3],4],4],[7,[[8,4],9]]]\n[1,1]\n").unwrap();
+let mut steps = Vec::new();
+let mut tracer = |_: usize, action: &str, state: &dyn std::fmt::Display| steps.push(format!("{} {}", action, state));
+aoc2021::day18::sum_magnitude_traced(&numbers, &mut tracer).unwrap();
+assert_eq!("add [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]", steps[0]);
+assert_eq!("reduce [[[[0,7],4],[7,[[8,4],9]]],[1,1]]", steps[1]);
+assert_eq!("reduce [[[[0,7],4],[[7,8],[6,0]]],[8,1]]", steps[5]);
+assert_eq!(6, steps.len());
 ```
  */
-pub fn run_part2(file: &str) -> u64 {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let pfn_strings: Vec<String> = buf_reader
-        .lines()
-        .map(|l| l.unwrap())
-        .collect();
+pub fn sum_magnitude_traced(pf_numbers: &[PFNumber], tracer: &mut dyn Tracer) -> Result<u64, AocError> {
+    let mut pfn = pf_numbers.first()
+        .ok_or_else(|| AocError::Unsolvable(String::from("no snailfish numbers")))?
+        .clone();
+    let mut step = 0;
+    for addend in pf_numbers[1..].iter() {
+        add_traced(&mut pfn, addend, &mut step, tracer);
+    }
+    Ok(pfn.magnitude_reduce())
+}
+
+/**
+Find the largest magnitude of the sum of any two different snailfish numbers.
+ */
+pub fn largest_pair_magnitude(pf_numbers: &[PFNumber]) -> Result<u64, AocError> {
+    largest_pair_magnitude_traced(pf_numbers, &mut NoTrace)
+}
+
+/**
+Find the largest magnitude like [`largest_pair_magnitude`], tracing the
+addition and reduction of every pair.
+ */
+pub fn largest_pair_magnitude_traced(pf_numbers: &[PFNumber], tracer: &mut dyn Tracer) -> Result<u64, AocError> {
+    let mut step = 0;
     let mut combos = Vec::new();
-    for a in 0..pfn_strings.len() {
-        for b in 0..pfn_strings.len() {
+    for a in 0..pf_numbers.len() {
+        for b in 0..pf_numbers.len() {
             if a != b {
                 combos.push((a,b));
             }
@@ -684,10 +789,83 @@ pub fn run_part2(file: &str) -> u64 {
     }
     combos.iter()
         .map(|t| {
-            let mut pfn = PFNumber::parse(&pfn_strings[t.0]);
-            pfn.add_assign(&PFNumber::parse(&pfn_strings[t.1]));
-            while pfn.reduce() {}
+            let mut pfn = pf_numbers[t.0].clone();
+            add_traced(&mut pfn, &pf_numbers[t.1], &mut step, tracer);
             pfn.magnitude_reduce()
         })
-        .max().unwrap()
+        .max()
+        .ok_or_else(|| AocError::Unsolvable(String::from("need at least two snailfish numbers")))
+}
+
+/**
+Run part 1 of the Day 18 exercise.
+
+# Examples
+```
+assert_eq!(4140, aoc2021::day18::run_part1("test_inputs/day18.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<u64, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of the Day 18 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(3 * 3 + 2 * 4, aoc2021::day18::solve_part1("[3,4]\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<u64, AocError> {
+    sum_magnitude(&parse_numbers(input)?)
+}
+
+/**
+Run part 2 of the Day 18 exercise.
+
+# Examples
+```
+assert_eq!(3993, aoc2021::day18::run_part2("test_inputs/day18.txt").unwrap());
+```
+ */
+pub fn run_part2(file: &str) -> Result<u64, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the Day 18 exercise from the text of the input.
+
+# Examples
+```
+assert!(aoc2021::day18::solve_part2("[3,4]\n").is_err());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<u64, AocError> {
+    largest_pair_magnitude(&parse_numbers(input)?)
+}
+
+pub struct Day18 {
+    pf_numbers: Vec<PFNumber>,
+}
+
+impl Solution for Day18 {
+    fn parse(input: &str) -> Result<Day18, AocError> {
+        Ok(Day18 { pf_numbers: parse_numbers(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(sum_magnitude(&self.pf_numbers)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(largest_pair_magnitude(&self.pf_numbers)?.into())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        match part {
+            1 => Ok(sum_magnitude_traced(&self.pf_numbers, tracer)?.into()),
+            _ => Ok(largest_pair_magnitude_traced(&self.pf_numbers, tracer)?.into()),
+        }
+    }
 }
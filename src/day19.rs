@@ -1,7 +1,33 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use crate::error::{parse_token, AocError};
+use crate::input;
+use crate::params::{self, Params};
+use crate::solution::{Answer, Solution};
+
+/**
+How many beacons two scanners must both see to be aligned, which can be
+changed with the `overlap` parameter.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub overlap: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { overlap: 12 }
+    }
+}
+
+impl Options {
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["overlap"])?;
+        let mut options = Options::default();
+        params.set("overlap", &mut options.overlap)?;
+        params::at_least("overlap", options.overlap, 1)?;
+        Ok(options)
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
 enum Angle {
@@ -78,12 +104,13 @@ fn beacon_sum(a: &[i32; 3], b: &[i32; 3]) -> [i32; 3] {
 
 /**
  * Returns a vec of pairs of indexes which represent the same beacon found by
- * different scanners.
+ * different scanners, when at least `overlap` beacons are the same.
  */
 fn find_overlap(
     a: &Vec<[i32; 3]>,
     b: &Vec<[i32; 3]>,
     rots: &HashSet<[i32; 9]>,
+    overlap: usize,
 ) -> (Option<[i32; 3]>, Vec<[i32; 3]>) {
     for m in rots.iter() {
         let rot_b: Vec<[i32; 3]> = b.iter().map(|beacon| rotate(m, beacon)).collect();
@@ -102,7 +129,7 @@ fn find_overlap(
             }
         }
         for item in diff_map.iter() {
-            if item.1.len() >= 12 {
+            if item.1.len() >= overlap {
                 return (
                     Option::Some(*item.0),
                     rot_b.iter().map(|b| beacon_sum(item.0, b)).collect(),
@@ -113,29 +140,49 @@ fn find_overlap(
     (Option::None, Vec::new())
 }
 
-fn load_scanners(file: &str) -> Vec<Vec<[i32; 3]>> {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
+/**
+Parse the beacons seen by each scanner.
+
+# Examples
+```
+let scanners = aoc2021::day19::parse_scanners("--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6\n").unwrap();
+assert_eq!(vec![vec![[1, 2, 3]], vec![[4, 5, 6]]], scanners);
+let e = aoc2021::day19::parse_scanners("1,2,3\n").unwrap_err();
+assert_eq!("line 1, column 1: beacon before the first scanner header", e.to_string());
+```
+ */
+pub fn parse_scanners(input: &str) -> Result<Vec<Vec<[i32; 3]>>, AocError> {
     let mut scanners = Vec::new();
-    for line in buf_reader.lines() {
-        let line_unwrap = line.unwrap();
-        if line_unwrap.len() > 0 {
+    for (ndx, line_unwrap) in input.lines().enumerate() {
+        if !line_unwrap.is_empty() {
             if line_unwrap.starts_with("--") {
                 scanners.push(Vec::new());
             } else {
-                let v: Vec<i32> = line_unwrap
-                    .split(',')
-                    .map(|s| s.parse::<i32>().unwrap())
-                    .collect();
+                let beacon = parse_beacon(line_unwrap).map_err(|e| e.on_line(ndx + 1))?;
                 scanners
                     .iter_mut()
                     .nth_back(0)
-                    .unwrap()
-                    .push([v[0], v[1], v[2]]);
+                    .ok_or_else(|| AocError::parse(ndx + 1, 1, "beacon before the first scanner header"))?
+                    .push(beacon);
             }
         }
     }
-    scanners
+    Ok(scanners)
+}
+
+/**
+Load the beacons seen by each scanner from a file.
+ */
+pub fn load_scanners(file: &str) -> Result<Vec<Vec<[i32; 3]>>, AocError> {
+    input::from_file(file, parse_scanners)
+}
+
+fn parse_beacon(line: &str) -> Result<[i32; 3], AocError> {
+    let v: Vec<&str> = line.split(',').collect();
+    if v.len() != 3 {
+        return Err(AocError::parse(1, 1, format!("expected 3 coordinates, found {}", v.len())));
+    }
+    Ok([parse_token(line, v[0])?, parse_token(line, v[1])?, parse_token(line, v[2])?])
 }
 
 /**
@@ -143,17 +190,50 @@ Run part 1 of the Day 19 exercise.
 
 # Examples
 ```
-assert_eq!(79, aoc2021::day19::run_part1("test_inputs/day19.txt"));
+assert_eq!(79, aoc2021::day19::run_part1("test_inputs/day19.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<usize, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of the Day 19 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(2, aoc2021::day19::solve_part1("--- scanner 0 ---\n1,2,3\n4,5,6\n").unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<usize, AocError> {
+    count_beacons(&parse_scanners(input)?, &Options::default())
+}
+
+/**
+Merge the scanners' beacons together and count the unique beacons.
+
+# Examples
+```
+use aoc2021::day19::{count_beacons, Options};
+
+// the scanners see two beacons in common, which is enough with an overlap of 2
+let scanners = vec![vec![[0, 0, 0], [1, 2, 3]], vec![[5, 5, 5], [6, 7, 8], [9, 9, 9]]];
+assert_eq!(3, count_beacons(&scanners, &Options { overlap: 2 }).unwrap());
+assert!(count_beacons(&scanners, &Options::default()).is_err());
 ```
  */
-pub fn run_part1(file: &str) -> usize {
+pub fn count_beacons(scanners: &[Vec<[i32; 3]>], options: &Options) -> Result<usize, AocError> {
     let matrices = all_rot_matrices();
-    let mut scanners = load_scanners(file);
+    let mut scanners = scanners.to_vec();
+    if scanners.is_empty() {
+        return Err(AocError::Unsolvable(String::from("no scanners")));
+    }
     // merge the scanners into the first scanner
     while scanners.len() > 1 {
+        let before = scanners.len();
         for a_ndx in 0..scanners.len() - 1 {
             for b_ndx in a_ndx + 1..scanners.len() {
-                let (_, overlaps) = find_overlap(&scanners[a_ndx], &scanners[b_ndx], &matrices);
+                let (_, overlaps) = find_overlap(&scanners[a_ndx], &scanners[b_ndx], &matrices, options.overlap);
                 if overlaps.len() > 0 {
                     // merge a and b together
                     let mut new_scanner_a: HashSet<[i32; 3]> = HashSet::new();
@@ -172,9 +252,12 @@ pub fn run_part1(file: &str) -> usize {
                 }
             }
         }
+        if scanners.len() == before {
+            return Err(AocError::Unsolvable(format!("{} groups of scanners do not overlap", before)));
+        }
     }
 
-    scanners[0].len()
+    Ok(scanners[0].len())
 }
 
 /**
@@ -182,20 +265,40 @@ Run part 2 of the Day 19 exercise.
 
 # Examples
 ```
-assert_eq!(3621, aoc2021::day19::run_part2("test_inputs/day19.txt"));
+assert_eq!(3621, aoc2021::day19::run_part2("test_inputs/day19.txt").unwrap());
+```
+ */
+pub fn run_part2(file: &str) -> Result<i32, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the Day 19 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(0, aoc2021::day19::solve_part2("--- scanner 0 ---\n1,2,3\n4,5,6\n").unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> i32 {
+pub fn solve_part2(input: &str) -> Result<i32, AocError> {
+    max_scanner_distance(&parse_scanners(input)?, &Options::default())
+}
+
+/**
+Align the scanners and find the largest Manhattan distance between any two.
+ */
+pub fn max_scanner_distance(scanners: &[Vec<[i32; 3]>], options: &Options) -> Result<i32, AocError> {
     let matrices = all_rot_matrices();
-    let mut scanners = load_scanners(file);
-    let mut scanner_distances: HashMap<(usize, usize), [i32;3]> = HashMap::new();
+    let mut scanners = scanners.to_vec();
+    // scanner 0 is at the origin
+    let mut scanner_distances: HashMap<(usize, usize), [i32;3]> = HashMap::from([((0, 0), [0, 0, 0])]);
     let mut aligned_scanners: Vec<usize> = vec![0];
     let mut as_ndx: usize = 0;
-    while as_ndx < scanners.len() {
+    while as_ndx < aligned_scanners.len() {
         let a_ndx = aligned_scanners[as_ndx];
         for b_ndx in 0..scanners.len() {
             if !aligned_scanners.contains(&b_ndx) {
-                let (loc, overlaps) = find_overlap(&scanners[a_ndx], &scanners[b_ndx], &matrices);
+                let (loc, overlaps) = find_overlap(&scanners[a_ndx], &scanners[b_ndx], &matrices, options.overlap);
                 if loc.is_some() {
                     scanners[b_ndx] = overlaps;
                     aligned_scanners.push(b_ndx);
@@ -205,11 +308,15 @@ pub fn run_part2(file: &str) -> i32 {
         }
         as_ndx += 1;
     }
+    if aligned_scanners.len() < scanners.len() {
+        return Err(AocError::Unsolvable(format!("only {} of {} scanners overlap",
+            aligned_scanners.len(), scanners.len())));
+    }
     let mut max_man_dist: i32 = 0;
     let scanner_distances: Vec<[i32;3]> = scanner_distances.iter()
         .map(|s| *s.1)
         .collect();
-    for c_ndx in 0..scanner_distances.len()-1 {
+    for c_ndx in 0..scanner_distances.len() {
         for d_ndx in c_ndx+1..scanner_distances.len() {
             let diff = beacon_diff(&scanner_distances[c_ndx], &scanner_distances[d_ndx]);
             let man_dist: i32 = diff.iter()
@@ -218,5 +325,29 @@ pub fn run_part2(file: &str) -> i32 {
             max_man_dist = max_man_dist.max(man_dist);
         }
     }
-    max_man_dist
+    Ok(max_man_dist)
+}
+
+pub struct Day19 {
+    scanners: Vec<Vec<[i32; 3]>>,
+    options: Options,
+}
+
+impl Solution for Day19 {
+    fn parse(input: &str) -> Result<Day19, AocError> {
+        Ok(Day19 { scanners: parse_scanners(input)?, options: Options::default() })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_beacons(&self.scanners, &self.options)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(max_scanner_distance(&self.scanners, &self.options)?.into())
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        self.options = Options::from_params(params)?;
+        Ok(())
+    }
 }
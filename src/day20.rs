@@ -1,129 +1,122 @@
-use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
+use std::fmt;
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors::{self, Neighborhood};
+use crate::params::Params;
+use crate::input;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
 
+/**
+How many times the image is enhanced in each part, which can be changed
+with the `part1_steps` and `part2_steps` parameters.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub part1_steps: usize,
+    pub part2_steps: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { part1_steps: 2, part2_steps: 50 }
+    }
+}
+
+impl Options {
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["part1_steps", "part2_steps"])?;
+        let mut options = Options::default();
+        params.set("part1_steps", &mut options.part1_steps)?;
+        params.set("part2_steps", &mut options.part2_steps)?;
+        Ok(options)
+    }
+}
+
+/**
+The pixels of an image, and whether the pixels outside them, stretching off
+forever, are lit.
+ */
+#[derive(Clone)]
 struct Image {
-    pixels: HashSet<(i32,i32)>,
+    pixels: Grid<bool>,
     other_pixels: bool,
 }
 
-fn load_image(file: &str) -> (HashSet<u16>, Image) {
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
+impl fmt::Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows = self.pixels.render(|lit| if *lit { '#' } else { '.' });
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+fn pixel(c: char) -> Option<bool> {
+    match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_image(input: &str) -> Result<(Vec<bool>, Image), AocError> {
+    let mut sections = input.splitn(3, '\n');
 
     // read image algorithm
-    let mut str_buf = String::new();
-    match buf_reader.read_line(&mut str_buf) {
-        Err(e) => panic!("could not read line: {}", e),
-        _ => (),
-    };
-    let mut n: u16 = 0;
-    let mut alg_set: HashSet<u16> = HashSet::new();
-    for c in str_buf.chars() {
-        if c == '#' {
-            alg_set.insert(n);
-        }
-        n += 1;
+    let alg = Grid::parse(sections.next().unwrap_or("").trim_end(), pixel, "'#' or '.'")?;
+    if alg.cols() != 512 {
+        return Err(AocError::parse(1, 1,
+            format!("expected 512 characters in the algorithm, found {}", alg.cols())));
     }
 
     // read blank line
-    match buf_reader.read_line(&mut str_buf) {
-        Err(e) => panic!("could not read line: {}", e),
-        _ => (),
-    };
+    if !sections.next().unwrap_or("").trim().is_empty() {
+        return Err(AocError::parse(2, 1, "expected a blank line after the algorithm"));
+    }
 
     // read image
-    let mut row: i32 = 0;
-    let mut image: HashSet<(i32,i32)> = HashSet::new();
-    for line_wrapped in buf_reader.lines() {
-        for t in line_wrapped.unwrap().chars().enumerate() {
-            if t.1 == '#' {
-                image.insert((row, t.0 as i32));
-            }
-        }
-        row += 1;
-    }
+    let pixels = Grid::parse(sections.next().unwrap_or(""), pixel, "'#' or '.'")
+        .map_err(|e| e.on_line(3))?;
 
-    (alg_set, Image{pixels: image, other_pixels: false})
+    Ok((alg.values().copied().collect(), Image { pixels, other_pixels: false }))
 }
 
-fn min_max(image: &HashSet<(i32,i32)>) -> (i32,i32,i32,i32) {
-    let mut min_row = 0;
-    let mut max_row = 0;
-    let mut min_col = 0;
-    let mut max_col = 0;
-    let mut started = false;
-    for pix in image {
-        if !started {
-            started = true;
-            min_row = pix.0;
-            max_row = pix.0;
-            min_col = pix.1;
-            max_col = pix.1;
-        } else {
-            if pix.0 < min_row {
-                min_row = pix.0;
-            }
-            if pix.0 > max_row {
-                max_row = pix.0;
-            }
-            if pix.1 < min_col {
-                min_col = pix.1;
-            }
-            if pix.1 > max_col {
-                max_col = pix.1;
-            }
-        }
+/**
+Enhance the image once. The image grows by a pixel on every side, since the
+pixels just outside it may change.
+ */
+fn enhance(image: &Image, alg: &[bool]) -> Image {
+    let lit = |row: i64, col: i64| match image.pixels.position(row, col) {
+        Some(coord) => image.pixels[coord],
+        None => image.other_pixels,
+    };
+    let pixels = Grid::from_fn(image.pixels.rows() + 2, image.pixels.cols() + 2, |row, col| {
+        // the pixel at (row, col) was at (row - 1, col - 1) in the old image
+        let (row, col) = (row as i64 - 1, col as i64 - 1);
+        let ndx = neighbors::around((row, col), Neighborhood::Block)
+            .fold(0, |ndx, (y, x)| 2 * ndx + lit(y, x) as usize);
+        alg[ndx]
+    });
+    Image {
+        pixels,
+        other_pixels: alg[if image.other_pixels { 511 } else { 0 }],
     }
-    (min_row,max_row,min_col,max_col)    
 }
 
-fn enhance(image: &mut Image, alg: &HashSet<u16>) -> Image {
-    let mut new_image: HashSet<(i32,i32)> = HashSet::new();
-    let (mut min_row, mut max_row, mut min_col, mut max_col) = min_max(&image.pixels);
-    if image.other_pixels {
-        for col in min_col-1 .. max_col+2 {
-            image.pixels.insert((min_row-1, col));
-            image.pixels.insert((max_row+1, col));
-        }
-        for row in min_row .. max_row+2 {
-            image.pixels.insert((row, min_col-1));
-            image.pixels.insert((row, max_col+1));
-        }
-        min_row -= 1;
-        min_col -= 1;
-        max_row += 1;
-        max_col += 1;
-    }
-    for row in min_row-1 .. max_row+2 {
-        for col in min_col-1 .. max_col+2 {
-            let mut ndx: u16 = 0;
-            for y in row-1 .. row+2 {
-                for x in col-1 .. col+2 {
-                    ndx = 2*ndx + if image.pixels.contains(&(y,x)) {
-                        1
-                    } else if image.other_pixels && 
-                              (y < min_row || y > max_row || x < min_col || x > max_col) {
-                        1
-                    } else {
-                        0
-                    };
-                }
-            }
-            if alg.contains(&ndx) {
-                new_image.insert((row,col));
-            }
-        }
+/**
+Enhance the image the specified number of times and count the lit pixels,
+tracing the image after each enhancement.
+ */
+fn count_lit(alg: &[bool], image: &Image, enhance_count: usize, tracer: &mut dyn Tracer) -> Result<usize, AocError> {
+    let mut image = image.clone();
+    for step in 1..=enhance_count {
+        image = enhance(&image, alg);
+        let action = if image.other_pixels { "enhance, every pixel outside lit" } else { "enhance" };
+        tracer.step(step, action, &image);
     }
-    Image { 
-        pixels: new_image,
-        other_pixels: alg.contains(if image.other_pixels {
-            &511
-        } else {
-            &0
-        }),
+    if image.other_pixels {
+        return Err(AocError::Unsolvable(String::from("infinitely many pixels are lit")));
     }
+    Ok(image.pixels.values().filter(|lit| **lit).count())
 }
 
 /**
@@ -131,17 +124,82 @@ Run Day 20's exercise.
 
 # Examples
 ```
-assert_eq!(35, aoc2021::day20::run("test_inputs/day20.txt", 2));
-assert_eq!(3351, aoc2021::day20::run("test_inputs/day20.txt", 2));
+assert_eq!(35, aoc2021::day20::run("test_inputs/day20.txt", 2).unwrap());
+assert_eq!(3351, aoc2021::day20::run("test_inputs/day20.txt", 50).unwrap());
 ```
  */
-pub fn run(file: &str, enhance_count: usize) -> usize {
-    let (alg, mut image) = load_image(file);
-    for _ in 0..enhance_count {
-        image = enhance(&mut image, &alg);
+pub fn run(file: &str, enhance_count: usize) -> Result<usize, AocError> {
+    input::from_file(file, |input| solve(input, enhance_count))
+}
+
+/**
+Solve Day 20's exercise from the text of the input.
+
+# Examples
+```
+// only a pixel lit on its own stays lit
+let mut alg = vec!['.'; 512];
+alg[16] = '#';
+let input = format!("{}\n\n#..\n...\n..#\n", alg.iter().collect::<String>());
+assert_eq!(2, aoc2021::day20::solve(&input, 2).unwrap());
+let e = aoc2021::day20::solve("#.#\n\n#\n", 2).unwrap_err();
+assert_eq!("line 1, column 1: expected 512 characters in the algorithm, found 3", e.to_string());
+let e = aoc2021::day20::solve(&format!("{}\n\n#.\n.x\n", alg.iter().collect::<String>()), 2).unwrap_err();
+assert_eq!("line 4, column 2: expected '#' or '.', found 'x'", e.to_string());
+```
+ */
+pub fn solve(input: &str, enhance_count: usize) -> Result<usize, AocError> {
+    let (alg, image) = parse_image(input)?;
+    count_lit(&alg, &image, enhance_count, &mut NoTrace)
+}
+
+/**
+Solve Day 20's exercise like [`solve`], tracing the image after each
+enhancement.
+
+# Examples
+```
+let mut alg = vec!['.'; 512];
+alg[16] = '#';
+let input = format!("{}\n\n#..\n...\n..#\n", alg.iter().collect::<String>());
+let mut images = Vec::new();
+let mut tracer = |_: usize, _: &str, image: &dyn std::fmt::Display| images.push(image.to_string());
+assert_eq!(2, aoc2021::day20::solve_traced(&input, 1, &mut tracer).unwrap());
+assert_eq!(vec![".....\n.#...\n.....\n...#.\n....."], images);
+```
+ */
+pub fn solve_traced(input: &str, enhance_count: usize, tracer: &mut dyn Tracer) -> Result<usize, AocError> {
+    let (alg, image) = parse_image(input)?;
+    count_lit(&alg, &image, enhance_count, tracer)
+}
+
+pub struct Day20 {
+    alg: Vec<bool>,
+    image: Image,
+    options: Options,
+}
+
+impl Solution for Day20 {
+    fn parse(input: &str) -> Result<Day20, AocError> {
+        let (alg, image) = parse_image(input)?;
+        Ok(Day20 { alg, image, options: Options::default() })
     }
-    if image.other_pixels {
-        panic!("Infinite pixels");
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(count_lit(&self.alg, &self.image, self.options.part1_steps, &mut NoTrace)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(count_lit(&self.alg, &self.image, self.options.part2_steps, &mut NoTrace)?.into())
+    }
+
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        let steps = if part == 1 { self.options.part1_steps } else { self.options.part2_steps };
+        Ok(count_lit(&self.alg, &self.image, steps, tracer)?.into())
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        self.options = Options::from_params(params)?;
+        Ok(())
     }
-    image.pixels.len()
 }
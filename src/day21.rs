@@ -1,126 +1,211 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
 use std::collections::HashMap;
-use std::sync::mpsc;
-use std::thread;
-use itertools::Itertools;
-
-fn load_start_pos(file: &str) -> (i32,i32) {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    let pos: Vec<i32> = buf_reader.lines()
-        .map(|s| s.unwrap().split(' ').nth_back(0).unwrap().parse::<i32>().unwrap())
-        .collect();
-    (pos[0],pos[1])
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::params::{self, Params};
+use crate::solution::{Answer, Solution};
+
+/**
+The board and the scores to reach, which can be changed with the `board`,
+`practice_target` and `dirac_target` parameters.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub board: i32,
+    pub practice_target: i32,
+    pub dirac_target: i32,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { board: 10, practice_target: 1000, dirac_target: 21 }
+    }
+}
+
+impl Options {
+    /**
+    The puzzle's options, overridden by any parameters.
+
+    # Examples
+    ```
+    let mut params = aoc2021::params::Params::new();
+    params.add("dirac_target=15").unwrap();
+    let options = aoc2021::day21::Options::from_params(&params).unwrap();
+    assert_eq!((10, 1000, 15), (options.board, options.practice_target, options.dirac_target));
+    ```
+     */
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["board", "practice_target", "dirac_target"])?;
+        let mut options = Options::default();
+        params.set("board", &mut options.board)?;
+        params.set("practice_target", &mut options.practice_target)?;
+        params.set("dirac_target", &mut options.dirac_target)?;
+        params::at_least("board", options.board, 1)?;
+        params::at_least("practice_target", options.practice_target, 1)?;
+        params::at_least("dirac_target", options.dirac_target, 1)?;
+        Ok(options)
+    }
+
+    /**
+    Check that both starting positions are on the board.
+
+    # Examples
+    ```
+    let options = aoc2021::day21::Options { board: 6, ..Default::default() };
+    assert!(options.check_start(4, 6).is_ok());
+    let e = options.check_start(4, 8).unwrap_err();
+    assert_eq!("starting position 8 is off a board of 6", e.to_string());
+    ```
+     */
+    pub fn check_start(&self, a_start: i32, b_start: i32) -> Result<(), AocError> {
+        for start in [a_start, b_start] {
+            if !(1..=self.board).contains(&start) {
+                return Err(AocError::Usage(format!("starting position {} is off a board of {}", start, self.board)));
+            }
+        }
+        Ok(())
+    }
 }
 
 /**
-Run part 1 of Day 21's exercise.
+Parse the starting positions of the two players.
 
 # Examples
 ```
-assert_eq!(739785, aoc2021::day21::run_part1("test_inputs/day21.txt"));
+let input = "Player 1 starting position: 4\nPlayer 2 starting position: 8\n";
+assert_eq!((4, 8), aoc2021::day21::parse_start_pos(input).unwrap());
+let e = aoc2021::day21::parse_start_pos("Player 1 starting position: 11\n").unwrap_err();
+assert_eq!("line 1, column 29: starting position 11 is not on the board", e.to_string());
 ```
  */
-pub fn run_part1(file: &str) -> i32 {
-    let (mut a_pos, mut b_pos) = load_start_pos(file);
-    let mut rolls = 0;
-    let mut a_score = 0;
-    let mut b_score = 0;
-    loop {
-        let spaces = (9*rolls + 6) % 10;
-        if rolls % 2 == 0 {
-            a_pos = (a_pos + spaces) % 10;
-            if a_pos == 0 {
-                a_pos = 10;
-            }
-            a_score += a_pos;
-            if a_score >= 1000 {
-                return b_score * 3 * (rolls+1);
-            }
-        } else {
-            b_pos = (b_pos + spaces) % 10;
-            if b_pos == 0 {
-                b_pos = 10;
-            }
-            b_score += b_pos;
-            if b_score >= 1000 {
-                return a_score * 3 * (rolls+1);
-            }
+pub fn parse_start_pos(input: &str) -> Result<(i32,i32), AocError> {
+    let mut pos: Vec<i32> = Vec::new();
+    for (ndx, line) in input.lines().enumerate() {
+        let token = line.split(' ').nth_back(0).unwrap_or("");
+        let p: i32 = parse_token(line, token).map_err(|e| e.on_line(ndx + 1))?;
+        if !(1..=10).contains(&p) {
+            return Err(AocError::parse(ndx + 1, column_of(line, token),
+                format!("starting position {} is not on the board", p)));
         }
-        rolls += 1;
+        pos.push(p);
+    }
+    if pos.len() != 2 {
+        return Err(AocError::parse(pos.len().max(1), 1,
+            format!("expected 2 players, found {}", pos.len())));
     }
+    Ok((pos[0],pos[1]))
 }
 
-fn next_roll(rolls: &mut Vec<u8>, is_winner: bool) {
-    if is_winner {
-        *rolls.iter_mut().nth_back(0).unwrap() += 1;
-    } else {
-        rolls.push(3);
-    }
-    while rolls.len() > 0 && rolls.iter().nth_back(0).unwrap() >= &10 {
-        rolls.remove(rolls.len()-1);
-        if rolls.len() > 0 {
-            *rolls.iter_mut().nth_back(0).unwrap() += 1;
-        }
-    }
+/**
+Run part 1 of Day 21's exercise.
 
+# Examples
+```
+assert_eq!(739785, aoc2021::day21::run_part1("test_inputs/day21.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<i32, AocError> {
+    input::from_file(file, solve_part1)
 }
 
-fn part2_thread(rolls: &Vec<u8>, totals: &HashMap<u8, u64>, a_start: i32, b_start: i32 ) -> (u64,u64) {
-    let rolls_len_init = rolls.len();
-    // combinations of 3-rolls that produce a winner
-    let mut rolls = rolls.clone();
-    rolls.push(3);
-    let mut winners_0: Vec<Vec<u8>> = Vec::new();
-    let mut winners_1: Vec<Vec<u8>> = Vec::new();
-    while rolls.len() > rolls_len_init {
-        let mut a_score = 0;
-        let mut b_score = 0;
-        let mut a_pos = a_start;
-        let mut b_pos = b_start;
-        for i in 0..rolls.len() {
-            if i%2 == 0 {
-                a_pos = (a_pos+ rolls[i] as i32)%10;
-                a_score += if a_pos == 0 {
-                    10
-                } else {
-                    a_pos
-                };
-            } else {
-                b_pos = (b_pos+ rolls[i] as i32)%10;
-                b_score += if b_pos == 0 {
-                    10
-                } else {
-                    b_pos
-                };
-            }
+/**
+Solve part 1 of Day 21's exercise from the text of the input.
+
+# Examples
+```
+let input = "Player 1 starting position: 4\nPlayer 2 starting position: 8\n";
+assert_eq!(739785, aoc2021::day21::solve_part1(input).unwrap());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<i32, AocError> {
+    let (a_start, b_start) = parse_start_pos(input)?;
+    play_practice(a_start, b_start, &Options::default())
+}
+
+/**
+Play with the deterministic die until a player reaches the practice target,
+then multiply the losing score by the number of rolls. Both players must
+start on the board, and the answer must fit in an `i32`.
+
+# Examples
+```
+use aoc2021::day21::{play_practice, Options};
+
+assert_eq!(739785, play_practice(4, 8, &Options::default()).unwrap());
+// player 1 moves 6 spaces to 10 and wins at once
+let options = Options { practice_target: 10, ..Options::default() };
+assert_eq!(0, play_practice(4, 8, &options).unwrap());
+let options = Options { practice_target: 1_000_000, ..Options::default() };
+let e = play_practice(4, 8, &options).unwrap_err();
+assert_eq!("no solution: the practice game's answer overflowed an i32", e.to_string());
+let e = play_practice(4, 11, &Options::default()).unwrap_err();
+assert_eq!("starting position 11 is off a board of 10", e.to_string());
+```
+ */
+pub fn play_practice(a_start: i32, b_start: i32, options: &Options) -> Result<i32, AocError> {
+    options.check_start(a_start, b_start)?;
+    let overflow = || AocError::Unsolvable(String::from("the practice game's answer overflowed an i32"));
+    let mut pos = [a_start, b_start];
+    let mut score: [i32; 2] = [0, 0];
+    let mut rolls: i32 = 0;
+    let mut player = 0;
+    loop {
+        let spaces: i32 = (0..3).map(|roll| (rolls % 100 + roll) % 100 + 1).sum();
+        rolls = rolls.checked_add(3).ok_or_else(overflow)?;
+        pos[player] = (pos[player] - 1 + spaces) % options.board + 1;
+        score[player] = score[player].checked_add(pos[player]).ok_or_else(overflow)?;
+        if score[player] >= options.practice_target {
+            return score[1 - player].checked_mul(rolls).ok_or_else(overflow);
         }
-        // winner?
-        if 21 <= if rolls.len()%2 == 1 {
-            a_score
-        } else {
-            b_score
-        } {
-            if rolls.len()%2 == 1 {
-                winners_1.push(Vec::from(&rolls[..]));
-            } else {
-                winners_0.push(Vec::from(&rolls[..]));
-            }
-            next_roll(&mut rolls, true);
+        player = 1 - player;
+    }
+}
+
+/**
+The totals of three rolls of the Dirac die, and the number of universes
+rolling each.
+ */
+const DIRAC_ROLLS: [(i32, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+/**
+A player's position and score.
+ */
+type Player = (i32, i32);
+
+/**
+The universes won by the player about to move, and by the other player,
+given the two players, the one about to move first. Counts too large for a
+u64 are unsolvable.
+ */
+// The players are passed as one pair: rustc 1.95 in release builds, given
+// them as two arguments, took the mover's score for always 0 and recursed
+// without end.
+fn dirac_wins(
+    players: (Player, Player),
+    options: &Options,
+    cache: &mut HashMap<(Player, Player), (u64, u64)>,
+) -> Result<(u64, u64), AocError> {
+    if let Some(wins) = cache.get(&players) {
+        return Ok(*wins);
+    }
+    let (current, other) = players;
+    let overflow = || AocError::Unsolvable(String::from("the number of universes won overflowed a u64"));
+    let add = |total: u64, universes: u64, wins: u64| universes.checked_mul(wins)
+        .and_then(|won| total.checked_add(won))
+        .ok_or_else(overflow);
+    let mut wins = (0, 0);
+    for (spaces, universes) in DIRAC_ROLLS {
+        let pos = (current.0 - 1 + spaces) % options.board + 1;
+        let score = current.1.saturating_add(pos);
+        if score >= options.dirac_target {
+            wins.0 = add(wins.0, universes, 1)?;
         } else {
-            next_roll(&mut rolls, false);
+            let (other_wins, current_wins) = dirac_wins((other, (pos, score)), options, cache)?;
+            wins.0 = add(wins.0, universes, current_wins)?;
+            wins.1 = add(wins.1, universes, other_wins)?;
         }
     }
-    let winners_0: u64 = winners_0.iter()
-        .map(|v| v.iter().map(|tot| totals.get(tot).unwrap()).product::<u64>())
-        .sum();
-    let winners_1: u64 = winners_1.iter()
-        .map(|v| v.iter().map(|tot| totals.get(tot).unwrap()).product::<u64>())
-        .sum();
-    
-    (winners_0,winners_1)
+    cache.insert(players, wins);
+    Ok(wins)
 }
 
 /**
@@ -128,52 +213,87 @@ Run part 2 of Day 21's exercise.
 
 # Examples
 ```
-assert_eq!(444356092776315, aoc2021::day21::run_part2("test_inputs/day21.txt"));
+assert_eq!(444356092776315, aoc2021::day21::run_part2("test_inputs/day21.txt").unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> u64 {
-    let (a_start, b_start) = load_start_pos(file);
-    // find all totals from 3 rolls of the 3-sided die
-    let mut totals: Vec<u8> = Vec::new();
-    for a in 1..4 {
-        for b in 1..4 {
-            for c in 1..4 {
-                totals.push((a+b+c) as u8);
-            }
-        }
+pub fn run_part2(file: &str) -> Result<u64, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of Day 21's exercise from the text of the input.
+
+# Examples
+```
+let input = "Player 1 starting position: 4\nPlayer 2 starting position: 8\n";
+assert_eq!(444356092776315, aoc2021::day21::solve_part2(input).unwrap());
+```
+ */
+pub fn solve_part2(input: &str) -> Result<u64, AocError> {
+    let (a_start, b_start) = parse_start_pos(input)?;
+    play_dirac(a_start, b_start, &Options::default())
+}
+
+/**
+Play with the Dirac die and count the universes won by the player who
+wins the most. Both players must start on the board.
+
+# Examples
+```
+use aoc2021::day21::{play_dirac, Options};
+
+assert_eq!(444356092776315, play_dirac(4, 8, &Options::default()).unwrap());
+// player 1 wins with their first move in every universe
+let options = Options { dirac_target: 1, ..Options::default() };
+assert_eq!(27, play_dirac(4, 8, &options).unwrap());
+let options = Options { board: 6, ..Options::default() };
+let e = play_dirac(4, 8, &options).unwrap_err();
+assert_eq!("starting position 8 is off a board of 6", e.to_string());
+let options = Options { dirac_target: 50, ..Options::default() };
+let e = play_dirac(4, 8, &options).unwrap_err();
+assert_eq!("no solution: the number of universes won overflowed a u64", e.to_string());
+let options = Options { dirac_target: 100_000, ..Options::default() };
+let e = play_dirac(4, 8, &options).unwrap_err();
+assert_eq!("no solution: the number of universes won overflowed a u64", e.to_string());
+```
+ */
+pub fn play_dirac(a_start: i32, b_start: i32, options: &Options) -> Result<u64, AocError> {
+    options.check_start(a_start, b_start)?;
+    // The winner makes at least this many moves, and the loser one fewer,
+    // each splitting the universes 27 ways: from 8 on, even the fewest
+    // universes are too many to count, and too deep to recurse through.
+    let moves = (options.dirac_target - 1) / options.board + 1;
+    if moves >= 8 {
+        return Err(AocError::Unsolvable(String::from("the number of universes won overflowed a u64")));
     }
-    totals.sort();
-    let totals: HashMap<u8, u64> = totals.iter()
-        .group_by(|t| *t)
-        .into_iter()
-        .map(|t| (*t.0, t.1.collect::<Vec<&u8>>().len() as u64))
-        .collect();
-    let mut winners_0: u64 = 0;
-    let mut winners_1: u64 = 0;
-    let (tx, rx) = mpsc::channel();
-    for a in 3..10 {
-        for b in 3..10 {
-            for c in 3..10 {
-                for d in 3..10 {
-                    let rolls: Vec<u8> = vec![a,b,c,d];
-                    let totals_clone = totals.clone();
-                    let a_start_copy = a_start;
-                    let b_start_copy = b_start;
-                    let txcl = tx.clone();
-                    thread::spawn(move || {
-                        let (r0, r1) = part2_thread(&rolls, &totals_clone, a_start_copy, b_start_copy);
-                        txcl.send((r0,r1)).unwrap();
-                    });                
-                }
-            }
-        }
+    let (a_wins, b_wins) = dirac_wins(((a_start, 0), (b_start, 0)), options, &mut HashMap::new())?;
+    Ok(a_wins.max(b_wins))
+}
+
+pub struct Day21 {
+    a_start: i32,
+    b_start: i32,
+    options: Options,
+}
+
+impl Solution for Day21 {
+    fn parse(input: &str) -> Result<Day21, AocError> {
+        let (a_start, b_start) = parse_start_pos(input)?;
+        Ok(Day21 { a_start, b_start, options: Options::default() })
     }
-    drop(tx);
-    
-    for (r0, r1) in rx {
-        winners_0 += r0;
-        winners_1 += r1;
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(play_practice(self.a_start, self.b_start, &self.options)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(play_dirac(self.a_start, self.b_start, &self.options)?.into())
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        let options = Options::from_params(params)?;
+        options.check_start(self.a_start, self.b_start)?;
+        self.options = options;
+        Ok(())
     }
-    
-    winners_0.max(winners_1)
 }
@@ -1,54 +1,87 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-use std::collections::HashSet;
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::params::{self, Params};
+use crate::solution::{Answer, Solution};
+
+/**
+The half-width of the initialization region in part 1, which can be changed
+with the `region` parameter.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub region: i32,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { region: 50 }
+    }
+}
+
+impl Options {
+    pub fn from_params(params: &Params) -> Result<Options, AocError> {
+        params.check_known(&["region"])?;
+        let mut options = Options::default();
+        params.set("region", &mut options.region)?;
+        params::at_least("region", options.region, 0)?;
+        Ok(options)
+    }
+}
 
 #[derive(Clone, Copy, Debug)]
-struct Instruction {
-    on: bool,
-    x_range: (i32,i32),
-    y_range: (i32,i32),
-    z_range: (i32,i32),
+pub(crate) struct Instruction {
+    pub(crate) on: bool,
+    pub(crate) x_range: (i32,i32),
+    pub(crate) y_range: (i32,i32),
+    pub(crate) z_range: (i32,i32),
 }
 
 impl Instruction {
     fn volume(&self) -> i128 {
-        (1 + self.x_range.1 - self.x_range.0) as i128 *
-        (1 + self.y_range.1 - self.y_range.0) as i128 *
-        (1 + self.z_range.1 - self.z_range.0) as i128
+        (1 + self.x_range.1 as i128 - self.x_range.0 as i128) *
+        (1 + self.y_range.1 as i128 - self.y_range.0 as i128) *
+        (1 + self.z_range.1 as i128 - self.z_range.0 as i128)
     }
     fn new(on: bool, x_range: &(i32,i32), y_range: &(i32,i32), z_range: &(i32,i32)) -> Instruction {
-        if x_range.0 > x_range.1 {
-            panic!("illegal x_range {:?}", x_range);
-        }
-        if y_range.0 > y_range.1 {
-            panic!("illegal x_range {:?}", y_range);
+        Instruction {on, x_range: *x_range, y_range: *y_range, z_range: *z_range}
+    }
+
+    /**
+    Parse an instruction such as `on x=10..12,y=10..12,z=10..12`.
+     */
+    fn parse(line: &str) -> Result<Instruction, AocError> {
+        let (state, cuboid) = line.split_once(' ')
+            .ok_or_else(|| AocError::parse(1, 1, "expected \"on\" or \"off\" and a cuboid"))?;
+        let on = match state {
+            "on" => true,
+            "off" => false,
+            _ => return Err(AocError::parse(1, 1, format!("expected \"on\" or \"off\", found \"{}\"", state))),
+        };
+        let axes: Vec<&str> = cuboid.split(',').collect();
+        if axes.len() != 3 {
+            return Err(AocError::parse(1, column_of(line, cuboid),
+                format!("expected 3 ranges, found {}", axes.len())));
         }
-        if z_range.0 > z_range.1 {
-            panic!("illegal x_range {:?}", z_range);
+        let mut ranges = [(0, 0); 3];
+        for (ndx, (axis, name)) in axes.iter().zip(["x=", "y=", "z="]).enumerate() {
+            let range_str = axis.strip_prefix(name)
+                .ok_or_else(|| AocError::parse(1, column_of(line, axis), format!("expected \"{}\"", name)))?;
+            let (low, high) = range_str.split_once("..")
+                .ok_or_else(|| AocError::parse(1, column_of(line, range_str), "expected \"low..high\""))?;
+            ranges[ndx] = (parse_token(line, low)?, parse_token(line, high)?);
+            if ranges[ndx].0 > ranges[ndx].1 {
+                return Err(AocError::parse(1, column_of(line, axis), format!("illegal range {}", axis)));
+            }
         }
-        Instruction {on, x_range: *x_range, y_range: *y_range, z_range: *z_range}
+        Ok(Instruction::new(on, &ranges[0], &ranges[1], &ranges[2]))
     }
 }
 
-fn load_instructions(file: &str) -> Vec<Instruction> {
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    buf_reader.lines()
-        .map(|line_wrap| {
-            let line = line_wrap.unwrap();
-            let split_line: Vec<&str> = line.split(" ").collect();
-            let on = split_line[0] == "on";
-            let ranges: Vec<(i32,i32)> = line.split(",")
-                .map(|s| {
-                    let range_str = s.split("=").nth(1).unwrap();
-                    let range_entries: Vec<i32> = range_str.split("..")
-                        .map(|s| s.parse::<i32>().unwrap())
-                        .collect();
-                    (range_entries[0],range_entries[1])
-                }).collect();
-            Instruction::new(on, &ranges[0],&ranges[1],&ranges[2])
-        }).collect()
+pub(crate) fn parse_instructions(input: &str) -> Result<Vec<Instruction>, AocError> {
+    input.lines()
+        .enumerate()
+        .map(|(ndx, line)| Instruction::parse(line).map_err(|e| e.on_line(ndx + 1)))
+        .collect()
 }
 
 fn has_overlap(a: &Instruction, b: &Instruction) -> bool {
@@ -62,30 +95,67 @@ Run part 1 of day 22's exercise.
 
 # Examples
 ```
-assert_eq!(39, aoc2021::day22::run_part1("test_inputs/day22_0.txt"));
-assert_eq!(590784, aoc2021::day22::run_part1("test_inputs/day22_1.txt"));
+assert_eq!(39, aoc2021::day22::run_part1("test_inputs/day22_0.txt").unwrap());
+assert_eq!(590784, aoc2021::day22::run_part1("test_inputs/day22_1.txt").unwrap());
 ```
  */
-pub fn run_part1(file: &str) -> usize {
-    let mut instructions: Vec<Instruction> = load_instructions(file);
-    instructions.retain(|i| i.x_range.0 >= -50 && i.x_range.1 <= 50 && i.y_range.0 >= -50 && i.y_range.1 <= 50 &&i.z_range.0 >= -50 && i.z_range.1 <= 50);
-    let mut grid: HashSet<(i32,i32,i32)> = HashSet::new();
-    for instruction in instructions {
-        for x in instruction.x_range.0 .. instruction.x_range.1 + 1 {
-            for y in instruction.y_range.0 .. instruction.y_range.1 + 1 {
-                for z in instruction.z_range.0 .. instruction.z_range.1 + 1 {
-                    let coord = (x,y,z);
-                    if instruction.on {
-                        grid.insert(coord);
-                    } else {
-                        grid.remove(&coord);
-                    }
-                }
-            }
-        }
+pub fn run_part1(file: &str) -> Result<i128, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of day 22's exercise from the text of the input.
+
+# Examples
+```
+let input = "on x=10..12,y=10..12,z=10..12\noff x=11..11,y=11..11,z=11..11\non x=51..52,y=0..0,z=0..0\n";
+assert_eq!(26, aoc2021::day22::solve_part1(input).unwrap());
+let e = aoc2021::day22::solve_part1("on x=10..12,y=12..10,z=10..12\n").unwrap_err();
+assert_eq!("line 1, column 13: illegal range y=12..10", e.to_string());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<i128, AocError> {
+    Ok(initialization_cubes(&parse_instructions(input)?, &Options::default()))
+}
+
+/**
+Solve one part of day 22's exercise with options other than the puzzle's.
+
+# Examples
+```
+use aoc2021::day22::{solve_with, Options};
+use aoc2021::solution::Answer;
 
+let input = "on x=10..12,y=10..12,z=10..12\noff x=11..11,y=11..11,z=11..11\non x=51..52,y=0..0,z=0..0\n";
+assert_eq!(Answer::Number(28), solve_with(1, input, &Options { region: 60 }).unwrap());
+assert_eq!(Answer::Number(0), solve_with(1, input, &Options { region: 5 }).unwrap());
+// the first cuboid is cut down to x=10..11, and the second still turns one cube off
+assert_eq!(Answer::Number(7), solve_with(1, input, &Options { region: 11 }).unwrap());
+let far = "on x=-2000000000..2000000000,y=-2000000000..2000000000,z=-2000000000..2000000000\n";
+assert_eq!(Answer::Number(101 * 101 * 101), solve_with(1, far, &Options::default()).unwrap());
+assert_eq!(Answer::Number(4_000_000_001i128.pow(3)), solve_with(2, far, &Options::default()).unwrap());
+```
+ */
+pub fn solve_with(part: u8, input: &str, options: &Options) -> Result<Answer, AocError> {
+    let instructions = parse_instructions(input)?;
+    match part {
+        1 => Ok(initialization_cubes(&instructions, options).into()),
+        2 => Ok(reboot_cubes(&instructions).into()),
+        _ => Err(AocError::UnknownPart(part.to_string())),
     }
-    grid.len()
+}
+
+/**
+Count the lit cubes in the initialization region, clipping each cuboid to
+the region and counting what is left like [`reboot_cubes`].
+ */
+fn initialization_cubes(instructions: &[Instruction], options: &Options) -> i128 {
+    let clip = |range: (i32, i32)| (range.0.max(-options.region), range.1.min(options.region));
+    let clipped: Vec<Instruction> = instructions.iter()
+        .map(|i| Instruction::new(i.on, &clip(i.x_range), &clip(i.y_range), &clip(i.z_range)))
+        .filter(|i| i.x_range.0 <= i.x_range.1 && i.y_range.0 <= i.y_range.1 && i.z_range.0 <= i.z_range.1)
+        .collect();
+    reboot_cubes(&clipped)
 }
 
 /**
@@ -93,18 +163,38 @@ Run part 2 of the exercise.
 
 # Examples
 ```
-assert_eq!(39, aoc2021::day22::run_part2("test_inputs/day22_0.txt"));
-assert_eq!(590784, aoc2021::day22::run_part2("test_inputs/day22_1a.txt"));
-assert_eq!(2758514936282235, aoc2021::day22::run_part2("test_inputs/day22_2.txt"));
+assert_eq!(39, aoc2021::day22::run_part2("test_inputs/day22_0.txt").unwrap());
+assert_eq!(590784, aoc2021::day22::run_part2("test_inputs/day22_1a.txt").unwrap());
+assert_eq!(2758514936282235, aoc2021::day22::run_part2("test_inputs/day22_2.txt").unwrap());
+```
+ */
+pub fn run_part2(file: &str) -> Result<i128, AocError> {
+    input::from_file(file, solve_part2)
+}
+
+/**
+Solve part 2 of the exercise from the text of the input.
+
+# Examples
+```
+let input = "on x=10..12,y=10..12,z=10..12\noff x=11..11,y=11..11,z=11..11\non x=51..52,y=0..0,z=0..0\n";
+assert_eq!(28, aoc2021::day22::solve_part2(input).unwrap());
 ```
  */
-pub fn run_part2(file: &str) -> i128 {
-    let instructions: Vec<Instruction> = load_instructions(file);
+pub fn solve_part2(input: &str) -> Result<i128, AocError> {
+    Ok(reboot_cubes(&parse_instructions(input)?))
+}
+
+/**
+Count the lit cubes after every instruction, by adding and subtracting the
+volumes of overlapping cuboids.
+ */
+fn reboot_cubes(instructions: &[Instruction]) -> i128 {
     // Use these instructions to sum the total number of lit cubes.
     // The bool value indicates whether the volumne must be added (true)
     // or subtracted (false).
     let mut addends: Vec<(Instruction, bool)> = Vec::new();
-    for instr in &instructions {
+    for instr in instructions {
         let mut new_addends: Vec<(Instruction, bool)> = Vec::new();
         if instr.on {
             new_addends.push((instr.clone(), true));
@@ -133,3 +223,27 @@ pub fn run_part2(file: &str) -> i128 {
    
     volume
 }
+
+pub struct Day22 {
+    instructions: Vec<Instruction>,
+    options: Options,
+}
+
+impl Solution for Day22 {
+    fn parse(input: &str) -> Result<Day22, AocError> {
+        Ok(Day22 { instructions: parse_instructions(input)?, options: Options::default() })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(initialization_cubes(&self.instructions, &self.options).into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(reboot_cubes(&self.instructions).into())
+    }
+
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        self.options = Options::from_params(params)?;
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-use std::collections::HashMap;
+use crate::error::AocError;
+use crate::input;
+use crate::search;
+use crate::solution::{Answer, Solution};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum Amphipod {
@@ -221,17 +221,42 @@ impl Burrow {
     }
 }
 
-fn load_burrow(file: &str, part: u8) -> Burrow {
-    if ![1,2].contains(&part) {
-        panic!("part was {}, must be 1 or 2", part);
-    }
-    let file = File::open(file).expect("could not open file");
-    let mut buf_reader = BufReader::new(file);
-    let mut buffer: String = String::new();
+/**
+Parse the two rows of amphipods in the rooms from the diagram.
+ */
+fn parse_rows(input: &str) -> Result<[[Amphipod; 4]; 2], AocError> {
     // first 2 rows irrelevant -- there are 11 spots in the hallway
-    buf_reader.read_line(&mut buffer).unwrap();
-    buf_reader.read_line(&mut buffer).unwrap();
+    let mut lines = input.lines().skip(2);
     // remaining rows: rooms
+    let mut rows = [[Amphipod::Amber; 4]; 2];
+    for (line, row) in rows.iter_mut().enumerate() {
+        let buffer = lines.next().unwrap_or("");
+        for (amph, room_loc) in row.iter_mut().zip(Burrow::room_locations()) {
+            *amph = match buffer.chars().nth(room_loc + 1) {
+                Some('A') => Amphipod::Amber,
+                Some('B') => Amphipod::Bronze,
+                Some('C') => Amphipod::Copper,
+                Some('D') => Amphipod::Desert,
+                c => {
+                    let found = c.map_or(String::from("end of line"), |c| format!("'{}'", c));
+                    return Err(AocError::parse(line + 3, room_loc + 2,
+                        format!("expected an amphipod, found {}", found)));
+                }
+            };
+        }
+    }
+    Ok(rows)
+}
+
+/**
+Build the starting burrow from the rows in the file. In part 1 the bottom
+of each room is already filled by its own amphipods; in part 2 the folded
+rows of the diagram are inserted between the rows from the file.
+ */
+fn build_burrow(rows: &[[Amphipod; 4]; 2], part: u8) -> Result<Burrow, AocError> {
+    if ![1,2].contains(&part) {
+        return Err(AocError::UnknownPart(part.to_string()));
+    }
     let mut room_a: [Option<Amphipod>; 4] = [Option::None; 4];
     let mut room_b: [Option<Amphipod>; 4] = [Option::None; 4];
     let mut room_c: [Option<Amphipod>; 4] = [Option::None; 4];
@@ -258,61 +283,27 @@ fn load_burrow(file: &str, part: u8) -> Burrow {
         room_d[2] = Option::Some(Amphipod::Copper);
         from_file = [0,3];
     }
-    for ndx in from_file {
-        buffer.clear();
-        buf_reader.read_line(&mut buffer).unwrap();
+    for (row, ndx) in rows.iter().zip(from_file) {
         let rooms = [&mut room_a, &mut room_b, &mut room_c, &mut room_d];
-        for room_loc in rooms.into_iter().zip(Burrow::room_locations()) {
-            room_loc.0[ndx] = match buffer.chars().nth(room_loc.1 + 1).unwrap() {
-                'A' => Option::Some(Amphipod::Amber),
-                'B' => Option::Some(Amphipod::Bronze),
-                'C' => Option::Some(Amphipod::Copper),
-                'D' => Option::Some(Amphipod::Desert),
-                _ => panic!("unexpected character"),
-            };
+        for (room, amph) in rooms.into_iter().zip(row) {
+            room[ndx] = Option::Some(*amph);
         }
     }
     let hallway = [Option::None; 11];
-    Burrow{ hallway, room_a, room_b, room_c, room_d, }
+    Ok(Burrow{ hallway, room_a, room_b, room_c, room_d, })
 }
 
-fn least_energy(source: &Burrow, dest: &Burrow) -> u32 {
-    let mut visited: HashMap<Burrow, u32> = HashMap::new();
-    let mut queue: Vec<(Burrow, u32)> = vec![(*source, 0)];
-    while !visited.contains_key(dest) {
-        queue.sort_by(|a, b| {
-            let h_a = a.1 + a.0.heuristic();
-            let h_b = b.1 + b.0.heuristic();
-            // put smaller values at the end 
-            h_b.cmp(&h_a)
-        });
-        let (burrow, energy) = queue.pop().unwrap();
-        visited.insert(burrow, energy);
-        for neighbor in burrow.next_states() {
-            if visited.contains_key(&neighbor.0) {
-                continue;
-            }
-            let neighbor_in_map = visited.get(&neighbor.0);
-            if neighbor_in_map.is_none() || *neighbor_in_map.unwrap() > energy + neighbor.1 {
-                visited.insert(neighbor.0, energy + neighbor.1);
-            }
-            queue.push((neighbor.0, energy + neighbor.1));
-        }
-    }
-    *visited.get(dest).unwrap()
+fn least_energy(source: &Burrow, dest: &Burrow) -> Result<u32, AocError> {
+    search::astar(*source, Burrow::next_states, Burrow::heuristic, |burrow| burrow == dest)
+        .map(|path| path.cost)
+        .ok_or_else(|| AocError::Unsolvable(String::from("the amphipods cannot be organized")))
 }
 
 /**
-Run Day 23's exercise.
-
-# Examples
-```
-assert_eq!(12521, aoc2021::day23::run(1, "test_inputs/day23.txt"));
-assert_eq!(44169, aoc2021::day23::run(2, "test_inputs/day23.txt"));
-```
+The least energy needed to organize the amphipods into their rooms.
  */
-pub fn run(part: u8, file: &str) -> u32 {
-    let burrow = load_burrow(file, part);
+fn organize(rows: &[[Amphipod; 4]; 2], part: u8) -> Result<u32, AocError> {
+    let burrow = build_burrow(rows, part)?;
     let dest = Burrow {
         hallway: [Option::None; 11],
         room_a: [Option::Some(Amphipod::Amber); 4],
@@ -322,3 +313,49 @@ pub fn run(part: u8, file: &str) -> u32 {
     };
     least_energy(&burrow, &dest)
 }
+
+/**
+Run Day 23's exercise.
+
+# Examples
+```
+assert_eq!(12521, aoc2021::day23::run(1, "test_inputs/day23.txt").unwrap());
+assert_eq!(44169, aoc2021::day23::run(2, "test_inputs/day23.txt").unwrap());
+```
+ */
+pub fn run(part: u8, file: &str) -> Result<u32, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve Day 23's exercise from the text of the input.
+
+# Examples
+```
+let input = "#############\n#...........#\n###A#B#C#D###\n  #A#B#C#D#\n  #########\n";
+assert_eq!(0, aoc2021::day23::solve(1, input).unwrap());
+let e = aoc2021::day23::solve(1, "#############\n#...........#\n###A#B#C#D###\n").unwrap_err();
+assert_eq!("line 4, column 4: expected an amphipod, found end of line", e.to_string());
+```
+ */
+pub fn solve(part: u8, input: &str) -> Result<u32, AocError> {
+    organize(&parse_rows(input)?, part)
+}
+
+pub struct Day23 {
+    rows: [[Amphipod; 4]; 2],
+}
+
+impl Solution for Day23 {
+    fn parse(input: &str) -> Result<Day23, AocError> {
+        Ok(Day23 { rows: parse_rows(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(organize(&self.rows, 1)?.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(organize(&self.rows, 2)?.into())
+    }
+}
@@ -0,0 +1,408 @@
+use std::str::FromStr;
+use crate::error::{column_of, parse_token, AocError};
+use crate::input;
+use crate::solution::{Answer, Solution};
+
+/**
+One of the ALU's four registers.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Register {
+    W,
+    X,
+    Y,
+    Z,
+}
+
+impl Register {
+    fn index(&self) -> usize {
+        match self {
+            Register::W => 0,
+            Register::X => 1,
+            Register::Y => 2,
+            Register::Z => 3,
+        }
+    }
+
+    fn parse(line: &str, token: &str) -> Result<Register, AocError> {
+        match token {
+            "w" => Ok(Register::W),
+            "x" => Ok(Register::X),
+            "y" => Ok(Register::Y),
+            "z" => Ok(Register::Z),
+            _ => Err(AocError::parse(1, column_of(line, token),
+                format!("expected a register, found \"{}\"", token))),
+        }
+    }
+}
+
+/**
+The second operand of an instruction: a register or a number.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Register(Register),
+    Number(i64),
+}
+
+impl Operand {
+    fn parse(line: &str, token: &str) -> Result<Operand, AocError> {
+        match Register::parse(line, token) {
+            Ok(register) => Ok(Operand::Register(register)),
+            Err(_) => Ok(Operand::Number(parse_token(line, token)?)),
+        }
+    }
+}
+
+/**
+An ALU instruction. Every instruction but `inp` stores its result in the
+register named by its first operand.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(Register),
+    Add(Register, Operand),
+    Mul(Register, Operand),
+    Div(Register, Operand),
+    Mod(Register, Operand),
+    Eql(Register, Operand),
+}
+
+impl FromStr for Instruction {
+    type Err = AocError;
+
+    /**
+    Parse an instruction such as `add x -12`.
+
+    # Examples
+    ```
+    use std::str::FromStr;
+    use aoc2021::day24::{Instruction, Operand, Register};
+
+    assert_eq!(Instruction::Inp(Register::W), Instruction::from_str("inp w").unwrap());
+    assert_eq!(Instruction::Add(Register::X, Operand::Number(-12)), Instruction::from_str("add x -12").unwrap());
+    assert_eq!(Instruction::Eql(Register::X, Operand::Register(Register::W)), Instruction::from_str("eql x w").unwrap());
+    let e = Instruction::from_str("mul q 0").unwrap_err();
+    assert_eq!("line 1, column 5: expected a register, found \"q\"", e.to_string());
+    let e = Instruction::from_str("sub x 1").unwrap_err();
+    assert_eq!("line 1, column 1: unknown instruction \"sub\"", e.to_string());
+    ```
+     */
+    fn from_str(s: &str) -> Result<Instruction, AocError> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let operands = match tokens.first() {
+            Some(&"inp") => 1,
+            Some(&("add" | "mul" | "div" | "mod" | "eql")) => 2,
+            Some(op) => {
+                return Err(AocError::parse(1, column_of(s, op), format!("unknown instruction \"{}\"", op)));
+            },
+            None => return Err(AocError::parse(1, 1, "missing instruction")),
+        };
+        if tokens.len() != operands + 1 {
+            return Err(AocError::parse(1, column_of(s, tokens[0]),
+                format!("\"{}\" takes {} operands, found {}", tokens[0], operands, tokens.len() - 1)));
+        }
+        let a = Register::parse(s, tokens[1])?;
+        if operands == 1 {
+            return Ok(Instruction::Inp(a));
+        }
+        let b = Operand::parse(s, tokens[2])?;
+        Ok(match tokens[0] {
+            "add" => Instruction::Add(a, b),
+            "mul" => Instruction::Mul(a, b),
+            "div" => Instruction::Div(a, b),
+            "mod" => Instruction::Mod(a, b),
+            _ => Instruction::Eql(a, b),
+        })
+    }
+}
+
+/**
+Parse an ALU program, one instruction per line.
+
+# Examples
+```
+let program = aoc2021::day24::parse_program("inp x\nmul x -1\n").unwrap();
+assert_eq!(2, program.len());
+let e = aoc2021::day24::parse_program("inp x\nmul x\n").unwrap_err();
+assert_eq!("line 2, column 1: \"mul\" takes 2 operands, found 1", e.to_string());
+```
+ */
+pub fn parse_program(input: &str) -> Result<Vec<Instruction>, AocError> {
+    input.lines()
+        .enumerate()
+        .map(|(ndx, line)| Instruction::from_str(line).map_err(|e| e.on_line(ndx + 1)))
+        .collect()
+}
+
+/**
+Load an ALU program from a file.
+ */
+pub fn load_program(file: &str) -> Result<Vec<Instruction>, AocError> {
+    input::from_file(file, parse_program)
+}
+
+/**
+The arithmetic logic unit: four integer registers, all starting at 0.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Alu {
+    registers: [i64; 4],
+}
+
+impl Alu {
+    pub fn new() -> Alu {
+        Alu { registers: [0; 4] }
+    }
+
+    /**
+    The value held in a register.
+     */
+    pub fn get(&self, register: Register) -> i64 {
+        self.registers[register.index()]
+    }
+
+    fn value(&self, operand: &Operand) -> i64 {
+        match operand {
+            Operand::Register(register) => self.get(*register),
+            Operand::Number(n) => *n,
+        }
+    }
+
+    /**
+    Run a program, reading each `inp` from `input` in turn. Running out of
+    input, dividing by zero, taking a negative modulus or overflowing stops
+    the program with an error.
+
+    # Examples
+    ```
+    use aoc2021::day24::{parse_program, Alu, Register};
+
+    // store the lowest four bits of the input in w, x, y and z
+    let program = parse_program("inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\n\
+        div w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2\n").unwrap();
+    let mut alu = Alu::new();
+    alu.run(&program, &[13]).unwrap();
+    assert_eq!((1, 1, 0, 1), (alu.get(Register::W), alu.get(Register::X), alu.get(Register::Y), alu.get(Register::Z)));
+
+    let program = parse_program("inp x\ninp y\ndiv x y\n").unwrap();
+    let e = Alu::new().run(&program, &[1, 0]).unwrap_err();
+    assert_eq!("no solution: instruction 3: division by zero", e.to_string());
+    let e = Alu::new().run(&program, &[1]).unwrap_err();
+    assert_eq!("no solution: instruction 2: out of input", e.to_string());
+
+    let program = parse_program("add x -9223372036854775807\nadd x -1\ndiv x -1\n").unwrap();
+    let e = Alu::new().run(&program, &[]).unwrap_err();
+    assert_eq!("no solution: instruction 3: overflow", e.to_string());
+    ```
+     */
+    pub fn run(&mut self, program: &[Instruction], input: &[i64]) -> Result<(), AocError> {
+        let mut input = input.iter();
+        for (ndx, instruction) in program.iter().enumerate() {
+            self.execute(instruction, &mut input)
+                .map_err(|reason| AocError::Unsolvable(format!("instruction {}: {}", ndx + 1, reason)))?;
+        }
+        Ok(())
+    }
+
+    fn execute<'a>(
+        &mut self,
+        instruction: &Instruction,
+        input: &mut impl Iterator<Item = &'a i64>,
+    ) -> Result<(), String> {
+        let (a, result) = match instruction {
+            Instruction::Inp(a) => (a, *input.next().ok_or("out of input")?),
+            Instruction::Add(a, b) => (a, self.get(*a).checked_add(self.value(b)).ok_or("overflow")?),
+            Instruction::Mul(a, b) => (a, self.get(*a).checked_mul(self.value(b)).ok_or("overflow")?),
+            Instruction::Div(a, b) => {
+                if self.value(b) == 0 {
+                    return Err(String::from("division by zero"));
+                }
+                (a, self.get(*a).checked_div(self.value(b)).ok_or("overflow")?)
+            },
+            Instruction::Mod(a, b) => {
+                if self.get(*a) < 0 || self.value(b) <= 0 {
+                    return Err(format!("cannot take {} mod {}", self.get(*a), self.value(b)));
+                }
+                (a, self.get(*a) % self.value(b))
+            },
+            Instruction::Eql(a, b) => (a, (self.get(*a) == self.value(b)) as i64),
+        };
+        self.registers[a.index()] = result;
+        Ok(())
+    }
+}
+
+/**
+One of the 14 blocks of MONAD, each of which reads one digit of the model
+number. The blocks differ only in what they divide z by and what they add
+to x and y.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Block {
+    div_z: i64,
+    add_x: i64,
+    add_y: i64,
+}
+
+const MONAD_BLOCK: &str = "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z 1\nadd x 0\neql x w\neql x 0\n\
+    mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y 0\nmul y x\nadd z y\n";
+
+/**
+Split MONAD into its blocks.
+ */
+fn monad_blocks(program: &[Instruction]) -> Result<Vec<Block>, AocError> {
+    let template = parse_program(MONAD_BLOCK).expect("the MONAD block template is valid");
+    if program.len() != 14 * template.len() {
+        return Err(AocError::Unsolvable(format!("expected MONAD to have {} instructions, found {}",
+            14 * template.len(), program.len())));
+    }
+    program.chunks(template.len())
+        .enumerate()
+        .map(|(block_ndx, block)| {
+            let mut params = Block { div_z: 1, add_x: 0, add_y: 0 };
+            for (ndx, (instruction, expected)) in block.iter().zip(&template).enumerate() {
+                match (ndx, instruction) {
+                    (4, Instruction::Div(Register::Z, Operand::Number(n))) if [1, 26].contains(n) => params.div_z = *n,
+                    (5, Instruction::Add(Register::X, Operand::Number(n))) => params.add_x = *n,
+                    (15, Instruction::Add(Register::Y, Operand::Number(n))) => params.add_y = *n,
+                    _ if instruction == expected => (),
+                    _ => return Err(AocError::Unsolvable(format!("instruction {} does not follow the MONAD pattern",
+                        block_ndx * template.len() + ndx + 1))),
+                }
+            }
+            Ok(params)
+        })
+        .collect()
+}
+
+/**
+Check whether MONAD accepts a model number: it must have 14 digits, none
+of them zero, and leave 0 in z.
+
+# Examples
+```
+use aoc2021::day24::{is_accepted, load_program};
+
+let program = load_program("test_inputs/day24.txt").unwrap();
+assert!(is_accepted(&program, 92915979999498).unwrap());
+assert!(!is_accepted(&program, 92915979999497).unwrap());
+assert!(!is_accepted(&program, 92915979990498).unwrap());
+```
+ */
+pub fn is_accepted(program: &[Instruction], model_number: u64) -> Result<bool, AocError> {
+    let digits: Vec<i64> = model_number.to_string()
+        .chars()
+        .map(|c| c.to_digit(10).unwrap() as i64)
+        .collect();
+    if digits.len() != 14 || digits.contains(&0) {
+        return Ok(false);
+    }
+    let mut alu = Alu::new();
+    alu.run(program, &digits)?;
+    Ok(alu.get(Register::Z) == 0)
+}
+
+/**
+Find the largest and smallest model numbers MONAD accepts.
+
+Each block multiplies z by 26 and adds the digit plus `add_y` (a push)
+unless `div z 26` is there, when it instead removes the last push if the
+digit is that push plus `add_x` (a pop). z ends at 0 only when every pop
+matches its push, which ties the digits together in pairs.
+
+# Examples
+```
+use aoc2021::day24::{load_program, model_numbers};
+
+let program = load_program("test_inputs/day24.txt").unwrap();
+assert_eq!((92915979999498, 21611513911181), model_numbers(&program).unwrap());
+assert!(model_numbers(&program[..18]).is_err());
+```
+ */
+pub fn model_numbers(program: &[Instruction]) -> Result<(u64, u64), AocError> {
+    let blocks = monad_blocks(program)?;
+    let mut largest = [0; 14];
+    let mut smallest = [0; 14];
+    let mut pushes: Vec<usize> = Vec::new();
+    for (ndx, block) in blocks.iter().enumerate() {
+        if block.div_z == 1 {
+            if block.add_x < 10 {
+                return Err(AocError::Unsolvable(format!("digit {} can match x without a pop", ndx + 1)));
+            }
+            pushes.push(ndx);
+            continue;
+        }
+        let push = pushes.pop()
+            .ok_or_else(|| AocError::Unsolvable(format!("digit {} pops from an empty stack", ndx + 1)))?;
+        // digit[ndx] = digit[push] + diff
+        let diff = blocks[push].add_y + block.add_x;
+        if diff.abs() > 8 {
+            return Err(AocError::Unsolvable(format!("digits {} and {} cannot differ by {}", push + 1, ndx + 1, diff)));
+        }
+        largest[push] = 9.min(9 - diff);
+        largest[ndx] = largest[push] + diff;
+        smallest[push] = 1.max(1 - diff);
+        smallest[ndx] = smallest[push] + diff;
+    }
+    if !pushes.is_empty() {
+        return Err(AocError::Unsolvable(format!("{} digits are never popped", pushes.len())));
+    }
+    let to_number = |digits: [i64; 14]| digits.iter().fold(0, |n, d| n * 10 + *d as u64);
+    let (largest, smallest) = (to_number(largest), to_number(smallest));
+    for model_number in [largest, smallest] {
+        if !is_accepted(program, model_number)? {
+            return Err(AocError::Unsolvable(format!("MONAD rejects {}", model_number)));
+        }
+    }
+    Ok((largest, smallest))
+}
+
+/**
+Run Day 24's exercise: part 1 finds the largest model number, part 2 the
+smallest.
+
+# Examples
+```
+assert_eq!(92915979999498, aoc2021::day24::run(1, "test_inputs/day24.txt").unwrap());
+assert_eq!(21611513911181, aoc2021::day24::run(2, "test_inputs/day24.txt").unwrap());
+```
+ */
+pub fn run(part: u8, file: &str) -> Result<u64, AocError> {
+    input::from_file(file, |input| solve(part, input))
+}
+
+/**
+Solve Day 24's exercise from the text of the input.
+
+# Examples
+```
+assert!(aoc2021::day24::solve(1, "inp w\n").is_err());
+assert!(aoc2021::day24::solve(3, "inp w\n").is_err());
+```
+ */
+pub fn solve(part: u8, input: &str) -> Result<u64, AocError> {
+    if part != 1 && part != 2 {
+        return Err(AocError::UnknownPart(part.to_string()));
+    }
+    let (largest, smallest) = model_numbers(&parse_program(input)?)?;
+    Ok(if part == 1 { largest } else { smallest })
+}
+
+pub struct Day24 {
+    program: Vec<Instruction>,
+}
+
+impl Solution for Day24 {
+    fn parse(input: &str) -> Result<Day24, AocError> {
+        Ok(Day24 { program: parse_program(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(model_numbers(&self.program)?.0.into())
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Ok(model_numbers(&self.program)?.1.into())
+    }
+}
@@ -1,102 +1,181 @@
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
-use std::collections::HashSet;
+use std::fmt;
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors;
+use crate::input;
+use crate::solution::{Answer, Solution};
+use crate::trace::{NoTrace, Tracer};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    East,
+    South,
+}
+
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = match self {
+            Cell::Empty => '.',
+            Cell::East => '>',
+            Cell::South => 'v',
+        };
+        write!(f, "{}", c)
+    }
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 struct Seafloor {
-    max_x: usize,
-    max_y: usize,
-    east: HashSet<(usize,usize)>,
-    south: HashSet<(usize,usize)>,
+    cells: Grid<Cell>,
 }
 
 impl Seafloor {
-    fn next_east(&self, coord: &(usize,usize)) -> (usize,usize) {
-        assert!(coord.0 < self.max_y, "y coord out of range");
-        assert!(coord.1 < self.max_x, "x coord out of range");
-        (coord.0, (coord.1 + 1)%self.max_x)        
+    fn next_east(&self, coord: (usize, usize)) -> (usize, usize) {
+        neighbors::wrap(coord, (0, 1), self.cells.rows(), self.cells.cols())
     }
 
-    fn next_south(&self, coord: &(usize,usize)) -> (usize,usize) {
-        assert!(coord.0 < self.max_y, "y coord out of range");
-        assert!(coord.1 < self.max_x, "x coord out of range");
-        ((coord.0 + 1)%self.max_y, coord.1)  
+    fn next_south(&self, coord: (usize, usize)) -> (usize, usize) {
+        neighbors::wrap(coord, (1, 0), self.cells.rows(), self.cells.cols())
     }
 
-    fn move_east(&mut self) {
-        let movers: Vec<(usize,usize)> = self.east.iter().filter(|coord| {
-            let next_coord = self.next_east(coord);
-            !self.east.contains(&next_coord) && !self.south.contains(&next_coord)
-        }).map(|coord| *coord).collect();
-        for mover in movers {
-            let next_coord = self.next_east(&mover);
-            self.east.remove(&mover);
-            self.east.insert(next_coord);
+    /**
+    Move every cucumber of one herd that has room to, all at once. Returns
+    whether any moved.
+     */
+    fn move_herd(&mut self, herd: Cell, next: fn(&Seafloor, (usize, usize)) -> (usize, usize)) -> bool {
+        let movers: Vec<(usize, usize)> = self.cells.iter()
+            .filter(|(coord, cell)| **cell == herd && self.cells[next(self, *coord)] == Cell::Empty)
+            .map(|(coord, _)| coord)
+            .collect();
+        for mover in &movers {
+            let next_coord = next(self, *mover);
+            self.cells[*mover] = Cell::Empty;
+            self.cells[next_coord] = herd;
         }
+        !movers.is_empty()
     }
 
-    fn move_south(&mut self) {
-        let movers: Vec<(usize,usize)> = self.south.iter().filter(|coord| {
-            let next_coord = self.next_south(coord);
-            !self.east.contains(&next_coord) && !self.south.contains(&next_coord)
-        }).map(|coord| *coord).collect();
-        for mover in movers {
-            let next_coord = self.next_south(&mover);
-            self.south.remove(&mover);
-            self.south.insert(next_coord);
-        }
+    fn move_east(&mut self) -> bool {
+        self.move_herd(Cell::East, Seafloor::next_east)
+    }
+
+    fn move_south(&mut self) -> bool {
+        self.move_herd(Cell::South, Seafloor::next_south)
     }
 }
 
-fn load_seafloor(file: &str) -> Seafloor {
-    let mut max_x: usize = 0;
-    let mut east: HashSet<(usize,usize)> = HashSet::new();
-    let mut south: HashSet<(usize,usize)> = HashSet::new();
-    let mut y: usize = 0;
-    let file = File::open(file).expect("could not open file");
-    let buf_reader = BufReader::new(file);
-    for line in buf_reader.lines() {
-        let line_unwrap = line.unwrap();
-        if y == 0 {
-            max_x = line_unwrap.len();
-        }
-        for en_char in line_unwrap.chars().enumerate() {
-            match en_char.1 {
-                '>' => {east.insert((y, en_char.0));},
-                'v' => {south.insert((y, en_char.0));},
-                _ => (),
-            };
-        }
-        y += 1;
+impl fmt::Display for Seafloor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.cells)
     }
-    Seafloor{ max_x, max_y: y, east, south }
 }
 
-/**
-Run part 1 of the Day 25 exercise.
+fn parse_seafloor(input: &str) -> Result<Seafloor, AocError> {
+    let cells = Grid::parse(input, |c| match c {
+        '>' => Some(Cell::East),
+        'v' => Some(Cell::South),
+        '.' => Some(Cell::Empty),
+        _ => None,
+    }, "'>', 'v' or '.'")?;
+    Ok(Seafloor { cells })
+}
 
-# Examples
-```
-assert_eq!(58, aoc2021::day25::run_part1("test_inputs/day25.txt"));
-```
+/**
+Count the steps until the sea cucumbers stop moving, tracing the seafloor
+after each herd moves.
  */
-pub fn run_part1(file: &str) -> u32 {
-    let mut seafloor = load_seafloor(file);
+fn steps_until_still(seafloor: &Seafloor, tracer: &mut dyn Tracer) -> u32 {
+    let mut seafloor = seafloor.clone();
     let mut count: u32 = 0;
     loop {
-        let last_seafloor = seafloor.clone();
-        seafloor.move_east();
-        seafloor.move_south();
+        let step = count as usize + 1;
+        let moved_east = seafloor.move_east();
+        tracer.step(step, "east", &seafloor);
+        let moved_south = seafloor.move_south();
+        tracer.step(step, "south", &seafloor);
         count += 1;
-        if last_seafloor == seafloor {
+        if !moved_east && !moved_south {
             break;
         }
     }
     count
 }
 
+/**
+Run part 1 of the Day 25 exercise.
+
+# Examples
+```
+assert_eq!(58, aoc2021::day25::run_part1("test_inputs/day25.txt").unwrap());
+```
+ */
+pub fn run_part1(file: &str) -> Result<u32, AocError> {
+    input::from_file(file, solve_part1)
+}
+
+/**
+Solve part 1 of the Day 25 exercise from the text of the input.
+
+# Examples
+```
+assert_eq!(1, aoc2021::day25::solve_part1(">v\nv>\n").unwrap());
+let e = aoc2021::day25::solve_part1("..>\n.x.\n").unwrap_err();
+assert_eq!("line 2, column 2: expected '>', 'v' or '.', found 'x'", e.to_string());
+```
+ */
+pub fn solve_part1(input: &str) -> Result<u32, AocError> {
+    solve_part1_traced(input, &mut NoTrace)
+}
+
+/**
+Solve part 1 like [`solve_part1`], tracing the seafloor after the east-facing
+herd moves and again after the south-facing herd moves.
+
+# Examples
+```
+let mut steps = Vec::new();
+let mut tracer = |step: usize, action: &str, state: &dyn std::fmt::Display| {
+    steps.push(format!("{} {}\n{}", step, action, state));
+};
+assert_eq!(2, aoc2021::day25::solve_part1_traced(">.v\n", &mut tracer).unwrap());
+assert_eq!(vec!["1 east\n.>v", "1 south\n.>v", "2 east\n.>v", "2 south\n.>v"], steps);
+```
+ */
+pub fn solve_part1_traced(input: &str, tracer: &mut dyn Tracer) -> Result<u32, AocError> {
+    Ok(steps_until_still(&parse_seafloor(input)?, tracer))
+}
+
+/**
+Day 25's puzzle. There is only one part.
+ */
+pub struct Day25 {
+    seafloor: Seafloor,
+}
+
+impl Solution for Day25 {
+    fn parse(input: &str) -> Result<Day25, AocError> {
+        Ok(Day25 { seafloor: parse_seafloor(input)? })
+    }
+
+    fn part1(&self) -> Result<Answer, AocError> {
+        Ok(steps_until_still(&self.seafloor, &mut NoTrace).into())
+    }
 
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        match part {
+            1 => Ok(steps_until_still(&self.seafloor, tracer).into()),
+            _ => self.part2(),
+        }
+    }
+
+    fn part2(&self) -> Result<Answer, AocError> {
+        Err(AocError::UnknownPart(String::from("2 for day 25")))
+    }
+
+    fn parts(&self) -> u8 {
+        1
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -104,10 +183,10 @@ mod tests {
 
     #[test]
     fn test_load_seafloor() {
-        let sf = load_seafloor("test_inputs/day25.txt");
-        assert_eq!(10, sf.max_x);
-        assert_eq!(9, sf.max_y);
-        assert_eq!(26, sf.south.len());
-        assert_eq!(23, sf.east.len());
+        let sf = input::from_file("test_inputs/day25.txt", parse_seafloor).unwrap();
+        assert_eq!(10, sf.cells.cols());
+        assert_eq!(9, sf.cells.rows());
+        assert_eq!(26, sf.cells.values().filter(|c| **c == Cell::South).count());
+        assert_eq!(23, sf.cells.values().filter(|c| **c == Cell::East).count());
     }
 }
\ No newline at end of file
@@ -0,0 +1,184 @@
+//! The error type shared by every day's loaders and solutions.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub enum AocError {
+    /// The input file could not be read.
+    Io { file: String, source: io::Error },
+    /// The input was malformed. Lines and columns start at 1.
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        reason: String,
+    },
+    /// The input was well-formed, but has no answer.
+    Unsolvable(String),
+    UnknownDay(String),
+    UnknownPart(String),
+    /// The command line was malformed.
+    Usage(String),
+}
+
+impl AocError {
+    pub fn io(file: &str, source: io::Error) -> AocError {
+        AocError::Io {
+            file: String::from(file),
+            source,
+        }
+    }
+
+    /**
+    An error parsing the input at the specified line and column. The file
+    name can be filled in later with `in_file`.
+     */
+    pub fn parse(line: usize, column: usize, reason: impl Into<String>) -> AocError {
+        AocError::Parse {
+            file: String::new(),
+            line,
+            column,
+            reason: reason.into(),
+        }
+    }
+
+    /**
+    Move an error found while parsing a single line to that line of the input.
+
+    # Examples
+    ```
+    use aoc2021::error::AocError;
+
+    let e = AocError::parse(1, 4, "bad digit").on_line(7);
+    assert_eq!("line 7, column 4: bad digit", e.to_string());
+    ```
+     */
+    pub fn on_line(self, line: usize) -> AocError {
+        match self {
+            AocError::Parse {
+                file,
+                line: l,
+                column,
+                reason,
+            } => AocError::Parse {
+                file,
+                line: line + l - 1,
+                column,
+                reason,
+            },
+            e => e,
+        }
+    }
+
+    /**
+    Record the file the input was read from.
+
+    # Examples
+    ```
+    use aoc2021::error::AocError;
+
+    let e = AocError::parse(2, 1, "missing value").in_file("input.txt");
+    assert_eq!("input.txt:2:1: missing value", e.to_string());
+    ```
+     */
+    pub fn in_file(self, file: &str) -> AocError {
+        match self {
+            AocError::Parse {
+                line,
+                column,
+                reason,
+                ..
+            } => AocError::Parse {
+                file: String::from(file),
+                line,
+                column,
+                reason,
+            },
+            AocError::Io { source, .. } => AocError::io(file, source),
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AocError::Io { file, source } => {
+                if file.is_empty() {
+                    write!(f, "{}", source)
+                } else {
+                    write!(f, "{}: {}", file, source)
+                }
+            }
+            AocError::Parse {
+                file,
+                line,
+                column,
+                reason,
+            } => {
+                if file.is_empty() {
+                    write!(f, "line {}, column {}: {}", line, column, reason)
+                } else {
+                    write!(f, "{}:{}:{}: {}", file, line, column, reason)
+                }
+            }
+            AocError::Unsolvable(reason) => write!(f, "no solution: {}", reason),
+            AocError::UnknownDay(day) => write!(f, "unknown day {}", day),
+            AocError::UnknownPart(part) => write!(f, "unknown part {}", part),
+            AocError::Usage(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/**
+The 1-based column where `token` starts in `line`, or 1 if `token` is not a
+slice of `line`.
+ */
+pub(crate) fn column_of(line: &str, token: &str) -> usize {
+    let offset = (token.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    if offset <= line.len() {
+        line[..offset].chars().count() + 1
+    } else {
+        1
+    }
+}
+
+/**
+Parse a token found in a line, reporting where it was if it is malformed.
+`token` must be a slice of `line`.
+
+# Examples
+```
+use aoc2021::error::parse_token;
+
+let line = "forward 5x";
+assert_eq!(Ok(5), parse_token::<i32>(line, &line[8..9]).map_err(|e| e.to_string()));
+let e = parse_token::<i32>(line, &line[8..]).unwrap_err();
+assert_eq!("line 1, column 9: could not parse \"5x\": invalid digit found in string", e.to_string());
+```
+ */
+pub fn parse_token<T>(line: &str, token: &str) -> Result<T, AocError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    token.parse::<T>().map_err(|e| {
+        AocError::parse(
+            1,
+            column_of(line, token),
+            format!("could not parse \"{}\": {}", token, e),
+        )
+    })
+}
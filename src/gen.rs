@@ -0,0 +1,797 @@
+//! Random but well-formed puzzle inputs of any size, for benchmarking and
+//! stress testing the solutions.
+//!
+//! Every input comes from a seed, so the same day, size and seed always give
+//! the same input. What the size counts depends on the day: lines for most,
+//! the side of the map for the grid days, and so on (see [`default_size`]).
+//! Days 17, 21, 23 and 24 have inputs of a fixed shape and ignore the size.
+
+use std::collections::{HashSet, VecDeque};
+use crate::error::AocError;
+use crate::grid::Grid;
+use crate::neighbors::Neighborhood;
+
+/**
+A small, seedable pseudo-random number generator (SplitMix64). It is not
+suitable for anything but making up test inputs.
+ */
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /**
+    A number from `0` up to but not including `n`, which must not be 0.
+
+    # Examples
+    ```
+    let mut rng = aoc2021::gen::Rng::new(7);
+    assert!((0..1000).all(|_| rng.below(6) < 6));
+    ```
+     */
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /**
+    A number from `low` to `high`, inclusive.
+
+    # Examples
+    ```
+    let mut rng = aoc2021::gen::Rng::new(7);
+    let rolls: std::collections::HashSet<i64> = (0..1000).map(|_| rng.range(-2, 2)).collect();
+    assert_eq!(5, rolls.len());
+    assert!(rolls.iter().all(|n| (-2..=2).contains(n)));
+    ```
+     */
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        low + self.below((high - low + 1) as u64) as i64
+    }
+
+    /**
+    True with a probability of `percent` in a hundred.
+     */
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for ndx in (1..items.len()).rev() {
+            items.swap(ndx, self.below(ndx as u64 + 1) as usize);
+        }
+    }
+}
+
+/**
+The size of the input generated for a day when none is given: about the
+size of a real puzzle input.
+
+# Examples
+```
+assert_eq!(2000, aoc2021::gen::default_size(1));
+assert_eq!(100, aoc2021::gen::default_size(15));
+```
+ */
+pub fn default_size(day: u8) -> usize {
+    match day {
+        1 => 2000,    // depths
+        2 => 1000,    // commands
+        3 => 1000,    // binary numbers
+        4 => 100,     // bingo boards
+        5 => 500,     // vent lines
+        6 => 300,     // lanternfish
+        7 => 1000,    // crabs
+        8 => 200,     // displays
+        9 => 100,     // side of the heightmap
+        10 => 100,    // lines of brackets
+        11 => 10,     // side of the octopus grid
+        12 => 8,      // caves besides start and end
+        13 => 200,    // dots
+        14 => 20,     // length of the polymer template
+        15 => 100,    // side of the risk map
+        16 => 50,     // packets
+        18 => 100,    // snailfish numbers
+        19 => 5,      // scanners
+        20 => 100,    // side of the image
+        22 => 420,    // reboot steps
+        25 => 100,    // side of the seafloor
+        _ => 1,
+    }
+}
+
+/**
+Generate an input for a day. The size must be at least 1.
+
+# Examples
+```
+let input = aoc2021::gen::generate(1, 10, 42).unwrap();
+assert_eq!(10, input.lines().count());
+assert_eq!(input, aoc2021::gen::generate(1, 10, 42).unwrap());
+assert_ne!(input, aoc2021::gen::generate(1, 10, 43).unwrap());
+
+// every day's input parses
+let registry = aoc2021::registry();
+for day in registry.days() {
+    let size = aoc2021::gen::default_size(day.day).min(20);
+    let input = aoc2021::gen::generate(day.day, size, 1).unwrap();
+    assert!(day.parse_str(&input).is_ok(), "day {}", day.day);
+}
+
+// more caves than two letters can name
+let caves = aoc2021::gen::generate(12, 1000, 1).unwrap();
+let names: std::collections::HashSet<_> = caves.lines().flat_map(|line| line.split('-')).collect();
+assert_eq!(1002, names.len());
+assert_eq!("unknown day 26", aoc2021::gen::generate(26, 1, 1).unwrap_err().to_string());
+assert_eq!("size must be at least 1, found 0", aoc2021::gen::generate(1, 0, 1).unwrap_err().to_string());
+```
+ */
+pub fn generate(day: u8, size: usize, seed: u64) -> Result<String, AocError> {
+    crate::params::at_least("size", size, 1)?;
+    let rng = &mut Rng::new(seed);
+    let input = match day {
+        1 => depths(size, rng),
+        2 => commands(size, rng),
+        3 => diagnostic(size, rng),
+        4 => bingo(size, rng),
+        5 => vents(size, rng),
+        6 => numbers(size, rng, 1, 5),
+        7 => numbers(size, rng, 0, 1999),
+        8 => displays(size, rng),
+        9 => heightmap(size, rng),
+        10 => brackets(size, rng),
+        11 => octopuses(size, rng),
+        12 => caves(size, rng),
+        13 => dots_and_folds(size, rng),
+        14 => polymer(size, rng),
+        15 => digit_grid(size, rng, 1, 9),
+        16 => transmission(size, rng),
+        17 => target_area(rng),
+        18 => snailfish(size, rng),
+        19 => scanner_reports(size, rng).0,
+        20 => image(size, rng),
+        21 => starting_positions(rng),
+        22 => reboot_steps(size, rng),
+        23 => burrow(rng),
+        24 => monad(rng),
+        25 => seafloor(size, rng),
+        _ => return Err(AocError::UnknownDay(day.to_string())),
+    };
+    Ok(input)
+}
+
+fn lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+fn depths(count: usize, rng: &mut Rng) -> String {
+    let mut depth = rng.range(100, 200);
+    lines((0..count).map(|_| {
+        depth = (depth + rng.range(-10, 20)).max(0);
+        depth.to_string()
+    }))
+}
+
+fn commands(count: usize, rng: &mut Rng) -> String {
+    let mut aim = 0;
+    lines((0..count).map(|_| {
+        let n = rng.range(1, 9);
+        match rng.below(3) {
+            0 => format!("forward {}", n),
+            // never aim above the surface
+            1 if aim >= n => {
+                aim -= n;
+                format!("up {}", n)
+            },
+            _ => {
+                aim += n;
+                format!("down {}", n)
+            },
+        }
+    }))
+}
+
+/**
+Distinct binary numbers with no bit where '0' and '1' are equally common,
+so that both ratings are found.
+ */
+fn diagnostic(count: usize, rng: &mut Rng) -> String {
+    let mut width = 12;
+    while count > 1 << (width - 1) {
+        width += 1;
+    }
+    let mut seen = HashSet::new();
+    let mut values = Vec::new();
+    while values.len() < count {
+        let value = rng.below(1 << width);
+        if seen.insert(value) {
+            values.push(value);
+        }
+    }
+    for bit in 0..width {
+        let ones = values.iter().filter(|v| *v >> bit & 1 == 1).count();
+        if ones * 2 == count {
+            // flip the bit in one number, keeping the numbers distinct
+            loop {
+                let ndx = rng.below(count as u64) as usize;
+                let flipped = values[ndx] ^ 1 << bit;
+                if seen.insert(flipped) {
+                    seen.remove(&values[ndx]);
+                    values[ndx] = flipped;
+                    break;
+                }
+            }
+        }
+    }
+    lines(values.iter().map(|v| format!("{:0width$b}", v, width = width)))
+}
+
+/**
+Boards of distinct numbers from 0 to 99, every one of which is drawn, so
+every board wins.
+ */
+fn bingo(boards: usize, rng: &mut Rng) -> String {
+    let mut draws: Vec<u32> = (0..100).collect();
+    rng.shuffle(&mut draws);
+    let mut input = draws.iter().map(u32::to_string).collect::<Vec<_>>().join(",") + "\n";
+    for _ in 0..boards {
+        rng.shuffle(&mut draws);
+        input.push('\n');
+        for row in draws[..25].chunks(5) {
+            input += &lines([row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>().join(" ")]);
+        }
+    }
+    input
+}
+
+fn vents(count: usize, rng: &mut Rng) -> String {
+    lines((0..count).map(|_| {
+        let (x1, y1) = (rng.range(0, 999), rng.range(0, 999));
+        let length = rng.range(1, 300);
+        let (dx, dy) = *rng.pick(&[(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)]);
+        // shorten the line to keep it on the map
+        let fits = |n: i64| (0..1000).contains(&(x1 + dx * n)) && (0..1000).contains(&(y1 + dy * n));
+        let length = (0..=length).rev().find(|n| fits(*n)).unwrap_or(0);
+        format!("{},{} -> {},{}", x1, y1, x1 + dx * length, y1 + dy * length)
+    }))
+}
+
+fn numbers(count: usize, rng: &mut Rng, low: i64, high: i64) -> String {
+    lines([(0..count).map(|_| rng.range(low, high).to_string()).collect::<Vec<_>>().join(",")])
+}
+
+fn displays(count: usize, rng: &mut Rng) -> String {
+    const DIGITS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+    let scramble = |digit: &str, wiring: &[char], rng: &mut Rng| {
+        let mut segments: Vec<char> = digit.chars().map(|c| wiring[c as usize - 'a' as usize]).collect();
+        rng.shuffle(&mut segments);
+        segments.into_iter().collect::<String>()
+    };
+    lines((0..count).map(|_| {
+        let mut wiring: Vec<char> = ('a'..='g').collect();
+        rng.shuffle(&mut wiring);
+        let mut patterns: Vec<String> = DIGITS.iter().map(|d| scramble(d, &wiring, rng)).collect();
+        rng.shuffle(&mut patterns);
+        let output: Vec<String> = (0..4)
+            .map(|_| {
+                let digit = *rng.pick(&DIGITS);
+                scramble(digit, &wiring, rng)
+            })
+            .collect();
+        format!("{} | {}", patterns.join(" "), output.join(" "))
+    }))
+}
+
+/**
+A heightmap of basins around random low points, walled off from each
+other by 9s. A breadth-first search out from every low point at once finds
+each cell's distances to its two nearest low points, visiting each cell at
+most twice.
+ */
+fn heightmap(side: usize, rng: &mut Rng) -> String {
+    let lows: Vec<(usize, usize)> = (0..(side * side / 50).max(3))
+        .map(|_| (rng.range(0, side as i64 - 1) as usize, rng.range(0, side as i64 - 1) as usize))
+        .collect();
+    let mut nearest: Grid<Vec<(usize, usize)>> = Grid::new(side, side, Vec::new());
+    let mut queue: VecDeque<((usize, usize), usize, usize)> = VecDeque::new();
+    for (low, coord) in lows.iter().enumerate() {
+        nearest[*coord].push((low, 0));
+        queue.push_back((*coord, low, 0));
+    }
+    while let Some((coord, low, distance)) = queue.pop_front() {
+        for next in nearest.neighbors(coord, Neighborhood::Orthogonal).collect::<Vec<_>>() {
+            let found = &mut nearest[next];
+            if found.len() < 2 && found.iter().all(|(other, _)| *other != low) {
+                found.push((low, distance + 1));
+                queue.push_back((next, low, distance + 1));
+            }
+        }
+    }
+    lines(nearest.render(|found| match found.as_slice() {
+        [(_, nearest), (_, next), ..] if next - nearest <= 1 => '9',
+        [(_, nearest), ..] => char::from(b'0' + (*nearest).min(8) as u8),
+        [] => '9',
+    }))
+}
+
+/**
+Lines of brackets, about half of them corrupted and the rest incomplete.
+There is always an odd number of incomplete lines, so that they have a
+middle score.
+ */
+fn brackets(count: usize, rng: &mut Rng) -> String {
+    const PAIRS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+    let incomplete = (count / 2) | 1;
+    let mut kinds: Vec<bool> = (0..count).map(|n| n < incomplete).collect();
+    rng.shuffle(&mut kinds);
+    lines(kinds.into_iter().map(|is_incomplete| {
+        let mut line = String::new();
+        let mut open = Vec::new();
+        for _ in 0..rng.range(10, 100) {
+            if open.is_empty() || rng.chance(55) {
+                let (opener, closer) = *rng.pick(&PAIRS);
+                line.push(opener);
+                open.push(closer);
+            } else {
+                line.extend(open.pop());
+            }
+        }
+        if open.is_empty() {
+            let (opener, closer) = *rng.pick(&PAIRS);
+            line.push(opener);
+            open.push(closer);
+        }
+        if !is_incomplete {
+            let expected = open.last().copied();
+            let wrong: Vec<char> = PAIRS.iter().map(|(_, c)| *c).filter(|c| Some(*c) != expected).collect();
+            line.push(*rng.pick(&wrong));
+        }
+        line
+    }))
+}
+
+fn digit_grid(side: usize, rng: &mut Rng, low: i64, high: i64) -> String {
+    lines((0..side).map(|_| (0..side).map(|_| char::from(b'0' + rng.range(low, high) as u8)).collect()))
+}
+
+/**
+Octopus energy levels that all flash together within 10000 steps. About
+half of all random grids never do, so grids are tried until one does or
+[`OCTOPUS_BUDGET`] octopus steps have been simulated across every try, and
+then the last grid is kept anyway, even though day 11 may find it
+unsolvable. Large grids run out of budget after a few tries, or before
+finishing the first. Without day 11 compiled in, the first grid is kept.
+ */
+fn octopuses(side: usize, rng: &mut Rng) -> String {
+    let mut grid = Grid::new(side, side, 0);
+    let mut budget = OCTOPUS_BUDGET;
+    for _ in 0..100 {
+        grid = Grid::from_fn(side, side, |_, _| rng.range(0, 9) as u8);
+        if budget == 0 || synchronizes(&grid, &mut budget) {
+            break;
+        }
+    }
+    lines(grid.render(|n| char::from(b'0' + n)))
+}
+
+/**
+How many single octopus steps (one octopus for one step) the search for a
+synchronizing grid may simulate: a fraction of a second in a release build.
+ */
+const OCTOPUS_BUDGET: usize = 20_000_000;
+
+#[cfg(feature = "day11")]
+fn synchronizes(octopuses: &Grid<u8>, budget: &mut usize) -> bool {
+    let mut octopuses = octopuses.clone();
+    let count = octopuses.rows() * octopuses.cols();
+    for _ in 0..crate::day11::MAX_STEPS {
+        if *budget < count {
+            *budget = 0;
+            return false;
+        }
+        *budget -= count;
+        if crate::day11::step(&mut octopuses) as usize == count {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(not(feature = "day11"))]
+fn synchronizes(_octopuses: &Grid<u8>, _budget: &mut usize) -> bool {
+    true
+}
+
+/**
+A connected cave system. Big caves are never joined to each other, which
+would allow endless paths. Names have two letters, or more once there are
+too many caves for two letters to name with room to spare.
+ */
+fn caves(count: usize, rng: &mut Rng) -> String {
+    let mut names = vec![String::from("start"), String::from("end")];
+    let mut seen: HashSet<String> = names.iter().cloned().collect();
+    // at least twice as many names as caves, so a new one is quick to find
+    let mut length = 2;
+    while 26usize.saturating_pow(length) < 2 * (count + 2) {
+        length += 1;
+    }
+    while names.len() < count + 2 {
+        let name: String = (0..length).map(|_| char::from(b'a' + rng.below(26) as u8)).collect();
+        let name = if rng.chance(25) { name.to_uppercase() } else { name };
+        if seen.insert(name.to_lowercase()) {
+            names.push(name);
+        }
+    }
+    let is_big = |ndx: usize| names[ndx].chars().all(|c| c.is_ascii_uppercase());
+    let mut edges = Vec::new();
+    let mut joined = HashSet::new();
+    let mut join = |a: usize, b: usize, edges: &mut Vec<(usize, usize)>| {
+        if a != b && !(is_big(a) && is_big(b)) && joined.insert((a.min(b), a.max(b))) {
+            edges.push((a, b));
+        }
+    };
+    // a tree joining every cave, so every cave can be reached
+    for ndx in 1..names.len() {
+        let small: Vec<usize> = (0..ndx).filter(|n| !is_big(*n)).collect();
+        let other = if is_big(ndx) { *rng.pick(&small) } else { rng.below(ndx as u64) as usize };
+        join(ndx, other, &mut edges);
+    }
+    for _ in 0..count / 2 {
+        let a = rng.below(names.len() as u64) as usize;
+        let b = rng.below(names.len() as u64) as usize;
+        join(a, b, &mut edges);
+    }
+    rng.shuffle(&mut edges);
+    lines(edges.into_iter().map(|(a, b)| format!("{}-{}", names[a], names[b])))
+}
+
+/**
+Dots that fold down onto a 40 by 6 code, made by unfolding random dots on
+the code. No dot ever lies on a fold line.
+ */
+fn dots_and_folds(count: usize, rng: &mut Rng) -> String {
+    // folds in the order they are made, each halving the paper
+    let mut folds = Vec::new();
+    let (mut width, mut height) = (40, 6);
+    for _ in 0..3 {
+        folds.push(('x', width));
+        folds.push(('y', height));
+        width = width * 2 + 1;
+        height = height * 2 + 1;
+    }
+    folds.reverse();
+    let mut dots = Vec::new();
+    let mut seen = HashSet::new();
+    for _ in 0..count * 10 {
+        if dots.len() == count {
+            break;
+        }
+        let (mut x, mut y) = (rng.range(0, 39), rng.range(0, 5));
+        for (axis, line) in folds.iter().rev() {
+            if rng.chance(50) {
+                match axis {
+                    'x' => x = 2 * line - x,
+                    _ => y = 2 * line - y,
+                }
+            }
+        }
+        if seen.insert((x, y)) {
+            dots.push(format!("{},{}", x, y));
+        }
+    }
+    lines(dots) + "\n" + &lines(folds.iter().map(|(axis, line)| format!("fold along {}={}", axis, line)))
+}
+
+/**
+A polymer template over ten elements, with an insertion rule for every
+pair of them.
+ */
+fn polymer(length: usize, rng: &mut Rng) -> String {
+    let mut alphabet: Vec<char> = ('A'..='Z').collect();
+    rng.shuffle(&mut alphabet);
+    let elements = &alphabet[..10];
+    let template: String = (0..length).map(|_| *rng.pick(elements)).collect();
+    let mut rules = Vec::new();
+    for a in elements {
+        for b in elements {
+            rules.push(format!("{}{} -> {}", a, b, rng.pick(elements)));
+        }
+    }
+    rng.shuffle(&mut rules);
+    lines([template, String::new()]) + &lines(rules)
+}
+
+/**
+A BITS transmission of `count` packets, as hexadecimal.
+ */
+fn transmission(count: usize, rng: &mut Rng) -> String {
+    let mut bits = Vec::new();
+    packet(count, false, rng, &mut bits);
+    while bits.len() % 4 != 0 {
+        bits.push(false);
+    }
+    let hex: String = bits.chunks(4)
+        .map(|nibble| nibble.iter().fold(0, |n, bit| n * 2 + *bit as u32))
+        .map(|n| std::char::from_digit(n, 16).unwrap().to_ascii_uppercase())
+        .collect();
+    lines([hex])
+}
+
+fn push_bits(bits: &mut Vec<bool>, value: u64, width: usize) {
+    bits.extend((0..width).rev().map(|bit| value >> bit & 1 == 1));
+}
+
+/**
+Append a packet holding `count` packets, itself included. The operands of
+a product are only ever small literals, so that no value overflows.
+ */
+fn packet(count: usize, small: bool, rng: &mut Rng, bits: &mut Vec<bool>) {
+    push_bits(bits, rng.below(8), 3);
+    let inner = count - 1;
+    let mut kinds = Vec::new();
+    if inner >= 1 {
+        kinds.extend([0, 2, 3]);
+    }
+    if (1..=3).contains(&inner) {
+        kinds.push(1);
+    }
+    if inner >= 2 {
+        kinds.extend([5, 6, 7]);
+    }
+    let kind = match kinds.as_slice() {
+        _ if small => 4,
+        [] => 4,
+        kinds => *rng.pick(kinds),
+    };
+    push_bits(bits, kind, 3);
+    if kind == 4 {
+        let value = rng.below(if small { 1 << 12 } else { 1 << 20 });
+        let groups = (1..).find(|n| value >> (4 * n) == 0).unwrap();
+        for group in (0..groups).rev() {
+            bits.push(group > 0);
+            push_bits(bits, value >> (4 * group) & 0xf, 4);
+        }
+        return;
+    }
+    // split the packets inside among the subpackets
+    let parts = match kind {
+        1 => inner,
+        5..=7 => 2,
+        _ => rng.range(1, inner.min(4) as i64) as usize,
+    };
+    let mut sizes = vec![1; parts];
+    for _ in parts..inner {
+        sizes[rng.below(parts as u64) as usize] += 1;
+    }
+    let mut subpackets = Vec::new();
+    for size in sizes {
+        packet(size, kind == 1, rng, &mut subpackets);
+    }
+    if subpackets.len() < 1 << 15 && rng.chance(50) {
+        bits.push(false);
+        push_bits(bits, subpackets.len() as u64, 15);
+    } else {
+        bits.push(true);
+        push_bits(bits, parts as u64, 11);
+    }
+    bits.extend(subpackets);
+}
+
+fn target_area(rng: &mut Rng) -> String {
+    let x1 = rng.range(20, 200);
+    let y1 = rng.range(-200, -10);
+    format!("target area: x={}..{}, y={}..{}\n", x1, x1 + rng.range(5, 40), y1, y1 + rng.range(5, 9))
+}
+
+fn snailfish(count: usize, rng: &mut Rng) -> String {
+    fn pair(depth: usize, rng: &mut Rng) -> String {
+        let element = |rng: &mut Rng| {
+            if depth < 4 && rng.chance(50) {
+                pair(depth + 1, rng)
+            } else {
+                rng.range(0, 9).to_string()
+            }
+        };
+        let left = element(rng);
+        format!("[{},{}]", left, element(rng))
+    }
+    lines((0..count).map(|_| pair(1, rng)))
+}
+
+/**
+What generated scanner reports should solve to: every scanner's position
+relative to scanner 0, and the number of beacons.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScannerTruth {
+    pub positions: Vec<[i32; 3]>,
+    pub beacons: usize,
+}
+
+impl ScannerTruth {
+    /**
+    The largest Manhattan distance between two scanners.
+     */
+    pub fn max_distance(&self) -> i32 {
+        let distance = |a: &[i32; 3], b: &[i32; 3]| (0..3).map(|n| (a[n] - b[n]).abs()).sum::<i32>();
+        self.positions.iter()
+            .flat_map(|a| self.positions.iter().map(move |b| distance(a, b)))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/**
+The 24 ways a scanner can face, as rotation matrices.
+ */
+fn rotations() -> Vec<[[i32; 3]; 3]> {
+    let mut rotations = Vec::new();
+    for axes in [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+        for signs in 0..8 {
+            let mut matrix = [[0; 3]; 3];
+            for row in 0..3 {
+                matrix[row][axes[row]] = if signs >> row & 1 == 1 { -1 } else { 1 };
+            }
+            let det = matrix[0][0] * (matrix[1][1] * matrix[2][2] - matrix[1][2] * matrix[2][1])
+                - matrix[0][1] * (matrix[1][0] * matrix[2][2] - matrix[1][2] * matrix[2][0])
+                + matrix[0][2] * (matrix[1][0] * matrix[2][1] - matrix[1][1] * matrix[2][0]);
+            if det == 1 {
+                rotations.push(matrix);
+            }
+        }
+    }
+    rotations
+}
+
+/**
+Reports from `count` scanners, each joined to an earlier one by 12 beacons
+both can see, along with what the reports should solve to. Every scanner
+reports every beacon within 1000 of it on each axis, relative to itself and
+turned the way it faces; scanner 0 faces the same way as the truth.
+
+# Examples
+```
+# #[cfg(feature = "day19")] {
+use aoc2021::day19::{count_beacons, max_scanner_distance, parse_scanners, Options};
+use aoc2021::gen::{scanner_reports, Rng};
+
+let (input, truth) = scanner_reports(4, &mut Rng::new(19));
+let scanners = parse_scanners(&input).unwrap();
+assert_eq!(4, scanners.len());
+assert_eq!(truth.beacons, count_beacons(&scanners, &Options::default()).unwrap());
+assert_eq!(truth.max_distance(), max_scanner_distance(&scanners, &Options::default()).unwrap());
+# }
+```
+ */
+pub fn scanner_reports(count: usize, rng: &mut Rng) -> (String, ScannerTruth) {
+    let mut positions = vec![[0; 3]];
+    let mut beacons = HashSet::new();
+    let mut add_beacons = |low: [i32; 3], high: [i32; 3], n: usize, rng: &mut Rng| {
+        let mut added = 0;
+        while added < n {
+            let beacon = [0, 1, 2].map(|axis| rng.range(low[axis] as i64, high[axis] as i64) as i32);
+            if beacons.insert(beacon) {
+                added += 1;
+            }
+        }
+    };
+    add_beacons([-1000; 3], [1000; 3], 14, rng);
+    for _ in 1..count {
+        let parent: [i32; 3] = *rng.pick(&positions);
+        let position = parent.map(|n| n + rng.range(-1200, 1200) as i32);
+        // 12 beacons where the two scanners' cubes meet, then more of its own
+        let low = [0, 1, 2].map(|axis| parent[axis].max(position[axis]) - 1000);
+        let high = [0, 1, 2].map(|axis| parent[axis].min(position[axis]) + 1000);
+        add_beacons(low, high, 12, rng);
+        add_beacons(position.map(|n| n - 1000), position.map(|n| n + 1000), 14, rng);
+        positions.push(position);
+    }
+    let rotations = rotations();
+    let mut input = String::new();
+    for (ndx, position) in positions.iter().enumerate() {
+        let rotation = if ndx == 0 { rotations[0] } else { *rng.pick(&rotations) };
+        let mut seen: Vec<[i32; 3]> = beacons.iter()
+            .map(|beacon| [0, 1, 2].map(|axis| beacon[axis] - position[axis]))
+            .filter(|offset| offset.iter().all(|n| n.abs() <= 1000))
+            .map(|offset| rotation.map(|row| (0..3).map(|n| row[n] * offset[n]).sum()))
+            .collect();
+        rng.shuffle(&mut seen);
+        if ndx > 0 {
+            input.push('\n');
+        }
+        input += &format!("--- scanner {} ---\n", ndx);
+        input += &lines(seen.iter().map(|[x, y, z]| format!("{},{},{}", x, y, z)));
+    }
+    let beacons = beacons.len();
+    (input, ScannerTruth { positions, beacons })
+}
+
+/**
+An image and enhancement algorithm. When the algorithm lights the dark
+void around the image, it darkens it again on the next step, so the count
+of lit pixels stays finite.
+ */
+fn image(side: usize, rng: &mut Rng) -> String {
+    let mut algorithm: Vec<bool> = (0..512).map(|_| rng.chance(50)).collect();
+    if algorithm[0] {
+        algorithm[511] = false;
+    }
+    let pixel = |lit: bool| if lit { '#' } else { '.' };
+    lines([algorithm.into_iter().map(pixel).collect(), String::new()])
+        + &lines((0..side).map(|_| (0..side).map(|_| pixel(rng.chance(50))).collect()))
+}
+
+fn starting_positions(rng: &mut Rng) -> String {
+    format!("Player 1 starting position: {}\nPlayer 2 starting position: {}\n", rng.range(1, 10), rng.range(1, 10))
+}
+
+/**
+Reboot steps, the first 20 of them within the initialization region.
+ */
+fn reboot_steps(count: usize, rng: &mut Rng) -> String {
+    lines((0..count).map(|ndx| {
+        let (limit, most) = if ndx < 20 { (50, 50) } else { (100_000, 30_000) };
+        let ranges: Vec<String> = ["x", "y", "z"].iter()
+            .map(|axis| {
+                let low = rng.range(-limit, limit - 1);
+                let high = rng.range(low, (low + most).min(limit));
+                format!("{}={}..{}", axis, low, high)
+            })
+            .collect();
+        let state = if ndx == 0 || rng.chance(70) { "on" } else { "off" };
+        format!("{} {}", state, ranges.join(","))
+    }))
+}
+
+fn burrow(rng: &mut Rng) -> String {
+    let mut amphipods: Vec<char> = "AABBCCDD".chars().collect();
+    rng.shuffle(&mut amphipods);
+    let room = |row: &[char]| row.iter().map(char::to_string).collect::<Vec<_>>().join("#");
+    format!("#############\n#...........#\n###{}###\n  #{}#\n  #########\n", room(&amphipods[..4]), room(&amphipods[4..]))
+}
+
+/**
+A MONAD program: 14 blocks that each read a digit, pushing it onto or
+popping it from a stack kept in z. Each pop requires its digit to differ
+from the pushed one by at most 8, so some model number is always accepted.
+ */
+fn monad(rng: &mut Rng) -> String {
+    let block = |div_z: i64, add_x: i64, add_y: i64| format!("inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\n\
+        add x {}\neql x w\neql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\n\
+        add y {}\nmul y x\nadd z y\n", div_z, add_x, add_y);
+    let mut program = String::new();
+    let mut pushes = Vec::new();
+    let mut pushes_left = 7;
+    while pushes_left > 0 || !pushes.is_empty() {
+        if pushes_left > 0 && (pushes.is_empty() || rng.chance(50)) {
+            let add_y = rng.range(1, 16);
+            program += &block(1, rng.range(10, 15), add_y);
+            pushes.push(add_y);
+            pushes_left -= 1;
+        } else {
+            let add_y = pushes.pop().unwrap();
+            program += &block(26, rng.range(-8, 8) - add_y, rng.range(1, 16));
+        }
+    }
+    program
+}
+
+fn seafloor(side: usize, rng: &mut Rng) -> String {
+    lines((0..side).map(|_| (0..side).map(|_| *rng.pick(&['.', '>', 'v'])).collect()))
+}
@@ -0,0 +1,345 @@
+//! A dense, rectangular grid of cells, for the puzzles drawn on a 2D map.
+//!
+//! Cells are addressed by `(row, col)`, starting from `(0, 0)` in the top
+//! left, and stored row by row in one `Vec`.
+
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use crate::error::AocError;
+use crate::neighbors::{self, Neighborhood};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: usize,
+    cols: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /**
+    Create a grid with every cell set to `value`.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::new(2, 3, 0);
+    assert_eq!((2, 3), (grid.rows(), grid.cols()));
+    assert_eq!(0, grid[(1, 2)]);
+    ```
+     */
+    pub fn new(rows: usize, cols: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid { rows, cols, cells: vec![value; rows * cols] }
+    }
+
+    /**
+    Create a grid, finding each cell's value from its coordinates.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::from_fn(2, 3, |row, col| row * 10 + col);
+    assert_eq!(12, grid[(1, 2)]);
+    ```
+     */
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(usize, usize) -> T) -> Grid<T> {
+        let mut cells = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                cells.push(f(row, col));
+            }
+        }
+        Grid { rows, cols, cells }
+    }
+
+    /**
+    Parse a character map, one row per line, turning each character into a
+    cell with `f`. A character `f` rejects is reported as not being
+    `expected`. Every row must be as long as the first.
+
+    # Examples
+    ```
+    use aoc2021::grid::Grid;
+
+    let grid = Grid::parse("#.\n.#\n", |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    }, "'#' or '.'").unwrap();
+    assert!(grid[(1, 1)]);
+    let e = Grid::parse("#.\n.?\n", |c| (c == '#').then(|| true), "'#'").unwrap_err();
+    assert_eq!("line 1, column 2: expected '#', found '.'", e.to_string());
+    let e = Grid::parse("#.\n#\n", |_| Some(()), "anything").unwrap_err();
+    assert_eq!("line 2, column 1: expected 2 columns, found 1", e.to_string());
+    ```
+     */
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> Option<T>, expected: &str) -> Result<Grid<T>, AocError> {
+        let mut cells = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+        for (row, line) in input.lines().enumerate() {
+            let mut count = 0;
+            for (col, c) in line.chars().enumerate() {
+                let cell = f(c).ok_or_else(|| AocError::parse(row + 1, col + 1,
+                    format!("expected {}, found '{}'", expected, c)))?;
+                cells.push(cell);
+                count += 1;
+            }
+            if row == 0 {
+                cols = count;
+            } else if count != cols {
+                return Err(AocError::parse(row + 1, 1, format!("expected {} columns, found {}", cols, count)));
+            }
+            rows += 1;
+        }
+        Ok(Grid { rows, cols, cells })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /**
+    The cell at `(row, col)`, or `None` if that is outside the grid.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::new(2, 3, 'x');
+    assert_eq!(Some(&'x'), grid.get(1, 2));
+    assert_eq!(None, grid.get(1, 3));
+    assert_eq!(None, grid.get(2, 0));
+    ```
+     */
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.index_of(row, col).map(|ndx| &self.cells[ndx])
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        self.index_of(row, col).map(move |ndx| &mut self.cells[ndx])
+    }
+
+    /**
+    The coordinates of a cell given signed coordinates, which may lie
+    outside the grid.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::new(2, 3, 0);
+    assert_eq!(Some((1, 2)), grid.position(1, 2));
+    assert_eq!(None, grid.position(-1, 2));
+    assert_eq!(None, grid.position(1, 3));
+    ```
+     */
+    pub fn position(&self, row: i64, col: i64) -> Option<(usize, usize)> {
+        let row = usize::try_from(row).ok()?;
+        let col = usize::try_from(col).ok()?;
+        self.index_of(row, col).map(|_| (row, col))
+    }
+
+    fn index_of(&self, row: usize, col: usize) -> Option<usize> {
+        if row < self.rows && col < self.cols {
+            Some(row * self.cols + col)
+        } else {
+            None
+        }
+    }
+
+    /**
+    The neighbors of a cell that lie in the grid.
+
+    # Examples
+    ```
+    use aoc2021::grid::Grid;
+    use aoc2021::neighbors::Neighborhood;
+
+    let grid = Grid::new(3, 3, 0);
+    assert_eq!(vec![(0, 1), (1, 0)], grid.neighbors((0, 0), Neighborhood::Orthogonal).collect::<Vec<_>>());
+    assert_eq!(8, grid.neighbors((1, 1), Neighborhood::All).count());
+    ```
+     */
+    pub fn neighbors(&self, coord: (usize, usize), neighborhood: Neighborhood) -> impl Iterator<Item = (usize, usize)> {
+        neighbors::within(coord, neighborhood, self.rows, self.cols)
+    }
+
+    /**
+    The neighbors of a cell, wrapping around the edges of the grid.
+
+    # Examples
+    ```
+    use aoc2021::grid::Grid;
+    use aoc2021::neighbors::Neighborhood;
+
+    let grid = Grid::new(3, 3, 0);
+    assert_eq!(8, grid.wrapping_neighbors((0, 0), Neighborhood::All).count());
+    ```
+     */
+    pub fn wrapping_neighbors(&self, coord: (usize, usize), neighborhood: Neighborhood) -> impl Iterator<Item = (usize, usize)> {
+        neighbors::wrapping(coord, neighborhood, self.rows, self.cols)
+    }
+
+    /**
+    The cells of one row.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::from_fn(2, 3, |row, col| row * 10 + col);
+    assert_eq!(&[10, 11, 12], grid.row(1));
+    ```
+     */
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.cells[row * self.cols..(row + 1) * self.cols]
+    }
+
+    /**
+    The cells of one column, from top to bottom.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::from_fn(2, 3, |row, col| row * 10 + col);
+    assert_eq!(vec![&1, &11], grid.column(1).collect::<Vec<_>>());
+    ```
+     */
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(col < self.cols, "column {} out of range", col);
+        self.cells.iter().skip(col).step_by(self.cols)
+    }
+
+    /**
+    Every row, from top to bottom.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::from_fn(2, 2, |row, col| row * 10 + col);
+    let rows: Vec<&[usize]> = grid.iter_rows().collect();
+    assert_eq!(vec![&[0, 1][..], &[10, 11][..]], rows);
+    ```
+     */
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        // chunks of 0 panic, and an empty grid has no rows anyway
+        self.cells.chunks(self.cols.max(1))
+    }
+
+    /**
+    Every cell with its coordinates, row by row.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::from_fn(2, 2, |row, col| row * 10 + col);
+    assert_eq!(Some(((1, 0), &10)), grid.iter().nth(2));
+    ```
+     */
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        let cols = self.cols;
+        self.cells.iter().enumerate().map(move |(ndx, cell)| ((ndx / cols, ndx % cols), cell))
+    }
+
+    /**
+    Every cell, row by row.
+     */
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /**
+    A grid of the same shape, with `f` applied to every cell.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::new(2, 2, 3);
+    assert_eq!(aoc2021::grid::Grid::new(2, 2, 6), grid.map(|n| n * 2));
+    ```
+     */
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid { rows: self.rows, cols: self.cols, cells: self.cells.iter().map(f).collect() }
+    }
+
+    /**
+    Draw the grid as text, one line per row, turning each cell into a
+    character with `f`.
+
+    # Examples
+    ```
+    use aoc2021::grid::Grid;
+
+    let grid = Grid::from_fn(2, 3, |row, col| row == col);
+    assert_eq!(vec!["#..", ".#."], grid.render(|lit| if *lit { '#' } else { '.' }));
+    ```
+     */
+    pub fn render(&self, mut f: impl FnMut(&T) -> char) -> Vec<String> {
+        self.iter_rows()
+            .take(self.rows)
+            .map(|row| row.iter().map(&mut f).collect())
+            .collect()
+    }
+}
+
+impl Grid<u8> {
+    /**
+    Parse a map of single digits.
+
+    # Examples
+    ```
+    let grid = aoc2021::grid::Grid::parse_digits("219\n398\n").unwrap();
+    assert_eq!(8, grid[(1, 2)]);
+    let e = aoc2021::grid::Grid::parse_digits("219\n3x8\n").unwrap_err();
+    assert_eq!("line 2, column 2: expected a digit, found 'x'", e.to_string());
+    ```
+     */
+    pub fn parse_digits(input: &str) -> Result<Grid<u8>, AocError> {
+        Grid::parse(input, |c| c.to_digit(10).map(|d| d as u8), "a digit")
+    }
+}
+
+/**
+Draw the grid one row per line, drawing each cell with its own `Display`.
+
+# Examples
+```
+let grid = aoc2021::grid::Grid::parse_digits("12\n34\n").unwrap();
+assert_eq!("12\n34", grid.to_string());
+```
+ */
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (ndx, row) in self.iter_rows().take(self.rows).enumerate() {
+            if ndx > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Index by `(row, col)`, panicking outside the grid.
+ */
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        match self.index_of(row, col) {
+            Some(ndx) => &self.cells[ndx],
+            None => panic!("({}, {}) is outside the {}x{} grid", row, col, self.rows, self.cols),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        match self.index_of(row, col) {
+            Some(ndx) => &mut self.cells[ndx],
+            None => panic!("({}, {}) is outside the {}x{} grid", row, col, self.rows, self.cols),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Reading puzzle input from files, stdin, or memory.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use crate::error::AocError;
+
+/**
+Read the whole of a puzzle input. The path `-` reads stdin.
+
+# Examples
+```
+let input = aoc2021::input::read_input("test_inputs/day01.txt").unwrap();
+assert!(input.starts_with("199\n200\n"));
+assert!(aoc2021::input::read_input("this is not a file").is_err());
+```
+ */
+pub fn read_input(file: &str) -> Result<String, AocError> {
+    if file == "-" {
+        read_all(io::stdin().lock()).map_err(|e| e.in_file("<stdin>"))
+    } else {
+        fs::read_to_string(file).map_err(|e| AocError::io(file, e))
+    }
+}
+
+/**
+Open a puzzle input to be read a line at a time. The path `-` reads stdin.
+
+# Examples
+```
+use std::io::BufRead;
+
+let first = aoc2021::input::open("test_inputs/day01.txt").unwrap().lines().next().unwrap().unwrap();
+assert_eq!("199", first);
+assert!(aoc2021::input::open("this is not a file").is_err());
+```
+ */
+pub fn open(file: &str) -> Result<Box<dyn BufRead>, AocError> {
+    if file == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(file).map_err(|e| AocError::io(file, e))?)))
+    }
+}
+
+/**
+Read the whole of a puzzle input from a reader.
+
+# Examples
+```
+let input = aoc2021::input::read_all("3,4,3,1,2\n".as_bytes()).unwrap();
+assert_eq!("3,4,3,1,2\n", input);
+```
+ */
+pub fn read_all(mut reader: impl Read) -> Result<String, AocError> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input).map_err(|e| AocError::io("", e))?;
+    Ok(input)
+}
+
+/**
+Read the input from the file, then hand it to a function that works on the
+text. Parse errors are reported against the file.
+
+# Examples
+```
+use aoc2021::error::AocError;
+use aoc2021::input::from_file;
+
+let depths = from_file("test_inputs/day01.txt", |text| Ok(text.lines().count())).unwrap();
+assert_eq!(10, depths);
+let e = from_file("test_inputs/day01_bad.txt", |_| -> Result<(), _> { Err(AocError::parse(5, 1, "not a depth")) }).unwrap_err();
+assert_eq!("test_inputs/day01_bad.txt:5:1: not a depth", e.to_string());
+```
+ */
+pub fn from_file<T, F>(file: &str, f: F) -> Result<T, AocError>
+where
+    F: FnOnce(&str) -> Result<T, AocError>,
+{
+    f(&read_input(file)?).map_err(|e| e.in_file(name(file)))
+}
+
+/**
+Open the input, then hand it to a function that reads it as it needs to.
+Errors are reported against the file.
+ */
+pub fn from_reader<T, F>(file: &str, f: F) -> Result<T, AocError>
+where
+    F: FnOnce(&mut dyn BufRead) -> Result<T, AocError>,
+{
+    f(&mut open(file)?).map_err(|e| e.in_file(name(file)))
+}
+
+/**
+The name errors in the input file are reported against.
+ */
+pub(crate) fn name(file: &str) -> &str {
+    if file == "-" { "<stdin>" } else { file }
+}
@@ -4,146 +4,145 @@
 //! in the 2021 version of Advent of Code.
 //! 
 //! See [Advent of Code 2021](https://adventofcode.com/2021)
+//!
+//! Each `dayNN` module is compiled only with the cargo feature of the same
+//! name. The `all` feature, on by default, turns on every day; the registry
+//! holds just the days compiled in. The tools the command line is built
+//! from, `bench`, `cache`, `render` and `verify`, each have a feature too, and
+//! the `cli` feature, also on by default, turns them all on.
 
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "cache")]
+pub mod cache;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod count;
+#[cfg(feature = "day01")]
 pub mod day01;
+#[cfg(feature = "day02")]
 pub mod day02;
+#[cfg(feature = "day03")]
 pub mod day03;
+#[cfg(feature = "day04")]
 pub mod day04;
+#[cfg(feature = "day05")]
 pub mod day05;
+#[cfg(feature = "day06")]
 pub mod day06;
+#[cfg(feature = "day07")]
 pub mod day07;
+#[cfg(feature = "day08")]
 pub mod day08;
+#[cfg(feature = "day09")]
 pub mod day09;
+#[cfg(feature = "day10")]
 pub mod day10;
+#[cfg(feature = "day11")]
 pub mod day11;
+#[cfg(feature = "day12")]
 pub mod day12;
+#[cfg(feature = "day13")]
 pub mod day13;
+#[cfg(feature = "day14")]
 pub mod day14;
+#[cfg(feature = "day15")]
 pub mod day15;
+#[cfg(feature = "day16")]
 pub mod day16;
+#[cfg(feature = "day17")]
 pub mod day17;
+#[cfg(feature = "day18")]
 pub mod day18;
+#[cfg(feature = "day19")]
 pub mod day19;
+#[cfg(feature = "day20")]
 pub mod day20;
+#[cfg(feature = "day21")]
 pub mod day21;
+#[cfg(feature = "day22")]
 pub mod day22;
+#[cfg(feature = "day23")]
 pub mod day23;
+#[cfg(feature = "day24")]
+pub mod day24;
+#[cfg(feature = "day25")]
 pub mod day25;
+pub mod error;
+pub mod gen;
+pub mod grid;
+pub mod input;
+pub mod neighbors;
+pub mod params;
+#[cfg(all(feature = "day06", feature = "day14", feature = "day17", feature = "day22"))]
+pub mod reference;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod runner;
+pub mod search;
+pub mod solution;
+pub mod trace;
+#[cfg(feature = "verify")]
+pub mod verify;
+
+use solution::Registry;
 
-pub fn run(problem: &str, args: &[String]) {
-    if problem == "1" {
-        println!("{}", day01::run(&args[0], args[1].parse::<usize>().unwrap()));        
-    } else if problem == "2" {     
-        let part = args[0].parse::<u8>().unwrap();
-        let result = day02::run(&args[1], part);
-        println!("{:?} (product {})", result, result.horizontal * result.depth);
-    } else if problem == "3" {
-        let f = if args[0] == "1" {
-            day03::run_part1
-        } else if args[0] == "2" {
-            day03::run_part2
-        } else {
-            panic!("part must be 1 or 2");
-        };
-        println!("{}", f(&args[1]));
-    } else if problem == "4" {
-        println!("{}", day04::run(args[0].parse::<i32>().unwrap(), &args[1]));
-    } else if problem == "5" {
-        println!("{}", day05::run(args[0].parse::<i32>().unwrap(), &args[1]));
-    } else if problem == "6" {
-        println!("{}", day06::run(&args[0], args[1].parse::<u32>().unwrap()));
-    } else if problem == "7" {
-        println!("{}", day07::run(args[0].parse::<i32>().unwrap(), &args[1]));
-    } else if problem == "8" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day08::run_part1(&args[1]));},
-            "2" => {println!("{}", day08::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "9" {
-        println!("{}", day09::run(args[0].parse::<i32>().unwrap(), &args[1]));
-    } else if problem == "10" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day10::run_part1(&args[1]));},
-            "2" => {println!("{}", day10::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "11" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day11::run_part1(&args[1]));},
-            "2" => {println!("{}", day11::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "12" {
-        println!("{}", day12::run(&args[1], args[0].parse::<usize>().unwrap()));
-    } else if problem == "13" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day13::run_part1(&args[1]));},
-            "2" => {
-                let result = day13::run_part2(&args[1]);
-                for line in result {
-                    println!("{}", line);
-                }
-            }
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "14" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day14::run(&args[1], 10));},
-            "2" => {println!("{}", day14::run(&args[1], 40));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "15" {
-        println!("{}", day15::run(args[0].parse::<u8>().unwrap(), &args[1]));
-    } else if problem == "16" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day16::run_part1(&args[1]));},
-            "2" => {println!("{}", day16::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "17" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day17::run_part1(&args[1]));},
-            "2" => {println!("{}", day17::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "18" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day18::run_part1(&args[1]));},
-            "2" => {println!("{}", day18::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "19" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day19::run_part1(&args[1]));},
-            "2" => {println!("{}", day19::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "20" {
-        println!("{}", day20::run(&args[1], match args[0].as_str() {
-            "1" => 2,
-            "2" => 50,
-            _ => {panic!("Unexpected part {}", args[0]);},
-        }));
-    } else if problem == "21" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day21::run_part1(&args[1]));},
-            "2" => {println!("{}", day21::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "22" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day22::run_part1(&args[1]));},
-            "2" => {println!("{}", day22::run_part2(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    } else if problem == "23" {
-        let part = args[0].parse::<u8>().unwrap();
-        println!("{}", day23::run(part, &args[1]));
-    } else if problem == "25" {
-        match args[0].as_str() {
-            "1" => {println!("{}", day25::run_part1(&args[1]));},
-            _ => {panic!("Unexpected part {}", args[0])},
-        };
-    }
+/**
+The registry of every day with a solution, among the days compiled in.
+ */
+pub fn registry() -> Registry {
+    // with no days compiled in, nothing is registered
+    #[allow(unused_mut)]
+    let mut registry = Registry::new();
+    #[cfg(feature = "day01")]
+    registry.register::<day01::Day01>(1);
+    #[cfg(feature = "day02")]
+    registry.register::<day02::Day02>(2);
+    #[cfg(feature = "day03")]
+    registry.register::<day03::Day03>(3);
+    #[cfg(feature = "day04")]
+    registry.register::<day04::Day04>(4);
+    #[cfg(feature = "day05")]
+    registry.register::<day05::Day05>(5);
+    #[cfg(feature = "day06")]
+    registry.register::<day06::Day06>(6);
+    #[cfg(feature = "day07")]
+    registry.register::<day07::Day07>(7);
+    #[cfg(feature = "day08")]
+    registry.register::<day08::Day08>(8);
+    #[cfg(feature = "day09")]
+    registry.register::<day09::Day09>(9);
+    #[cfg(feature = "day10")]
+    registry.register::<day10::Day10>(10);
+    #[cfg(feature = "day11")]
+    registry.register::<day11::Day11>(11);
+    #[cfg(feature = "day12")]
+    registry.register::<day12::Day12>(12);
+    #[cfg(feature = "day13")]
+    registry.register::<day13::Day13>(13);
+    #[cfg(feature = "day14")]
+    registry.register::<day14::Day14>(14);
+    #[cfg(feature = "day15")]
+    registry.register::<day15::Day15>(15);
+    #[cfg(feature = "day16")]
+    registry.register::<day16::Day16>(16);
+    #[cfg(feature = "day17")]
+    registry.register::<day17::Day17>(17);
+    #[cfg(feature = "day18")]
+    registry.register::<day18::Day18>(18);
+    #[cfg(feature = "day19")]
+    registry.register::<day19::Day19>(19);
+    #[cfg(feature = "day20")]
+    registry.register::<day20::Day20>(20);
+    #[cfg(feature = "day21")]
+    registry.register::<day21::Day21>(21);
+    #[cfg(feature = "day22")]
+    registry.register::<day22::Day22>(22);
+    #[cfg(feature = "day23")]
+    registry.register::<day23::Day23>(23);
+    #[cfg(feature = "day24")]
+    registry.register::<day24::Day24>(24);
+    #[cfg(feature = "day25")]
+    registry.register::<day25::Day25>(25);
+    registry
 }
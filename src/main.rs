@@ -1,7 +1,25 @@
 use std::env;
-use aoc2021::run;
+use std::process;
+use aoc2021::bench::CountingAllocator;
+use aoc2021::cli::{self, EXIT_FAILED, EXIT_OK, EXIT_USAGE};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    run(&args[1], &args[2..]);
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = cli::parse_args(&args)
+        .and_then(|command| cli::execute(&aoc2021::registry(), &command, &mut std::io::stdout().lock()));
+    match result {
+        Ok(true) => process::exit(EXIT_OK),
+        Ok(false) => process::exit(EXIT_FAILED),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            let code = cli::exit_code(&e);
+            if code == EXIT_USAGE {
+                eprintln!("run `aoc2021 help` for usage");
+            }
+            process::exit(code);
+        }
+    }
 }
@@ -0,0 +1,222 @@
+//! The cells around a cell, in 2D and 3D.
+//!
+//! Neighbors come from fixed tables of offsets, so none of these iterators
+//! allocate. They may be unbounded, clipped to a grid's bounds, or wrap
+//! around the edges of a grid as on a torus. 2D coordinates are `(row, col)`
+//! and neighbors are always given in reading order: by row, then column.
+
+/**
+Which cells around a 2D cell count as its neighbors.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// The 4 cells above, left, right and below.
+    Orthogonal,
+    /// The 8 cells touching, including diagonally.
+    All,
+    /// The 3x3 block centred on the cell, including the cell itself.
+    Block,
+}
+
+const ORTHOGONAL: [(i64, i64); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const ALL: [(i64, i64); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+const BLOCK: [(i64, i64); 9] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 0), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+impl Neighborhood {
+    /**
+    The offsets of the neighbors from the cell, in reading order.
+
+    # Examples
+    ```
+    use aoc2021::neighbors::Neighborhood;
+
+    assert_eq!(&[(-1, 0), (0, -1), (0, 1), (1, 0)], Neighborhood::Orthogonal.offsets());
+    assert_eq!(8, Neighborhood::All.offsets().len());
+    assert_eq!((0, 0), Neighborhood::Block.offsets()[4]);
+    ```
+     */
+    pub fn offsets(self) -> &'static [(i64, i64)] {
+        match self {
+            Neighborhood::Orthogonal => &ORTHOGONAL,
+            Neighborhood::All => &ALL,
+            Neighborhood::Block => &BLOCK,
+        }
+    }
+}
+
+/**
+Which cells around a 3D cell count as its neighbors.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Neighborhood3 {
+    /// The 6 cells sharing a face.
+    Faces,
+    /// The 26 cells touching, including along an edge or at a corner.
+    All,
+}
+
+const FACES_3D: [(i64, i64, i64); 6] = [(-1, 0, 0), (0, -1, 0), (0, 0, -1), (0, 0, 1), (0, 1, 0), (1, 0, 0)];
+const ALL_3D: [(i64, i64, i64); 26] = all_3d();
+
+const fn all_3d() -> [(i64, i64, i64); 26] {
+    let mut offsets = [(0, 0, 0); 26];
+    let mut n = 0;
+    let mut ndx = 0;
+    while ndx < 27 {
+        let offset = (ndx / 9 - 1, ndx / 3 % 3 - 1, ndx % 3 - 1);
+        if ndx != 13 {
+            offsets[n] = offset;
+            n += 1;
+        }
+        ndx += 1;
+    }
+    offsets
+}
+
+impl Neighborhood3 {
+    /**
+    The offsets of the neighbors from the cell, ordered by x, then y, then z.
+
+    # Examples
+    ```
+    use aoc2021::neighbors::Neighborhood3;
+
+    assert_eq!(6, Neighborhood3::Faces.offsets().len());
+    let all = Neighborhood3::All.offsets();
+    assert_eq!(26, all.len());
+    assert_eq!((-1, -1, -1), all[0]);
+    assert_eq!((1, 1, 1), all[25]);
+    assert!(!all.contains(&(0, 0, 0)));
+    ```
+     */
+    pub fn offsets(self) -> &'static [(i64, i64, i64)] {
+        match self {
+            Neighborhood3::Faces => &FACES_3D,
+            Neighborhood3::All => &ALL_3D,
+        }
+    }
+}
+
+/**
+The neighbors of a cell on an unbounded plane.
+
+# Examples
+```
+use aoc2021::neighbors::{around, Neighborhood};
+
+let neighbors: Vec<(i64, i64)> = around((0, 5), Neighborhood::Orthogonal).collect();
+assert_eq!(vec![(-1, 5), (0, 4), (0, 6), (1, 5)], neighbors);
+```
+ */
+pub fn around((row, col): (i64, i64), neighborhood: Neighborhood) -> impl Iterator<Item = (i64, i64)> {
+    neighborhood.offsets().iter().map(move |(dr, dc)| (row + dr, col + dc))
+}
+
+/**
+The neighbors of a cell that lie in a grid of `rows` by `cols`. Neighbors
+off the edges are left out.
+
+# Examples
+```
+use aoc2021::neighbors::{within, Neighborhood};
+
+let neighbors: Vec<(usize, usize)> = within((0, 0), Neighborhood::All, 3, 3).collect();
+assert_eq!(vec![(0, 1), (1, 0), (1, 1)], neighbors);
+assert_eq!(4, within((1, 1), Neighborhood::Orthogonal, 3, 3).count());
+assert_eq!(4, within((2, 2), Neighborhood::Block, 3, 3).count());
+```
+ */
+pub fn within(
+    (row, col): (usize, usize),
+    neighborhood: Neighborhood,
+    rows: usize,
+    cols: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighborhood.offsets().iter().filter_map(move |(dr, dc)| {
+        let row = row.checked_add_signed(*dr as isize).filter(|r| *r < rows)?;
+        let col = col.checked_add_signed(*dc as isize).filter(|c| *c < cols)?;
+        Some((row, col))
+    })
+}
+
+/**
+Step from a cell by `offset` in a grid of `rows` by `cols` whose edges wrap
+around, as on a torus.
+
+# Examples
+```
+use aoc2021::neighbors::wrap;
+
+assert_eq!((0, 0), wrap((0, 2), (0, 1), 2, 3));
+assert_eq!((1, 2), wrap((0, 0), (-1, -1), 2, 3));
+```
+ */
+pub fn wrap((row, col): (usize, usize), (dr, dc): (i64, i64), rows: usize, cols: usize) -> (usize, usize) {
+    (
+        (row as i64 + dr).rem_euclid(rows as i64) as usize,
+        (col as i64 + dc).rem_euclid(cols as i64) as usize,
+    )
+}
+
+/**
+The neighbors of a cell in a grid of `rows` by `cols` whose edges wrap
+around, as on a torus. On a grid narrower than 3, a cell may be its own
+neighbor, or appear more than once.
+
+# Examples
+```
+use aoc2021::neighbors::{wrapping, Neighborhood};
+
+let neighbors: Vec<(usize, usize)> = wrapping((0, 0), Neighborhood::Orthogonal, 3, 3).collect();
+assert_eq!(vec![(2, 0), (0, 2), (0, 1), (1, 0)], neighbors);
+```
+ */
+pub fn wrapping(
+    coord: (usize, usize),
+    neighborhood: Neighborhood,
+    rows: usize,
+    cols: usize,
+) -> impl Iterator<Item = (usize, usize)> {
+    neighborhood.offsets().iter().map(move |offset| wrap(coord, *offset, rows, cols))
+}
+
+/**
+The neighbors of a cell in unbounded 3D space.
+
+# Examples
+```
+use aoc2021::neighbors::{around_3d, Neighborhood3};
+
+assert_eq!(Some((0, 1, 1)), around_3d((1, 1, 1), Neighborhood3::Faces).next());
+assert_eq!(26, around_3d((1, 1, 1), Neighborhood3::All).count());
+```
+ */
+pub fn around_3d((x, y, z): (i64, i64, i64), neighborhood: Neighborhood3) -> impl Iterator<Item = (i64, i64, i64)> {
+    neighborhood.offsets().iter().map(move |(dx, dy, dz)| (x + dx, y + dy, z + dz))
+}
+
+/**
+The neighbors of a cell that lie in a box of `size`, from `(0, 0, 0)`.
+Neighbors outside the box are left out.
+
+# Examples
+```
+use aoc2021::neighbors::{within_3d, Neighborhood3};
+
+assert_eq!(3, within_3d((0, 0, 0), Neighborhood3::Faces, (2, 2, 2)).count());
+assert_eq!(7, within_3d((0, 0, 0), Neighborhood3::All, (2, 2, 2)).count());
+assert_eq!(26, within_3d((1, 1, 1), Neighborhood3::All, (3, 3, 3)).count());
+```
+ */
+pub fn within_3d(
+    (x, y, z): (usize, usize, usize),
+    neighborhood: Neighborhood3,
+    size: (usize, usize, usize),
+) -> impl Iterator<Item = (usize, usize, usize)> {
+    neighborhood.offsets().iter().filter_map(move |(dx, dy, dz)| {
+        let x = x.checked_add_signed(*dx as isize).filter(|x| *x < size.0)?;
+        let y = y.checked_add_signed(*dy as isize).filter(|y| *y < size.1)?;
+        let z = z.checked_add_signed(*dz as isize).filter(|z| *z < size.2)?;
+        Some((x, y, z))
+    })
+}
@@ -0,0 +1,134 @@
+//! Overriding a puzzle's constants, such as the size of day 22's
+//! initialization region, for what-if experiments.
+//!
+//! Parameters are given as `key=value` pairs. Each day with constants worth
+//! changing has an `Options` struct holding the puzzle's own values by
+//! default, built from the parameters with its `from_params`.
+
+use std::fmt;
+use std::str::FromStr;
+use crate::error::AocError;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Params {
+    values: Vec<(String, String)>,
+}
+
+impl Params {
+    pub fn new() -> Params {
+        Params { values: Vec::new() }
+    }
+
+    /**
+    Add a parameter given as `key=value`.
+
+    # Examples
+    ```
+    let mut params = aoc2021::params::Params::new();
+    params.add("tiles=3").unwrap();
+    assert_eq!(Some(3), params.get::<u32>("tiles").unwrap());
+    assert_eq!("expected key=value, found 'tiles'", params.add("tiles").unwrap_err().to_string());
+    assert_eq!("tiles given more than once", params.add("tiles=4").unwrap_err().to_string());
+    ```
+     */
+    pub fn add(&mut self, param: &str) -> Result<(), AocError> {
+        let (key, value) = param.split_once('=')
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| AocError::Usage(format!("expected key=value, found '{}'", param)))?;
+        if self.values.iter().any(|(k, _)| k == key) {
+            return Err(AocError::Usage(format!("{} given more than once", key)));
+        }
+        self.values.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /**
+    Every parameter as a key and value, in the order given.
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /**
+    The value of a parameter, if it was given.
+
+    # Examples
+    ```
+    let mut params = aoc2021::params::Params::new();
+    params.add("region=ten").unwrap();
+    assert_eq!(None, params.get::<i32>("tiles").unwrap());
+    let e = params.get::<i32>("region").unwrap_err();
+    assert_eq!("invalid value 'ten' for region: invalid digit found in string", e.to_string());
+    ```
+     */
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>, AocError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.values.iter().find(|(k, _)| k == key) {
+            Some((_, value)) => value.parse().map(Some).map_err(|e| {
+                AocError::Usage(format!("invalid value '{}' for {}: {}", value, key, e))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /**
+    Overwrite `field` with the parameter's value, if it was given.
+     */
+    pub fn set<T>(&self, key: &str, field: &mut T) -> Result<(), AocError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        if let Some(value) = self.get(key)? {
+            *field = value;
+        }
+        Ok(())
+    }
+
+    /**
+    Fail on any parameter not among `known`.
+
+    # Examples
+    ```
+    let mut params = aoc2021::params::Params::new();
+    params.add("tiles=3").unwrap();
+    assert!(params.check_known(&["tiles"]).is_ok());
+    let e = params.check_known(&["region"]).unwrap_err();
+    assert_eq!("unknown parameter tiles (expected region)", e.to_string());
+    let e = params.check_known(&[]).unwrap_err();
+    assert_eq!("unknown parameter tiles (this day has no parameters)", e.to_string());
+    ```
+     */
+    pub fn check_known(&self, known: &[&str]) -> Result<(), AocError> {
+        match self.values.iter().find(|(key, _)| !known.contains(&key.as_str())) {
+            Some((key, _)) if known.is_empty() =>
+                Err(AocError::Usage(format!("unknown parameter {} (this day has no parameters)", key))),
+            Some((key, _)) =>
+                Err(AocError::Usage(format!("unknown parameter {} (expected {})", key, known.join(", ")))),
+            None => Ok(()),
+        }
+    }
+}
+
+/**
+Fail unless a parameter's value is at least `min`.
+
+# Examples
+```
+assert!(aoc2021::params::at_least("tiles", 1, 1).is_ok());
+assert_eq!("tiles must be at least 1, found 0", aoc2021::params::at_least("tiles", 0, 1).unwrap_err().to_string());
+```
+ */
+pub fn at_least<T: PartialOrd + fmt::Display>(key: &str, value: T, min: T) -> Result<(), AocError> {
+    if value < min {
+        return Err(AocError::Usage(format!("{} must be at least {}, found {}", key, min, value)));
+    }
+    Ok(())
+}
@@ -0,0 +1,339 @@
+//! Slow but plainly correct solutions to the days whose real solutions are
+//! clever, and a differential check of one against the other.
+//!
+//! Each reference solution simulates the puzzle just as it is told: every
+//! lanternfish on day 6, the whole polymer on day 14, every probe launch on
+//! day 17 and every cube on day 22. They are only fast enough for small
+//! inputs, such as those from [`random_input`].
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use crate::day06;
+use crate::day14;
+use crate::day17;
+use crate::day22;
+use crate::error::AocError;
+use crate::gen::Rng;
+
+/**
+The days with a reference solution.
+ */
+pub const DAYS: [u8; 4] = [6, 14, 17, 22];
+
+/**
+Count the lanternfish after `days` days, keeping the timer of each fish.
+
+# Examples
+```
+assert_eq!(26, aoc2021::reference::lanternfish("3,4,3,1,2\n", 18).unwrap());
+assert_eq!(5934, aoc2021::reference::lanternfish("3,4,3,1,2\n", 80).unwrap());
+```
+ */
+pub fn lanternfish(input: &str, days: u32) -> Result<u128, AocError> {
+    let mut fish: Vec<i8> = Vec::new();
+    for group in day06::parse_fish(input)? {
+        for _ in 0..group.get_count() {
+            fish.push(group.get_days_until_spawn());
+        }
+    }
+    for _ in 0..days {
+        let mut born = 0;
+        for timer in fish.iter_mut() {
+            if *timer == 0 {
+                *timer = 6;
+                born += 1;
+            } else {
+                *timer -= 1;
+            }
+        }
+        fish.extend(std::iter::repeat_n(8, born));
+    }
+    Ok(fish.len() as u128)
+}
+
+/**
+Apply the insertion rules `steps` times to the whole polymer, then find the
+difference between the most and least common elements.
+
+# Examples
+```
+assert_eq!(1588, aoc2021::reference::polymer(&std::fs::read_to_string("test_inputs/day14.txt").unwrap(), 10).unwrap());
+```
+ */
+pub fn polymer(input: &str, steps: u32) -> Result<u128, AocError> {
+    let (_, _, _, rules) = day14::parse_polymers(input)?;
+    // each rule turns AB into AC and CB; C is what goes between
+    let inserts: HashMap<(char, char), char> = rules.iter()
+        .map(|(pair, new_pairs)| {
+            let mut pair = pair.chars();
+            let (a, b) = (pair.next().unwrap(), pair.next().unwrap());
+            ((a, b), new_pairs[0].chars().nth(1).unwrap())
+        })
+        .collect();
+    let mut polymer: Vec<char> = input.lines().next().unwrap_or("").trim().chars().collect();
+    for _ in 0..steps {
+        let mut next = Vec::with_capacity(polymer.len() * 2);
+        for pair in polymer.windows(2) {
+            next.push(pair[0]);
+            if let Some(c) = inserts.get(&(pair[0], pair[1])) {
+                next.push(*c);
+            }
+        }
+        next.extend(polymer.last());
+        polymer = next;
+    }
+    let mut counts: HashMap<char, u128> = HashMap::new();
+    for c in polymer {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    Ok(counts.values().max().unwrap_or(&0) - counts.values().min().unwrap_or(&0))
+}
+
+/**
+Launch the probe at every velocity that could reach the target and follow
+it step by step. Returns the highest y of any probe that lands in the target,
+and the number of velocities that land in it.
+
+# Examples
+```
+assert_eq!((45, 112), aoc2021::reference::probe("target area: x=20..30, y=-10..-5\n").unwrap());
+```
+ */
+pub fn probe(input: &str) -> Result<(i32, usize), AocError> {
+    let ((x_low, x_high), (y_low, y_high)) = day17::parse_target_coord(input)?;
+    let mut highest = i32::MIN;
+    let mut count = 0;
+    // faster probes overshoot the target in their first step, whichever
+    // way they go; a probe going up at v comes back down at -v - 1
+    for x_v in 1..=x_high {
+        for y_v in y_low..-y_low {
+            let (mut x, mut y, mut dx, mut dy) = (0, 0, x_v, y_v);
+            let mut peak = 0;
+            while x <= x_high && y >= y_low {
+                if x >= x_low && y <= y_high {
+                    highest = highest.max(peak);
+                    count += 1;
+                    break;
+                }
+                x += dx;
+                y += dy;
+                peak = peak.max(y);
+                dx -= dx.signum();
+                dy -= 1;
+            }
+        }
+    }
+    if count == 0 {
+        return Err(AocError::Unsolvable(String::from("no velocity lands the probe in the target")));
+    }
+    Ok((highest, count))
+}
+
+/**
+Count the lit cubes after every reboot step, lighting each cube.
+
+# Examples
+```
+let input = "on x=10..12,y=10..12,z=10..12\noff x=11..11,y=11..11,z=11..11\non x=51..52,y=0..0,z=0..0\n";
+assert_eq!(28, aoc2021::reference::reactor(input).unwrap());
+```
+ */
+pub fn reactor(input: &str) -> Result<usize, AocError> {
+    let mut lit: HashSet<(i32, i32, i32)> = HashSet::new();
+    for instruction in day22::parse_instructions(input)? {
+        for x in instruction.x_range.0..=instruction.x_range.1 {
+            for y in instruction.y_range.0..=instruction.y_range.1 {
+                for z in instruction.z_range.0..=instruction.z_range.1 {
+                    if instruction.on {
+                        lit.insert((x, y, z));
+                    } else {
+                        lit.remove(&(x, y, z));
+                    }
+                }
+            }
+        }
+    }
+    Ok(lit.len())
+}
+
+/**
+A difference between the answers of the reference and real solutions.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub day: u8,
+    pub what: String,
+    pub reference: String,
+    pub solution: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "day {} {}: reference {}, solution {}", self.day, self.what, self.reference, self.solution)
+    }
+}
+
+/**
+Solve the input with both the reference and real solutions and list where
+they differ.
+
+# Examples
+```
+use aoc2021::reference::compare;
+
+assert!(compare(6, "3,4,3,1,2\n").unwrap().is_empty());
+assert!(compare(17, "target area: x=20..30, y=-10..-5\n").unwrap().is_empty());
+let e = compare(5, "0,9 -> 5,9\n").unwrap_err();
+assert_eq!("unknown day 5 (days with a reference solution are 6, 14, 17 and 22)", e.to_string());
+```
+ */
+pub fn compare(day: u8, input: &str) -> Result<Vec<Mismatch>, AocError> {
+    let mut mismatches = Vec::new();
+    let mut check = |what: String, reference: String, solution: String| {
+        if reference != solution {
+            mismatches.push(Mismatch { day, what, reference, solution });
+        }
+    };
+    match day {
+        6 => {
+            let fish = day06::parse_fish(input)?;
+            for days in [1, 18, 80] {
+                check(format!("after {} days", days),
+                    lanternfish(input, days)?.to_string(),
+                    day06::count_fish(&fish, days)?.to_string());
+            }
+        },
+        14 => {
+            for steps in [1, 4, 10] {
+                check(format!("after {} steps", steps),
+                    polymer(input, steps)?.to_string(),
+                    day14::solve(input, steps)?.to_string());
+            }
+        },
+        17 => {
+            let (highest, count) = probe(input)?;
+            check(String::from("highest y"), highest.to_string(), day17::solve_part1(input)?.to_string());
+            check(String::from("velocities"), count.to_string(), day17::solve_part2(input)?.to_string());
+        },
+        22 => {
+            check(String::from("lit cubes"), reactor(input)?.to_string(), day22::solve_part2(input)?.to_string());
+        },
+        _ => {
+            return Err(AocError::UnknownDay(
+                format!("{} (days with a reference solution are 6, 14, 17 and 22)", day)));
+        },
+    }
+    Ok(mismatches)
+}
+
+/**
+A random input small enough for the reference solution to day 6, 14, 17 or
+22. The same seed always gives the same input.
+
+# Examples
+```
+use aoc2021::reference::random_input;
+
+assert_eq!(random_input(22, 7).unwrap(), random_input(22, 7).unwrap());
+assert!(aoc2021::reference::compare(22, &random_input(22, 7).unwrap()).unwrap().is_empty());
+```
+ */
+pub fn random_input(day: u8, seed: u64) -> Result<String, AocError> {
+    let rng = &mut Rng::new(seed);
+    let input = match day {
+        6 => {
+            let timers: Vec<String> = (0..rng.range(1, 6)).map(|_| rng.range(0, 8).to_string()).collect();
+            timers.join(",") + "\n"
+        },
+        14 => {
+            let elements = ['B', 'C', 'H', 'N'];
+            let template: String = (0..rng.range(2, 6)).map(|_| *rng.pick(&elements)).collect();
+            let mut rules = Vec::new();
+            for a in elements {
+                for b in elements {
+                    // leave out some rules, so some pairs never change
+                    if rng.chance(80) {
+                        rules.push(format!("{}{} -> {}\n", a, b, rng.pick(&elements)));
+                    }
+                }
+            }
+            format!("{}\n\n{}", template, rules.concat())
+        },
+        17 => {
+            let x = rng.range(1, 40);
+            let y = rng.range(-40, -1);
+            format!("target area: x={}..{}, y={}..{}\n", x, x + rng.range(0, 15), y, (y + rng.range(0, 10)).min(-1))
+        },
+        22 => {
+            let steps: Vec<String> = (0..rng.range(1, 8))
+                .map(|ndx| {
+                    let ranges: Vec<String> = ["x", "y", "z"].iter()
+                        .map(|axis| {
+                            let low = rng.range(-8, 8);
+                            format!("{}={}..{}", axis, low, low + rng.range(0, 6))
+                        })
+                        .collect();
+                    let state = if ndx == 0 || rng.chance(60) { "on" } else { "off" };
+                    format!("{} {}\n", state, ranges.join(","))
+                })
+                .collect();
+            steps.concat()
+        },
+        _ => {
+            return Err(AocError::UnknownDay(
+                format!("{} (days with a reference solution are 6, 14, 17 and 22)", day)));
+        },
+    };
+    Ok(input)
+}
+
+/**
+Compare the reference and real solutions on the random inputs from `seeds`,
+returning each input with a mismatch.
+
+# Examples
+```
+assert!(aoc2021::reference::differential(6, 0..20).unwrap().is_empty());
+```
+ */
+pub fn differential(day: u8, seeds: std::ops::Range<u64>) -> Result<Vec<(String, Mismatch)>, AocError> {
+    let mut failures = Vec::new();
+    for seed in seeds {
+        let input = random_input(day, seed)?;
+        for mismatch in compare(day, &input)? {
+            failures.push((input.clone(), mismatch));
+        }
+    }
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_agree(day: u8) {
+        let failures = differential(day, 0..200).unwrap();
+        assert!(failures.is_empty(), "{} mismatches, first on\n{}{}",
+            failures.len(), failures[0].0, failures[0].1);
+    }
+
+    #[test]
+    fn test_lanternfish_agrees() {
+        assert_agree(6);
+    }
+
+    #[test]
+    fn test_polymer_agrees() {
+        assert_agree(14);
+    }
+
+    #[test]
+    fn test_probe_agrees() {
+        assert_agree(17);
+    }
+
+    #[test]
+    fn test_reactor_agrees() {
+        assert_agree(22);
+    }
+}
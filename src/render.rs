@@ -0,0 +1,355 @@
+//! Drawing the states of the simulation days as pictures, for debugging and
+//! for showing off.
+//!
+//! A [`Recorder`] is a tracer keeping every state as a [`Frame`] of
+//! characters, as the day draws it. The frames can be written as text, as PPM
+//! or PBM images, or put together into an animated GIF.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+use crate::error::AocError;
+use crate::trace::Tracer;
+
+/**
+The days whose states are pictures: the octopuses on day 11, the folded
+paper on day 13, the image on day 20 and the seafloor on day 25.
+ */
+pub const DAYS: [u8; 4] = [11, 13, 20, 25];
+
+/**
+The colours of the cells, by the index [`colour_index`] gives them.
+Unknown characters are grey.
+ */
+const PALETTE: [[u8; 3]; 16] = [
+    [0, 0, 0],        // background: '.' and ' '
+    [255, 255, 255],  // '#' and '*'
+    [255, 240, 120],  // '0', an octopus that just flashed
+    [20, 20, 60],     // '1' to '9', brighter as the octopus charges
+    [35, 35, 80],
+    [50, 50, 100],
+    [65, 65, 120],
+    [80, 80, 140],
+    [95, 95, 160],
+    [110, 110, 180],
+    [125, 125, 200],
+    [140, 140, 220],
+    [255, 140, 0],    // '>'
+    [0, 160, 255],    // 'v'
+    [128, 128, 128],  // anything else
+    [0, 0, 0],        // unused, to make a power of two
+];
+
+/**
+The index in the palette of a cell drawn as `c`.
+
+# Examples
+```
+use aoc2021::render::colour_index;
+
+assert_eq!(0, colour_index('.'));
+assert_eq!(1, colour_index('#'));
+assert_eq!(2, colour_index('0'));
+assert_eq!(11, colour_index('9'));
+assert_eq!(14, colour_index('x'));
+```
+ */
+pub fn colour_index(c: char) -> u8 {
+    match c {
+        '.' | ' ' => 0,
+        '#' | '*' => 1,
+        '0'..='9' => 2 + c as u8 - b'0',
+        '>' => 12,
+        'v' => 13,
+        _ => 14,
+    }
+}
+
+/**
+One state of a simulation, as rows of characters. Short rows are padded
+with blanks.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub step: usize,
+    pub action: String,
+    cells: Vec<Vec<char>>,
+    cols: usize,
+}
+
+impl Frame {
+    /**
+    A frame of the state drawn as `text`.
+
+    # Examples
+    ```
+    let frame = aoc2021::render::Frame::new(1, "east", "..>\nv");
+    assert_eq!((2, 3), (frame.rows(), frame.cols()));
+    assert_eq!("..>\nv  \n", frame.to_ascii());
+    ```
+     */
+    pub fn new(step: usize, action: &str, text: &str) -> Frame {
+        let mut cells: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        let cols = cells.iter().map(Vec::len).max().unwrap_or(0);
+        for row in cells.iter_mut() {
+            row.resize(cols, ' ');
+        }
+        Frame { step, action: action.to_string(), cells, cols }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /**
+    The character at a cell, or a blank outside the frame.
+     */
+    fn get(&self, row: usize, col: usize) -> char {
+        self.cells.get(row).and_then(|r| r.get(col)).copied().unwrap_or(' ')
+    }
+
+    /**
+    The frame as text, each row ending in a newline.
+     */
+    pub fn to_ascii(&self) -> String {
+        self.cells.iter()
+            .map(|row| row.iter().collect::<String>() + "\n")
+            .collect()
+    }
+
+    /**
+    The palette index of every pixel, with each cell drawn as a square
+    `scale` pixels wide, padded with background to `rows` by `cols` cells.
+     */
+    fn indexed_pixels(&self, scale: usize, rows: usize, cols: usize) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(rows * cols * scale * scale);
+        for row in 0..rows {
+            let line: Vec<u8> = (0..cols * scale)
+                .map(|x| colour_index(self.get(row, x / scale)))
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        pixels
+    }
+
+    /**
+    The frame as a binary PPM colour image, with each cell drawn as a square
+    `scale` pixels wide.
+
+    # Examples
+    ```
+    let frame = aoc2021::render::Frame::new(1, "enhance", "#.");
+    let ppm = frame.to_ppm(1);
+    assert_eq!(b"P6\n2 1\n255\n\xff\xff\xff\x00\x00\x00".to_vec(), ppm);
+    assert_eq!(b"P6\n4 2\n255\n".len() + 4 * 2 * 3, frame.to_ppm(2).len());
+    ```
+     */
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.cols * scale, self.rows() * scale).into_bytes();
+        for index in self.indexed_pixels(scale, self.rows(), self.cols) {
+            ppm.extend_from_slice(&PALETTE[index as usize]);
+        }
+        ppm
+    }
+
+    /**
+    The frame as a binary PBM black and white image, with every cell that is
+    not background drawn black, each as a square `scale` pixels wide.
+
+    # Examples
+    ```
+    let frame = aoc2021::render::Frame::new(1, "fold along x=5", "#.#\n.#.");
+    assert_eq!(b"P4\n3 2\n\xa0\x40".to_vec(), frame.to_pbm(1));
+    ```
+     */
+    pub fn to_pbm(&self, scale: usize) -> Vec<u8> {
+        let width = self.cols * scale;
+        let mut pbm = format!("P4\n{} {}\n", width, self.rows() * scale).into_bytes();
+        for line in self.indexed_pixels(scale, self.rows(), self.cols).chunks(width.max(1)) {
+            // rows are packed 8 pixels to a byte, first pixel in the high bit
+            for byte in line.chunks(8) {
+                pbm.push(byte.iter().enumerate()
+                    .filter(|(_, index)| **index != 0)
+                    .fold(0, |bits, (bit, _)| bits | 0x80 >> bit));
+            }
+        }
+        pbm
+    }
+}
+
+/**
+A tracer keeping every state it is shown as a frame.
+
+# Examples
+```
+# #[cfg(feature = "day25")] {
+use aoc2021::render::Recorder;
+
+let mut recorder = Recorder::new();
+aoc2021::day25::solve_part1_traced(">.v\n", &mut recorder).unwrap();
+let frames = recorder.into_frames();
+assert_eq!(4, frames.len());
+assert_eq!((2, "south"), (frames[3].step, frames[3].action.as_str()));
+assert_eq!(".>v\n", frames[3].to_ascii());
+# }
+```
+ */
+#[derive(Default)]
+pub struct Recorder {
+    frames: Vec<Frame>,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder { frames: Vec::new() }
+    }
+
+    pub fn into_frames(self) -> Vec<Frame> {
+        self.frames
+    }
+}
+
+impl Tracer for Recorder {
+    fn step(&mut self, step: usize, action: &str, state: &dyn std::fmt::Display) {
+        self.frames.push(Frame::new(step, action, &state.to_string()));
+    }
+}
+
+/**
+How frames are written: as text, PPM or PBM files in a directory, or as
+one animated GIF.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ascii,
+    Ppm,
+    Pbm,
+    Gif,
+}
+
+impl ImageFormat {
+    /**
+    The extension of a file in this format.
+     */
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ascii => "txt",
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pbm => "pbm",
+            ImageFormat::Gif => "gif",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<ImageFormat, AocError> {
+        match s {
+            "ascii" => Ok(ImageFormat::Ascii),
+            "ppm" => Ok(ImageFormat::Ppm),
+            "pbm" => Ok(ImageFormat::Pbm),
+            "gif" => Ok(ImageFormat::Gif),
+            _ => Err(AocError::Usage(format!("unknown image format '{}' (expected ascii, ppm, pbm or gif)", s))),
+        }
+    }
+}
+
+/**
+Write each frame to its own numbered file in `dir`, such as
+`frame0001.ppm`, creating the directory if need be. GIFs are written with
+[`write_gif`] instead, since they hold every frame.
+
+# Examples
+```
+use aoc2021::render::{write_frames, Frame, ImageFormat};
+
+let dir = std::env::temp_dir().join("aoc2021_write_frames_doctest");
+let frames = vec![Frame::new(1, "east", ".>"), Frame::new(1, "south", ">.")];
+write_frames(&frames, ImageFormat::Ascii, 1, &dir).unwrap();
+assert_eq!(">.\n", std::fs::read_to_string(dir.join("frame0002.txt")).unwrap());
+# std::fs::remove_dir_all(&dir).unwrap();
+```
+ */
+pub fn write_frames(frames: &[Frame], format: ImageFormat, scale: usize, dir: &Path) -> Result<(), AocError> {
+    let dir_name = dir.display().to_string();
+    fs::create_dir_all(dir).map_err(|e| AocError::io(&dir_name, e))?;
+    let digits = frames.len().to_string().len().max(4);
+    for (ndx, frame) in frames.iter().enumerate() {
+        let path = dir.join(format!("frame{:0width$}.{}", ndx + 1, format.extension(), width = digits));
+        let contents = match format {
+            ImageFormat::Ascii => frame.to_ascii().into_bytes(),
+            ImageFormat::Ppm => frame.to_ppm(scale),
+            ImageFormat::Pbm => frame.to_pbm(scale),
+            ImageFormat::Gif => {
+                return Err(AocError::Usage(String::from("a GIF holds every frame in one file")));
+            },
+        };
+        fs::write(&path, contents).map_err(|e| AocError::io(&path.display().to_string(), e))?;
+    }
+    Ok(())
+}
+
+/**
+Write the frames as an animated GIF that loops forever, showing each frame
+for `delay` hundredths of a second. Frames smaller than the largest are
+padded with background at the bottom and right.
+
+# Examples
+```
+use aoc2021::render::{write_gif, Frame};
+
+let frames = vec![Frame::new(1, "enhance", "#"), Frame::new(2, "enhance", "#.#\n.#.")];
+let mut gif = Vec::new();
+write_gif(&frames, 2, 10, &mut gif).unwrap();
+let mut options = gif::DecodeOptions::new();
+options.set_color_output(gif::ColorOutput::Indexed);
+let mut decoder = options.read_info(gif.as_slice()).unwrap();
+assert_eq!((6, 4), (decoder.width(), decoder.height()));
+let first = decoder.read_next_frame().unwrap().unwrap();
+assert_eq!(10, first.delay);
+// the first frame is padded with background
+assert_eq!(&[1, 1, 0, 0, 0, 0], &first.buffer[..6]);
+let second = decoder.read_next_frame().unwrap().unwrap();
+assert_eq!(&[1, 1, 0, 0, 1, 1], &second.buffer[..6]);
+assert!(decoder.read_next_frame().unwrap().is_none());
+```
+ */
+pub fn write_gif(frames: &[Frame], scale: usize, delay: u16, out: impl Write) -> Result<(), AocError> {
+    let rows = frames.iter().map(Frame::rows).max().unwrap_or(0);
+    let cols = frames.iter().map(Frame::cols).max().unwrap_or(0);
+    let too_large = || AocError::Usage(format!("{}x{} pixels is too large for a GIF", cols * scale, rows * scale));
+    let width = u16::try_from(cols * scale).map_err(|_| too_large())?;
+    let height = u16::try_from(rows * scale).map_err(|_| too_large())?;
+    let gif_error = |e: gif::EncodingError| match e {
+        gif::EncodingError::Io(e) => AocError::io("<gif>", e),
+        e => AocError::io("<gif>", io::Error::other(e)),
+    };
+    let palette: Vec<u8> = PALETTE.iter().flatten().copied().collect();
+    let mut encoder = gif::Encoder::new(out, width, height, &palette).map_err(gif_error)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(gif_error)?;
+    for frame in frames {
+        let mut image = gif::Frame::from_indexed_pixels(width, height, frame.indexed_pixels(scale, rows, cols), None);
+        image.delay = delay;
+        encoder.write_frame(&image).map_err(gif_error)?;
+    }
+    Ok(())
+}
+
+/**
+Write the frames as an animated GIF file, as [`write_gif`] does.
+ */
+pub fn save_gif(frames: &[Frame], scale: usize, delay: u16, file: &Path) -> Result<(), AocError> {
+    let name = file.display().to_string();
+    let out = File::create(file).map_err(|e| AocError::io(&name, e))?;
+    let mut out = BufWriter::new(out);
+    write_gif(frames, scale, delay, &mut out).map_err(|e| e.in_file(&name))?;
+    out.flush().map_err(|e| AocError::io(&name, e))
+}
@@ -0,0 +1,496 @@
+//! Running every registered day at once, timing the parse and each part.
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+#[cfg(feature = "cache")]
+use crate::cache::{Cache, Entry, Key};
+use crate::error::AocError;
+use crate::input;
+use crate::params::Params;
+use crate::solution::{Answer, Day, Registry, Solution};
+use crate::trace::{NoTrace, Tracer};
+
+/**
+The result of running one part of a day. `part` is `None` when the day's
+input could not be read or parsed, so no part was run. A `cached` answer was
+found in the cache rather than solved, and its solve time is the time spent
+looking it up.
+ */
+#[derive(Debug)]
+pub struct Outcome {
+    pub day: u8,
+    pub part: Option<u8>,
+    pub answer: Result<Answer, AocError>,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    pub cached: bool,
+}
+
+impl Outcome {
+    pub fn is_ok(&self) -> bool {
+        self.answer.is_ok()
+    }
+
+    /**
+    The outcome as a JSON object of the day, part, answer and the
+    milliseconds spent parsing and solving. A failure has a null answer and
+    an `error` message, and a cached answer is marked `"cached": true`.
+
+    # Examples
+    ```
+    use std::time::Duration;
+    use aoc2021::error::AocError;
+    use aoc2021::runner::Outcome;
+    use aoc2021::solution::Answer;
+
+    let mut outcome = Outcome {
+        day: 1,
+        part: Some(2),
+        answer: Ok(Answer::Number(5)),
+        parse_time: Duration::from_micros(1500),
+        solve_time: Duration::from_micros(20),
+        cached: false,
+    };
+    assert_eq!(r#"{"answer":5,"day":1,"elapsed_ms":1.52,"part":2}"#, outcome.to_json().to_string());
+    outcome.answer = Err(AocError::Unsolvable(String::from("no fold instructions")));
+    assert_eq!(r#"{"answer":null,"day":1,"elapsed_ms":1.52,"error":"no solution: no fold instructions","part":2}"#,
+        outcome.to_json().to_string());
+    outcome.answer = Ok(Answer::Number(5));
+    outcome.cached = true;
+    assert_eq!(r#"{"answer":5,"cached":true,"day":1,"elapsed_ms":1.52,"part":2}"#, outcome.to_json().to_string());
+    ```
+     */
+    pub fn to_json(&self) -> Value {
+        let elapsed = (self.parse_time + self.solve_time).as_secs_f64() * 1000.0;
+        let mut value = json!({
+            "day": self.day,
+            "part": self.part,
+            "answer": self.answer.as_ref().ok().map(Answer::to_json),
+            "elapsed_ms": elapsed,
+        });
+        if let Err(e) = &self.answer {
+            value["error"] = json!(e.to_string());
+        }
+        if self.cached {
+            value["cached"] = json!(true);
+        }
+        value
+    }
+}
+
+/**
+How answers are printed: as text for people, or as JSON for programs.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Format, AocError> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(AocError::Usage(format!("unknown format '{}', expected text or json", s))),
+        }
+    }
+}
+
+/**
+The input file for a day in a directory of inputs, named like `day07.txt`.
+
+# Examples
+```
+let file = aoc2021::runner::input_file("test_inputs".as_ref(), 7);
+assert_eq!(std::path::Path::new("test_inputs/day07.txt"), file);
+```
+ */
+pub fn input_file(dir: &Path, day: u8) -> std::path::PathBuf {
+    dir.join(format!("day{:02}.txt", day))
+}
+
+/**
+Parse a day's input once, then run each of its parts.
+
+# Examples
+```
+# #[cfg(feature = "day01")] {
+let registry = aoc2021::registry();
+let outcomes = aoc2021::runner::run_day(registry.get(1).unwrap(), "test_inputs/day01.txt");
+assert_eq!(2, outcomes.len());
+assert_eq!(Some(2), outcomes[1].part);
+assert_eq!(5, outcomes[1].answer.as_ref().unwrap().to_string().parse::<i32>().unwrap());
+
+let outcomes = aoc2021::runner::run_day(registry.get(1).unwrap(), "this is not a file");
+assert_eq!(1, outcomes.len());
+assert_eq!(None, outcomes[0].part);
+assert!(!outcomes[0].is_ok());
+# }
+```
+ */
+pub fn run_day(day: &Day, file: &str) -> Vec<Outcome> {
+    run_day_in(day, file, |_| None, |_, part, solution| day.solve_parsed(part, solution))
+}
+
+/**
+Run each part of a day like [`run_day`], taking the answers from the cache
+when every part is there, and saving those it solves.
+
+# Examples
+```
+# #[cfg(feature = "day25")] {
+use aoc2021::cache::Cache;
+
+let dir = std::env::temp_dir().join(format!("aoc2021-run-day-{}", std::process::id()));
+let cache = Cache::new(&dir);
+let registry = aoc2021::registry();
+let day25 = registry.get(25).unwrap();
+let outcomes = aoc2021::runner::run_day_cached(day25, "test_inputs/day25.txt", &cache);
+assert!(outcomes.iter().all(|outcome| !outcome.cached));
+let outcomes = aoc2021::runner::run_day_cached(day25, "test_inputs/day25.txt", &cache);
+assert_eq!(1, outcomes.len());
+assert!(outcomes[0].cached);
+assert_eq!("58", outcomes[0].answer.as_ref().unwrap().to_string());
+std::fs::remove_dir_all(&dir).unwrap();
+# }
+```
+ */
+#[cfg(feature = "cache")]
+pub fn run_day_cached(day: &Day, file: &str, cache: &Cache) -> Vec<Outcome> {
+    let key = |text: &str, part| Key::new(day.day, part, text, &Params::new());
+    run_day_in(day, file,
+        |text| cached_day(day.day, cache, |part| key(text, part)),
+        |text, part, solution| solve_and_save(day, part, solution, cache, key(text, part)))
+}
+
+/**
+Run each part of a day, taking the outcomes from `cached` when it has them
+for the input's text, and otherwise solving each part with `solve`.
+ */
+fn run_day_in(
+    day: &Day,
+    file: &str,
+    cached: impl FnOnce(&str) -> Option<Vec<Outcome>>,
+    solve: impl Fn(&str, u8, &dyn Solution) -> Result<Answer, AocError>,
+) -> Vec<Outcome> {
+    let failed = |e: AocError, parse_time| vec![Outcome {
+        day: day.day,
+        part: None,
+        answer: Err(e),
+        parse_time,
+        solve_time: Duration::ZERO,
+        cached: false,
+    }];
+    let text = match input::read_input(file) {
+        Ok(text) => text,
+        Err(e) => return failed(e, Duration::ZERO),
+    };
+    if let Some(outcomes) = cached(&text) {
+        return outcomes;
+    }
+    let start = Instant::now();
+    let parsed = day.parse_str(&text);
+    let parse_time = start.elapsed();
+    let solution = match parsed {
+        Ok(solution) => solution,
+        Err(e) => return failed(e.in_file(input::name(file)), parse_time),
+    };
+    (1..=solution.parts())
+        .map(|part| {
+            let start = Instant::now();
+            let answer = solve(&text, part, solution.as_ref());
+            Outcome {
+                day: day.day,
+                part: Some(part),
+                answer,
+                parse_time,
+                solve_time: start.elapsed(),
+                cached: false,
+            }
+        })
+        .collect()
+}
+
+/**
+The outcomes of every part of a day from the cache, if all of them are
+there. A cached answer knows how many parts its day has.
+ */
+#[cfg(feature = "cache")]
+fn cached_day(day: u8, cache: &Cache, key: impl Fn(u8) -> Key) -> Option<Vec<Outcome>> {
+    let start = Instant::now();
+    let first = cache.get(&key(1))?;
+    let mut answers = vec![first.answer];
+    for part in 2..=first.parts {
+        answers.push(cache.get(&key(part))?.answer);
+    }
+    let lookup_time = start.elapsed() / answers.len() as u32;
+    Some(answers.into_iter()
+        .zip(1..)
+        .map(|(answer, part)| Outcome {
+            day,
+            part: Some(part),
+            answer: Ok(answer),
+            parse_time: Duration::ZERO,
+            solve_time: lookup_time,
+            cached: true,
+        })
+        .collect())
+}
+
+/**
+Solve a part, saving the answer in the cache.
+ */
+#[cfg(feature = "cache")]
+fn solve_and_save(day: &Day, part: u8, solution: &dyn Solution, cache: &Cache, key: Key) -> Result<Answer, AocError> {
+    let answer = day.solve_parsed(part, solution)?;
+    cache.put(&key, &Entry { answer: answer.clone(), parts: solution.parts() })?;
+    Ok(answer)
+}
+
+/**
+Parse a day's input, configure it with `params`, and run one of its parts.
+
+# Examples
+```
+# #[cfg(all(feature = "day01", feature = "day15"))] {
+use aoc2021::params::Params;
+
+let registry = aoc2021::registry();
+let outcome = aoc2021::runner::run_part(registry.get(1).unwrap(), 2, "test_inputs/day01.txt", &Params::new());
+assert_eq!(Some(2), outcome.part);
+assert_eq!("5", outcome.answer.unwrap().to_string());
+let outcome = aoc2021::runner::run_part(registry.get(1).unwrap(), 3, "test_inputs/day01.txt", &Params::new());
+assert!(!outcome.is_ok());
+
+let mut params = Params::new();
+params.add("tiles=1").unwrap();
+let outcome = aoc2021::runner::run_part(registry.get(15).unwrap(), 2, "test_inputs/day15.txt", &params);
+assert_eq!("40", outcome.answer.unwrap().to_string());
+let outcome = aoc2021::runner::run_part(registry.get(1).unwrap(), 1, "test_inputs/day01.txt", &params);
+assert_eq!("unknown parameter tiles (this day has no parameters)", outcome.answer.unwrap_err().to_string());
+# }
+```
+ */
+pub fn run_part(day: &Day, part: u8, file: &str, params: &Params) -> Outcome {
+    run_part_traced(day, part, file, params, &mut NoTrace)
+}
+
+/**
+Run one part of a day like [`run_part`], reporting each step of the
+simulation to `tracer`.
+
+# Examples
+```
+# #[cfg(feature = "day11")] {
+use aoc2021::params::Params;
+
+let registry = aoc2021::registry();
+let mut steps = 0;
+let mut tracer = |_: usize, _: &str, _: &dyn std::fmt::Display| steps += 1;
+let outcome = aoc2021::runner::run_part_traced(registry.get(11).unwrap(), 1, "test_inputs/day11.txt", &Params::new(), &mut tracer);
+assert_eq!("1656", outcome.answer.unwrap().to_string());
+assert_eq!(100, steps);
+# }
+```
+ */
+pub fn run_part_traced(day: &Day, part: u8, file: &str, params: &Params, tracer: &mut dyn Tracer) -> Outcome {
+    let start = Instant::now();
+    let parsed = day.parse(file).and_then(|mut solution| {
+        solution.configure(params)?;
+        Ok(solution)
+    });
+    let parse_time = start.elapsed();
+    let start = Instant::now();
+    let answer = parsed.and_then(|solution| day.solve_traced(part, solution.as_ref(), tracer));
+    Outcome {
+        day: day.day,
+        part: Some(part),
+        answer,
+        parse_time,
+        solve_time: start.elapsed(),
+        cached: false,
+    }
+}
+
+/**
+Run one part of a day like [`run_part`], taking the answer from the cache
+if it is there, and otherwise saving it there once solved. The answer is
+kept under the input and the parameters, so other parameters solve it
+afresh. Failing to save the answer fails the part.
+
+# Examples
+```
+# #[cfg(feature = "day06")] {
+use aoc2021::cache::Cache;
+use aoc2021::params::Params;
+use aoc2021::runner::run_part_cached;
+
+let dir = std::env::temp_dir().join(format!("aoc2021-run-part-{}", std::process::id()));
+let cache = Cache::new(&dir);
+let registry = aoc2021::registry();
+let day06 = registry.get(6).unwrap();
+let outcome = run_part_cached(day06, 1, "test_inputs/day06.txt", &Params::new(), &cache);
+assert!(!outcome.cached);
+let outcome = run_part_cached(day06, 1, "test_inputs/day06.txt", &Params::new(), &cache);
+assert!(outcome.cached);
+assert_eq!("5934", outcome.answer.unwrap().to_string());
+
+let mut params = Params::new();
+params.add("part1_days=18").unwrap();
+let outcome = run_part_cached(day06, 1, "test_inputs/day06.txt", &params, &cache);
+assert!(!outcome.cached);
+assert_eq!("26", outcome.answer.unwrap().to_string());
+std::fs::remove_dir_all(&dir).unwrap();
+# }
+```
+ */
+#[cfg(feature = "cache")]
+pub fn run_part_cached(day: &Day, part: u8, file: &str, params: &Params, cache: &Cache) -> Outcome {
+    let outcome = |answer, parse_time, solve_time, cached| Outcome {
+        day: day.day,
+        part: Some(part),
+        answer,
+        parse_time,
+        solve_time,
+        cached,
+    };
+    let text = match input::read_input(file) {
+        Ok(text) => text,
+        Err(e) => return outcome(Err(e), Duration::ZERO, Duration::ZERO, false),
+    };
+    let key = Key::new(day.day, part, &text, params);
+    let start = Instant::now();
+    if let Some(entry) = cache.get(&key) {
+        return outcome(Ok(entry.answer), Duration::ZERO, start.elapsed(), true);
+    }
+    let start = Instant::now();
+    let parsed = day.parse_str(&text).map_err(|e| e.in_file(input::name(file))).and_then(|mut solution| {
+        solution.configure(params)?;
+        Ok(solution)
+    });
+    let parse_time = start.elapsed();
+    let start = Instant::now();
+    let answer = parsed.and_then(|solution| solve_and_save(day, part, solution.as_ref(), cache, key));
+    outcome(answer, parse_time, start.elapsed(), false)
+}
+
+/**
+Run every registered day on its `dayNN.txt` input in `dir`. A day with no
+input file fails.
+
+# Examples
+```
+let registry = aoc2021::registry();
+let outcomes = aoc2021::runner::run_all(&registry, "this is not a directory".as_ref());
+assert_eq!(registry.days().count(), outcomes.len());
+assert!(outcomes.iter().all(|o| !o.is_ok()));
+```
+ */
+pub fn run_all(registry: &Registry, dir: &Path) -> Vec<Outcome> {
+    registry.days()
+        .flat_map(|day| run_day(day, &input_file(dir, day.day).to_string_lossy()))
+        .collect()
+}
+
+/**
+Run every registered day like [`run_all`], through the cache.
+ */
+#[cfg(feature = "cache")]
+pub fn run_all_cached(registry: &Registry, dir: &Path, cache: &Cache) -> Vec<Outcome> {
+    registry.days()
+        .flat_map(|day| run_day_cached(day, &input_file(dir, day.day).to_string_lossy(), cache))
+        .collect()
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.3}", d.as_secs_f64() * 1000.0)
+}
+
+/**
+Print the outcomes as a table of answers and times in milliseconds, then a
+summary line. Answers that span several lines, and errors, are printed below
+their row.
+
+# Examples
+```
+use std::time::Duration;
+use aoc2021::runner::{write_table, Outcome};
+use aoc2021::solution::Answer;
+
+let outcomes = vec![Outcome {
+    day: 1,
+    part: Some(2),
+    answer: Ok(Answer::Number(5)),
+    parse_time: Duration::from_micros(1500),
+    solve_time: Duration::from_micros(20),
+    cached: false,
+}];
+let mut out = Vec::new();
+write_table(&outcomes, &mut out).unwrap();
+let out = String::from_utf8(out).unwrap();
+assert_eq!("day part answer   parse ms   solve ms", out.lines().next().unwrap());
+assert_eq!("  1    2 5           1.500      0.020", out.lines().nth(1).unwrap());
+```
+ */
+pub fn write_table(outcomes: &[Outcome], out: &mut impl Write) -> io::Result<()> {
+    let rows: Vec<(String, Vec<String>)> = outcomes.iter()
+        .map(|outcome| match &outcome.answer {
+            Ok(Answer::Lines(lines)) => (format!("({} lines)", lines.len()), lines.clone()),
+            Ok(answer) => (answer.to_string(), Vec::new()),
+            Err(e) => (String::from("FAILED"), vec![format!("error: {}", e)]),
+        })
+        .collect();
+    let width = rows.iter().map(|(answer, _)| answer.chars().count()).max().unwrap_or(0).max(6);
+    writeln!(out, "{:>3} {:>4} {:<width$} {:>10} {:>10}", "day", "part", "answer", "parse ms", "solve ms")?;
+    for (outcome, (answer, below)) in outcomes.iter().zip(rows) {
+        let part = outcome.part.map_or(String::from("-"), |p| p.to_string());
+        writeln!(out, "{:>3} {:>4} {:<width$} {:>10} {:>10}", outcome.day, part, answer,
+            millis(outcome.parse_time), millis(outcome.solve_time))?;
+        for line in below {
+            writeln!(out, "         {}", line)?;
+        }
+    }
+    let total: Duration = outcomes.iter().map(|o| o.solve_time).sum();
+    let failures = outcomes.iter().filter(|o| !o.is_ok()).count();
+    write!(out, "{} parts, {} failed, {} ms solving", outcomes.len(), failures, millis(total))?;
+    match outcomes.iter().filter(|o| o.cached).count() {
+        0 => writeln!(out),
+        cached => writeln!(out, ", {} from the cache", cached),
+    }
+}
+
+/**
+Print the outcomes as JSON, one object per line.
+
+# Examples
+```
+use std::time::Duration;
+use aoc2021::runner::{write_json, Outcome};
+use aoc2021::solution::Answer;
+
+let outcomes = vec![Outcome {
+    day: 13,
+    part: Some(2),
+    answer: Ok(Answer::Lines(vec![String::from("* *"), String::from(" * ")])),
+    parse_time: Duration::ZERO,
+    solve_time: Duration::from_millis(2),
+    cached: false,
+}];
+let mut out = Vec::new();
+write_json(&outcomes, &mut out).unwrap();
+assert_eq!("{\"answer\":[\"* *\",\" * \"],\"day\":13,\"elapsed_ms\":2.0,\"part\":2}\n",
+    String::from_utf8(out).unwrap());
+```
+ */
+pub fn write_json(outcomes: &[Outcome], out: &mut impl Write) -> io::Result<()> {
+    for outcome in outcomes {
+        writeln!(out, "{}", outcome.to_json())?;
+    }
+    Ok(())
+}
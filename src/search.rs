@@ -0,0 +1,131 @@
+//! Finding the cheapest path through a weighted graph, with Dijkstra's
+//! algorithm or A*.
+//!
+//! The graph is never built up front: a search starts from one state and
+//! asks a successor function for the states one step away, and what each
+//! step costs. Any `Hash + Eq` type can be a state.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/**
+The cheapest path found by a search: its total cost, and every state along
+it from the start to the goal.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path<S, C> {
+    pub cost: C,
+    pub states: Vec<S>,
+}
+
+/**
+A state reached so far: the cheapest known cost of reaching it, and the
+state it was reached from along that path.
+ */
+struct Reached<S, C> {
+    state: S,
+    cost: C,
+    parent: Option<usize>,
+}
+
+/**
+Find the cheapest path from `start` to a state satisfying `is_goal`, where
+`successors` gives the states one step from a state and the cost of each
+step. Costs must not be negative. Returns `None` when no goal can be
+reached.
+
+# Examples
+```
+use aoc2021::search::dijkstra;
+
+// from 0, step +1 for a cost of 1 or *2 for a cost of 1
+let path = dijkstra(0, |n: &u32| [(n + 1, 1), (n * 2, 1)], |n| *n == 10).unwrap();
+assert_eq!(5, path.cost);
+assert_eq!(vec![0, 1, 2, 4, 5, 10], path.states);
+assert!(dijkstra(0, |n: &u32| [((n + 1).min(5), 1)], |n| *n == 10).is_none());
+```
+ */
+pub fn dijkstra<S, C, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, successors, |_| C::default(), is_goal)
+}
+
+/**
+Find the cheapest path like [`dijkstra`], exploring first the states that
+`heuristic` estimates are closest to a goal. The estimate must never be more
+than the real cost of reaching a goal, or the path found may not be the
+cheapest.
+
+# Examples
+```
+use aoc2021::search::astar;
+
+// walk a number line to 10, guided by the distance still to go
+let path = astar(0i32, |n| [(n - 1, 1), (n + 1, 1)], |n| (10 - n).abs(), |n| *n == 10).unwrap();
+assert_eq!(10, path.cost);
+assert_eq!(11, path.states.len());
+```
+ */
+pub fn astar<S, C, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+    S: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut reached = vec![Reached { state: start.clone(), cost: C::default(), parent: None }];
+    let mut index = HashMap::from([(start, 0)]);
+    let mut queue = BinaryHeap::from([Reverse((heuristic(&reached[0].state), 0, C::default()))]);
+    while let Some(Reverse((_, ndx, cost))) = queue.pop() {
+        // a cheaper way here was found after this entry was queued
+        if cost > reached[ndx].cost {
+            continue;
+        }
+        if is_goal(&reached[ndx].state) {
+            return Some(path(&reached, ndx));
+        }
+        for (next, step) in successors(&reached[ndx].state) {
+            let next_cost = cost + step;
+            let next_ndx = match index.get(&next) {
+                Some(&n) if reached[n].cost <= next_cost => continue,
+                Some(&n) => {
+                    reached[n].cost = next_cost;
+                    reached[n].parent = Some(ndx);
+                    n
+                },
+                None => {
+                    index.insert(next.clone(), reached.len());
+                    reached.push(Reached { state: next, cost: next_cost, parent: Some(ndx) });
+                    reached.len() - 1
+                },
+            };
+            queue.push(Reverse((next_cost + heuristic(&reached[next_ndx].state), next_ndx, next_cost)));
+        }
+    }
+    None
+}
+
+fn path<S: Clone, C: Copy>(reached: &[Reached<S, C>], goal: usize) -> Path<S, C> {
+    let mut states = Vec::new();
+    let mut ndx = Some(goal);
+    while let Some(n) = ndx {
+        states.push(reached[n].state.clone());
+        ndx = reached[n].parent;
+    }
+    states.reverse();
+    Path { cost: reached[goal].cost, states }
+}
@@ -0,0 +1,288 @@
+//! The common interface implemented by every day's puzzle, and the registry
+//! used to look a day up by number.
+
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use serde_json::{json, Value};
+use crate::error::AocError;
+use crate::input;
+use crate::params::Params;
+use crate::trace::{NoTrace, Tracer};
+
+/**
+The answer to one part of a puzzle. A `Record` is an answer made of several
+named values, such as a position.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+    Lines(Vec<String>),
+    Record(Vec<(String, Answer)>),
+}
+
+impl Answer {
+    /**
+    The answer as a JSON value. Numbers too large for JSON readers to hold
+    exactly are written as strings, lines as an array of strings, and records
+    as objects.
+
+    # Examples
+    ```
+    use aoc2021::solution::Answer;
+
+    assert_eq!("7", Answer::Number(7).to_json().to_string());
+    assert_eq!("\"340282366920938463463374607431768211455\"",
+        Answer::from(u128::MAX).to_json().to_string());
+    assert_eq!("[\"* *\",\" * \"]",
+        Answer::Lines(vec![String::from("* *"), String::from(" * ")]).to_json().to_string());
+    let record = Answer::Record(vec![(String::from("depth"), Answer::Number(10))]);
+    assert_eq!("{\"depth\":10}", record.to_json().to_string());
+    ```
+     */
+    pub fn to_json(&self) -> Value {
+        match self {
+            Answer::Number(n) => match (i64::try_from(*n), u64::try_from(*n)) {
+                (Ok(n), _) => json!(n),
+                (_, Ok(n)) => json!(n),
+                _ => json!(n.to_string()),
+            },
+            Answer::Text(s) => json!(s),
+            Answer::Lines(v) => json!(v),
+            Answer::Record(fields) => Value::Object(fields.iter()
+                .map(|(name, value)| (name.clone(), value.to_json()))
+                .collect()),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Lines(v) => write!(f, "{}", v.join("\n")),
+            Answer::Record(fields) => {
+                for (ndx, (name, value)) in fields.iter().enumerate() {
+                    let sep = if ndx == 0 { "" } else { ", " };
+                    write!(f, "{}{}: {}", sep, name, value)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Answer {
+                    Answer::Number(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, u32, i64, u64, usize, i128);
+
+impl From<u128> for Answer {
+    fn from(n: u128) -> Answer {
+        match i128::try_from(n) {
+            Ok(n) => Answer::Number(n),
+            Err(_) => Answer::Text(n.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl From<num_bigint::BigUint> for Answer {
+    fn from(n: num_bigint::BigUint) -> Answer {
+        match i128::try_from(&n) {
+            Ok(n) => Answer::Number(n),
+            Err(_) => Answer::Text(n.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Answer {
+        Answer::Text(s)
+    }
+}
+
+impl From<Vec<String>> for Answer {
+    fn from(v: Vec<String>) -> Answer {
+        Answer::Lines(v)
+    }
+}
+
+/**
+A day's puzzle: the parsed input, and the two parts solved from it.
+ */
+pub trait Solution {
+    /**
+    Parse the text of the puzzle input.
+     */
+    fn parse(input: &str) -> Result<Self, AocError>
+    where
+        Self: Sized;
+
+    /**
+    Parse the puzzle input from a reader. Most puzzles read the whole input
+    and parse its text; those that can parse it as it arrives read it a
+    line at a time instead.
+     */
+    fn read(reader: &mut dyn BufRead) -> Result<Self, AocError>
+    where
+        Self: Sized,
+    {
+        Self::parse(&input::read_all(reader)?)
+    }
+
+    fn part1(&self) -> Result<Answer, AocError>;
+
+    fn part2(&self) -> Result<Answer, AocError>;
+
+    /**
+    The number of parts this puzzle has.
+     */
+    fn parts(&self) -> u8 {
+        2
+    }
+
+    /**
+    Override the puzzle's constants with parameters. Puzzles without any
+    constants worth changing take no parameters.
+     */
+    fn configure(&mut self, params: &Params) -> Result<(), AocError> {
+        params.check_known(&[])
+    }
+
+    /**
+    Solve a part, reporting every step of the puzzle's simulation to
+    `tracer`. Puzzles that are not simulations have no steps to report.
+     */
+    fn traced(&self, part: u8, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        let _ = tracer;
+        if part == 1 { self.part1() } else { self.part2() }
+    }
+}
+
+type Parsed = Result<Box<dyn Solution>, AocError>;
+
+/**
+A day in the registry.
+ */
+pub struct Day {
+    pub day: u8,
+    parse: fn(&str) -> Parsed,
+    read: fn(&mut dyn BufRead) -> Parsed,
+}
+
+impl Day {
+    /**
+    Load the puzzle input for this day from the file, or from stdin when
+    the file is `-`.
+     */
+    pub fn parse(&self, file: &str) -> Result<Box<dyn Solution>, AocError> {
+        input::from_reader(file, self.read)
+    }
+
+    /**
+    Parse the text of the puzzle input for this day.
+     */
+    pub fn parse_str(&self, input: &str) -> Result<Box<dyn Solution>, AocError> {
+        (self.parse)(input)
+    }
+
+    /**
+    Solve one part of this day's puzzle from the file, or from stdin when
+    the file is `-`.
+     */
+    pub fn solve(&self, part: u8, file: &str) -> Result<Answer, AocError> {
+        self.solve_parsed(part, self.parse(file)?.as_ref())
+    }
+
+    /**
+    Solve one part of this day's puzzle from the text of the input.
+     */
+    pub fn solve_str(&self, part: u8, input: &str) -> Result<Answer, AocError> {
+        self.solve_parsed(part, self.parse_str(input)?.as_ref())
+    }
+
+    /**
+    Solve one part of this day's puzzle from a reader.
+     */
+    pub fn solve_reader(&self, part: u8, reader: impl Read) -> Result<Answer, AocError> {
+        self.solve_parsed(part, (self.read)(&mut BufReader::new(reader))?.as_ref())
+    }
+
+    pub(crate) fn solve_parsed(&self, part: u8, solution: &dyn Solution) -> Result<Answer, AocError> {
+        self.solve_traced(part, solution, &mut NoTrace)
+    }
+
+    pub(crate) fn solve_traced(&self, part: u8, solution: &dyn Solution, tracer: &mut dyn Tracer) -> Result<Answer, AocError> {
+        match part {
+            1 => solution.traced(1, tracer),
+            2 if solution.parts() >= 2 => solution.traced(2, tracer),
+            _ => Err(AocError::UnknownPart(format!("{} for day {}", part, self.day))),
+        }
+    }
+}
+
+/**
+The days with a solution, in order.
+
+# Examples
+```
+# #[cfg(all(feature = "day01", feature = "day06", feature = "day25"))] {
+use aoc2021::solution::{Answer, Registry};
+
+let registry = aoc2021::registry();
+assert!(registry.get(1).is_some());
+assert!(registry.get(26).is_none());
+assert_eq!(Answer::Number(7), registry.get(1).unwrap().solve(1, "test_inputs/day01.txt").unwrap());
+assert!(registry.get(1).unwrap().solve(1, "this is not a file").is_err());
+assert!(registry.get(25).unwrap().solve(2, "test_inputs/day25.txt").is_err());
+
+let day06 = registry.get(6).unwrap();
+assert_eq!(Answer::Number(5934), day06.solve_str(1, "3,4,3,1,2\n").unwrap());
+assert_eq!(Answer::Number(5934), day06.solve_reader(1, "3,4,3,1,2\n".as_bytes()).unwrap());
+# }
+```
+ */
+#[derive(Default)]
+pub struct Registry {
+    days: Vec<Day>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry { days: Vec::new() }
+    }
+
+    /**
+    Sign up a day's solution.
+     */
+    pub fn register<S: Solution + 'static>(&mut self, day: u8) {
+        if self.get(day).is_some() {
+            panic!("day {} registered twice", day);
+        }
+        self.days.push(Day {
+            day,
+            parse: |input| Ok(Box::new(S::parse(input)?)),
+            read: |reader| Ok(Box::new(S::read(reader)?)),
+        });
+        self.days.sort_by_key(|d| d.day);
+    }
+
+    pub fn get(&self, day: u8) -> Option<&Day> {
+        self.days.iter().find(|d| d.day == day)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &Day> {
+        self.days.iter()
+    }
+}
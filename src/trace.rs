@@ -0,0 +1,94 @@
+//! Following a simulation step by step, to see where a wrong answer goes
+//! wrong.
+//!
+//! The simulation days call a [`Tracer`] after every step with the state the
+//! step left behind. The state is only drawn if the tracer looks at it, so
+//! [`NoTrace`] costs next to nothing.
+
+use std::fmt;
+use std::io::{self, Write};
+
+/**
+Something watching a simulation.
+ */
+pub trait Tracer {
+    /**
+    Called after each step, counting from 1, with what the step did and the
+    state after it.
+     */
+    fn step(&mut self, step: usize, action: &str, state: &dyn fmt::Display);
+}
+
+/**
+Any closure taking the same arguments as [`Tracer::step`] is a tracer.
+
+# Examples
+```
+use aoc2021::trace::Tracer;
+
+let mut steps = Vec::new();
+let mut tracer = |step: usize, action: &str, state: &dyn std::fmt::Display| {
+    steps.push(format!("{} {} {}", step, action, state));
+};
+tracer.step(1, "add", &5);
+assert_eq!(vec!["1 add 5"], steps);
+```
+ */
+impl<F> Tracer for F
+where
+    F: FnMut(usize, &str, &dyn fmt::Display),
+{
+    fn step(&mut self, step: usize, action: &str, state: &dyn fmt::Display) {
+        self(step, action, state)
+    }
+}
+
+/**
+A tracer that ignores every step.
+ */
+pub struct NoTrace;
+
+impl Tracer for NoTrace {
+    fn step(&mut self, _step: usize, _action: &str, _state: &dyn fmt::Display) {}
+}
+
+/**
+A tracer writing a readable log of every step. The first error writing the
+log stops it, and is returned by [`Log::finish`].
+
+# Examples
+```
+use aoc2021::trace::{Log, Tracer};
+
+let mut log = Log::new(Vec::new());
+log.step(1, "east", &"..>\n>..");
+assert_eq!("step 1 (east):\n..>\n>..\n\n", String::from_utf8(log.finish().unwrap()).unwrap());
+```
+ */
+pub struct Log<W: Write> {
+    out: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Log<W> {
+    pub fn new(out: W) -> Log<W> {
+        Log { out, error: None }
+    }
+
+    pub fn finish(self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.out),
+        }
+    }
+}
+
+impl<W: Write> Tracer for Log<W> {
+    fn step(&mut self, step: usize, action: &str, state: &dyn fmt::Display) {
+        if self.error.is_none() {
+            if let Err(e) = writeln!(self.out, "step {} ({}):\n{}\n", step, action, state) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
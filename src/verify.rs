@@ -0,0 +1,278 @@
+//! Checking answers against a file of known-good answers, such as the
+//! answers to our real inputs.
+//!
+//! The answers file is TOML with one `[[answer]]` table per check. Inputs
+//! are found relative to the answers file. Answers that span several lines,
+//! like day 13's letters, use a multi-line string.
+//!
+//! ```toml
+//! [[answer]]
+//! day = 1
+//! part = 1
+//! input = "day01.txt"
+//! expected = 7
+//!
+//! [[answer]]
+//! day = 13
+//! part = 2
+//! input = "day13.txt"
+//! expected = """
+//! *****
+//! *   *
+//! """
+//! ```
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use toml::Value;
+use crate::error::AocError;
+use crate::input;
+use crate::solution::Registry;
+
+/**
+One answer to check: running `part` of `day` on `input` should give
+`expected`.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: u8,
+    pub input: PathBuf,
+    pub expected: String,
+}
+
+/**
+The outcome of a check. A failure holds the difference between the expected
+and the actual answer.
+ */
+#[derive(Debug)]
+pub enum Verdict {
+    Pass,
+    Fail(Vec<String>),
+    Error(AocError),
+}
+
+/**
+The line of the `n`th `[[name]]` table in a TOML document, for reporting
+errors in it. The TOML parser keeps no positions for values, so headers are
+found by hand: whitespace, quotes around the name and trailing comments are
+ignored, and so are the lines of multi-line strings.
+ */
+pub(crate) fn table_line(text: &str, name: &str, n: usize) -> usize {
+    let forms = [format!("[[{}]]", name), format!("[[\"{}\"]]", name), format!("[['{}']]", name)];
+    let mut in_string = None;
+    let mut headers = text.lines().enumerate().filter(|(_, line)| {
+        if let Some(delimiter) = in_string {
+            if line.matches(delimiter).count() % 2 == 1 {
+                in_string = None;
+            }
+            return false;
+        }
+        in_string = ["\"\"\"", "'''"].into_iter().find(|delimiter| line.matches(delimiter).count() % 2 == 1);
+        let header: String = line.split('#').next().unwrap_or("").split_whitespace().collect();
+        forms.contains(&header)
+    });
+    headers.nth(n).map_or(1, |(ndx, _)| ndx + 1)
+}
+
+/**
+Parse a TOML document, reporting errors at their line and column.
+ */
+pub(crate) fn parse_toml(text: &str) -> Result<Value, AocError> {
+    text.parse().map_err(|e: toml::de::Error| {
+        let (line, column) = e.line_col().unwrap_or((0, 0));
+        // the location is already in the error, so drop it from the message
+        let message = e.to_string();
+        let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(m, _)| m);
+        AocError::parse(line + 1, column + 1, message)
+    })
+}
+
+/**
+Parse the checks in an answers file. Relative input paths are taken from
+`dir`.
+
+# Examples
+```
+use aoc2021::verify::parse_answers;
+
+let text = "[[answer]]\nday = 1\npart = 2\ninput = \"day01.txt\"\nexpected = 5\n\n\
+    [[answer]]\nday = 13\npart = 2\ninput = \"/inputs/day13.txt\"\nexpected = \"\"\"\n* *\n * \n\"\"\"\n";
+let checks = parse_answers(text, "answers".as_ref()).unwrap();
+assert_eq!(2, checks.len());
+assert_eq!(std::path::Path::new("answers/day01.txt"), checks[0].input);
+assert_eq!("5", checks[0].expected);
+assert_eq!(std::path::Path::new("/inputs/day13.txt"), checks[1].input);
+assert_eq!("* *\n * \n", checks[1].expected);
+
+let e = parse_answers("[[answer]]\nday = 1\n\n[[answer]]\nday = 2\npart = 1\n", ".".as_ref()).unwrap_err();
+assert_eq!("line 1, column 1: answer is missing \"part\"", e.to_string());
+let text = "[[answer]]\nday = 1\npart = 1\ninput = \"a\"\nexpected = \"\"\"\n[[answer]]\n\"\"\"\n\n\
+    [[ answer ]] # the second\nday = 2\npart = 1\n";
+let e = parse_answers(text, ".".as_ref()).unwrap_err();
+assert_eq!("line 9, column 1: answer is missing \"input\"", e.to_string());
+```
+ */
+pub fn parse_answers(text: &str, dir: &Path) -> Result<Vec<Check>, AocError> {
+    let value = parse_toml(text)?;
+    let answers = match value.get("answer") {
+        Some(Value::Array(answers)) => answers,
+        Some(_) => return Err(AocError::parse(1, 1, "expected [[answer]] tables")),
+        None => return Ok(Vec::new()),
+    };
+    answers.iter()
+        .enumerate()
+        .map(|(ndx, answer)| {
+            let error = |reason: String| AocError::parse(table_line(text, "answer", ndx), 1, reason);
+            let field = |name: &str| answer.get(name)
+                .ok_or_else(|| error(format!("answer is missing \"{}\"", name)));
+            let number = |name: &str| match field(name)? {
+                Value::Integer(n) => u8::try_from(*n).map_err(|_| error(format!("{} {} is out of range", name, n))),
+                _ => Err(error(format!("\"{}\" must be a number", name))),
+            };
+            let day = number("day")?;
+            let part = number("part")?;
+            let input = match field("input")? {
+                Value::String(s) => dir.join(s),
+                _ => return Err(error(String::from("\"input\" must be a string"))),
+            };
+            let expected = match field("expected")? {
+                Value::String(s) => s.clone(),
+                Value::Integer(n) => n.to_string(),
+                _ => return Err(error(String::from("\"expected\" must be a number or a string"))),
+            };
+            Ok(Check { day, part, input, expected })
+        })
+        .collect()
+}
+
+/**
+Load the checks in an answers file.
+
+# Examples
+```
+let checks = aoc2021::verify::load_answers("test_inputs/answers.toml").unwrap();
+assert_eq!(std::path::Path::new("test_inputs/day01.txt"), checks[0].input);
+```
+ */
+pub fn load_answers(file: &str) -> Result<Vec<Check>, AocError> {
+    let dir = Path::new(file).parent().unwrap_or_else(|| Path::new(""));
+    input::from_file(file, |text| parse_answers(text, dir))
+}
+
+/**
+The lines of an answer, ignoring trailing whitespace and trailing blank lines.
+ */
+fn answer_lines(answer: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = answer.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+}
+
+/**
+Compare an answer with the expected answer, line by line. Returns nothing
+when they match; otherwise every line, with the expected line marked `-`
+and the actual line marked `+` wherever they differ.
+
+# Examples
+```
+use aoc2021::verify::diff;
+
+assert!(diff("7", "7").is_none());
+assert!(diff("* *\n * \n", "* *\n *").is_none());
+assert_eq!(Some(vec![String::from("- 7"), String::from("+ 8")]), diff("7", "8"));
+assert_eq!(
+    Some(vec![String::from("  * *"), String::from("-  *"), String::from("+ **")]),
+    diff("* *\n *\n", "* *\n**"),
+);
+```
+ */
+pub fn diff(expected: &str, actual: &str) -> Option<Vec<String>> {
+    let expected = answer_lines(expected);
+    let actual = answer_lines(actual);
+    if expected == actual {
+        return None;
+    }
+    let mut lines = Vec::new();
+    for ndx in 0..expected.len().max(actual.len()) {
+        match (expected.get(ndx), actual.get(ndx)) {
+            (Some(e), Some(a)) if e == a => lines.push(format!("  {}", e)),
+            (e, a) => {
+                lines.extend(e.map(|e| format!("- {}", e)));
+                lines.extend(a.map(|a| format!("+ {}", a)));
+            },
+        }
+    }
+    Some(lines)
+}
+
+/**
+Run one check.
+
+# Examples
+```
+# #[cfg(feature = "day01")] {
+use aoc2021::verify::{check, Check, Verdict};
+
+let registry = aoc2021::registry();
+let mut c = Check { day: 1, part: 1, input: "test_inputs/day01.txt".into(), expected: String::from("7") };
+assert!(matches!(check(&registry, &c), Verdict::Pass));
+c.expected = String::from("8");
+assert!(matches!(check(&registry, &c), Verdict::Fail(_)));
+c.day = 26;
+assert!(matches!(check(&registry, &c), Verdict::Error(_)));
+# }
+```
+ */
+pub fn check(registry: &Registry, check: &Check) -> Verdict {
+    let answer = registry.get(check.day)
+        .ok_or_else(|| AocError::UnknownDay(check.day.to_string()))
+        .and_then(|day| day.solve(check.part, &check.input.to_string_lossy()));
+    match answer {
+        Ok(answer) => match diff(&check.expected, &answer.to_string()) {
+            None => Verdict::Pass,
+            Some(lines) => Verdict::Fail(lines),
+        },
+        Err(e) => Verdict::Error(e),
+    }
+}
+
+/**
+Print a pass/fail line for each check, with the difference for each
+failure, then a summary. Returns whether every check passed.
+
+# Examples
+```
+use aoc2021::verify::{write_report, Check, Verdict};
+
+let c = Check { day: 1, part: 1, input: "day01.txt".into(), expected: String::from("7") };
+let results = vec![(c.clone(), Verdict::Pass), (c, Verdict::Fail(vec![String::from("- 7"), String::from("+ 8")]))];
+let mut out = Vec::new();
+assert!(!write_report(&results, &mut out).unwrap());
+assert_eq!("PASS day  1 part 1\nFAIL day  1 part 1\n       - 7\n       + 8\n1 passed, 1 failed\n",
+    String::from_utf8(out).unwrap());
+```
+ */
+pub fn write_report(results: &[(Check, Verdict)], out: &mut impl Write) -> io::Result<bool> {
+    let mut passed = 0;
+    for (check, verdict) in results {
+        let name = format!("day {:>2} part {}", check.day, check.part);
+        match verdict {
+            Verdict::Pass => {
+                passed += 1;
+                writeln!(out, "PASS {}", name)?;
+            },
+            Verdict::Fail(lines) => {
+                writeln!(out, "FAIL {}", name)?;
+                for line in lines {
+                    writeln!(out, "       {}", line)?;
+                }
+            },
+            Verdict::Error(e) => writeln!(out, "FAIL {}: error: {}", name, e)?,
+        }
+    }
+    writeln!(out, "{} passed, {} failed", passed, results.len() - passed)?;
+    Ok(passed == results.len())
+}